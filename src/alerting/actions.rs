@@ -0,0 +1,128 @@
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{self, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::alerting::rules::{Action, HttpEndpoint, Rule};
+use crate::model::{AppState, Severity};
+use crate::util::time::format_utc;
+
+/// Connect and read/write timeout for HTTP posts.
+const POST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Carry out one of a fired rule's actions.
+///
+/// Popup and bell are flags on the recorded alert, handled by the TUI.
+/// Commands and posts run in the background; a failed post is reported
+/// as an alert of its own.
+pub fn run(action: &Action, rule: &Rule, message: &str, state: &Arc<Mutex<AppState>>) -> Result<(), String> {
+    match action {
+        Action::Popup | Action::Bell => Ok(()),
+        Action::Log(path) => {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+            let message = escape_controls(message);
+            writeln!(file, "{} [{}] {message}", format_utc(SystemTime::now()), rule.severity)
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+        }
+        Action::Command(command) => {
+            let mut child = process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .env("WIFIMONITOR_RULE", &rule.name)
+                .env("WIFIMONITOR_SEVERITY", rule.severity.to_string())
+                .env("WIFIMONITOR_MESSAGE", message)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("Failed to run '{command}': {e}"))?;
+            // Reap it without waiting here
+            thread::spawn(move || child.wait());
+            Ok(())
+        }
+        Action::Post(endpoint) => {
+            let endpoint = endpoint.clone();
+            let body = serde_json::json!({
+                "rule": rule.name,
+                "severity": rule.severity.to_string(),
+                "message": message,
+            })
+            .to_string();
+            let (name, state) = (rule.name.clone(), Arc::clone(state));
+            thread::spawn(move || {
+                if let Err(e) = post(&endpoint, &body) {
+                    let message = format!("Rule '{name}': {e}");
+                    state.lock().unwrap().raise_alert(Severity::Warning, message, None);
+                }
+            });
+            Ok(())
+        }
+    }
+}
+
+/// Minimal HTTP/1.1 POST of a JSON body; any 2xx status is success.
+fn post(endpoint: &HttpEndpoint, body: &str) -> Result<(), String> {
+    let url = format!("http://{}{}", endpoint.addr, endpoint.path);
+    let fail = |e: std::io::Error| format!("POST {url} failed: {e}");
+    let addr = endpoint
+        .addr
+        .to_socket_addrs()
+        .map_err(fail)?
+        .next()
+        .ok_or_else(|| format!("POST {url} failed: no address for {}", endpoint.addr))?;
+    let mut stream = TcpStream::connect_timeout(&addr, POST_TIMEOUT).map_err(fail)?;
+    stream.set_read_timeout(Some(POST_TIMEOUT)).map_err(fail)?;
+    stream.set_write_timeout(Some(POST_TIMEOUT)).map_err(fail)?;
+
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        endpoint.path,
+        endpoint.addr,
+        body.len()
+    )
+    .map_err(fail)?;
+
+    let mut status_line = String::new();
+    BufReader::new(&stream).read_line(&mut status_line).map_err(fail)?;
+    match status_line.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!("POST {url} returned '{}'", status_line.trim())),
+    }
+}
+
+/// `message` with control characters escaped. Messages quote SSIDs, and an
+/// SSID holding a newline must not be able to forge log lines.
+fn escape_controls(message: &str) -> String {
+    let mut escaped = String::with_capacity(message.len());
+    for c in message.chars() {
+        if c.is_control() {
+            escaped.extend(c.escape_default());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_messages_stay_on_one_line() {
+        let message = "rogue: \"evil\n2024-01-01T00:00:00Z [info] all clear\r\" (02:00:00:00:00:01)";
+        assert_eq!(
+            escape_controls(message),
+            "rogue: \"evil\\n2024-01-01T00:00:00Z [info] all clear\\r\" (02:00:00:00:00:01)"
+        );
+        assert_eq!(escape_controls("café \u{1b}[2J"), "café \\u{1b}[2J");
+    }
+}
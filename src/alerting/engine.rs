@@ -0,0 +1,452 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alerting::actions;
+use crate::alerting::rules::{Action, Rule, Target, Trigger, Value};
+use crate::model::{AccessPoint, Alert, AppState, Severity, Station};
+use crate::util::time::format_duration;
+
+/// How often rules are evaluated.
+const EVAL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Evaluate `rules` against the shared state on a separate thread, recording
+/// an alert and running the rule's actions each time one fires.
+pub fn start_alerting(rules: Vec<Rule>, state: Arc<Mutex<AppState>>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut engine = Engine::new(rules);
+        loop {
+            let firings = {
+                let mut st = state.lock().unwrap();
+                if !st.is_running() {
+                    break;
+                }
+                let now = st.now();
+                let firings = engine.evaluate(&st);
+                for firing in &firings {
+                    let rule = &engine.rules[firing.rule];
+                    st.push_alert(Alert {
+                        popup: rule.actions.contains(&Action::Popup),
+                        bell: rule.actions.contains(&Action::Bell),
+                        ..Alert::new(rule.severity, firing.message.clone(), firing.bssid, now)
+                    });
+                }
+                firings
+            };
+
+            // Commands and HTTP posts must not hold up capture
+            for firing in &firings {
+                let rule = &engine.rules[firing.rule];
+                for action in &rule.actions {
+                    if let Err(e) = actions::run(action, rule, &firing.message, &state) {
+                        let message = format!("Rule '{}': {e}", rule.name);
+                        state.lock().unwrap().raise_alert(Severity::Warning, message, None);
+                    }
+                }
+            }
+            thread::sleep(EVAL_INTERVAL);
+        }
+    })
+}
+
+/// A rule firing for one subject.
+pub struct Firing {
+    /// Index into the engine's rules.
+    pub rule: usize,
+    pub message: String,
+    pub bssid: Option<[u8; 6]>,
+}
+
+/// Rules plus what each has seen so far, for durations and edge triggering.
+pub struct Engine {
+    pub rules: Vec<Rule>,
+    states: Vec<RuleState>,
+}
+
+#[derive(Default)]
+struct RuleState {
+    /// When the trigger started holding, per subject.
+    since: HashMap<String, Instant>,
+    /// Subjects the rule has fired for; cleared when the trigger stops
+    /// holding so the rule can fire again.
+    fired: HashSet<String>,
+    /// Subjects present at the last evaluation, with their labels and BSSIDs.
+    present: HashMap<String, (String, Option<[u8; 6]>)>,
+    /// Subjects heard before the first evaluation, which `new` ignores.
+    baseline: Option<HashSet<String>>,
+}
+
+/// Something a rule's condition is checked against.
+struct Subject<'a> {
+    key: String,
+    label: String,
+    bssid: Option<[u8; 6]>,
+    last_seen: Option<Instant>,
+    kind: SubjectKind<'a>,
+}
+
+enum SubjectKind<'a> {
+    Ap(&'a AccessPoint),
+    Station(&'a Station),
+    Channel(u8),
+    Capture,
+}
+
+impl Engine {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        let states = rules.iter().map(|_| RuleState::default()).collect();
+        Self { rules, states }
+    }
+
+    /// Check every rule once, returning the ones that fired.
+    pub fn evaluate(&mut self, st: &AppState) -> Vec<Firing> {
+        let now = st.now();
+        let mut firings = Vec::new();
+        for (index, (rule, state)) in self.rules.iter().zip(self.states.iter_mut()).enumerate() {
+            let subjects = subjects(&rule.condition.target, st);
+            let fired = match &rule.condition.trigger {
+                Trigger::Holds(_) => evaluate_holds(rule, state, &subjects, st, now),
+                Trigger::Disappears => evaluate_disappears(rule, state, &subjects, now),
+            };
+            firings.extend(fired.into_iter().map(|(message, bssid)| Firing {
+                rule: index,
+                message,
+                bssid,
+            }));
+        }
+        firings
+    }
+}
+
+fn evaluate_holds(
+    rule: &Rule,
+    state: &mut RuleState,
+    subjects: &[Subject],
+    st: &AppState,
+    now: Instant,
+) -> Vec<(String, Option<[u8; 6]>)> {
+    let Trigger::Holds(comparisons) = &rule.condition.trigger else {
+        return Vec::new();
+    };
+    let new_only = rule.condition.new_only;
+    if new_only && state.baseline.is_none() {
+        state.baseline = Some(subjects.iter().map(|subject| subject.key.clone()).collect());
+        return Vec::new();
+    }
+
+    let mut fired = Vec::new();
+    for subject in subjects {
+        if state.baseline.as_ref().is_some_and(|baseline| baseline.contains(&subject.key)) {
+            continue;
+        }
+        let values: Option<Vec<Value>> = comparisons
+            .iter()
+            .map(|cmp| field(subject, cmp.field, st).filter(|value| cmp.matches(value)))
+            .collect();
+        let Some(values) = values else {
+            state.since.remove(&subject.key);
+            // `new` fires once per subject, ever
+            if !new_only {
+                state.fired.remove(&subject.key);
+            }
+            continue;
+        };
+        let start = *state.since.entry(subject.key.clone()).or_insert(now);
+        let held = now.saturating_duration_since(start);
+        if held >= rule.condition.duration && state.fired.insert(subject.key.clone()) {
+            let detail = comparisons
+                .iter()
+                .zip(&values)
+                .map(|(cmp, value)| format!("{} {value}", cmp.field))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut message = format!("{}: {} {detail}", rule.name, subject.label);
+            if !rule.condition.duration.is_zero() {
+                message.push_str(&format!(" for {}", format_duration(held)));
+            }
+            fired.push((message, subject.bssid));
+        }
+    }
+
+    // Forget subjects that went away so they start over if they return
+    let current: HashSet<&String> = subjects.iter().map(|subject| &subject.key).collect();
+    state.since.retain(|key, _| current.contains(key));
+    if !new_only {
+        state.fired.retain(|key| current.contains(key));
+    }
+    fired
+}
+
+/// Fires when a subject heard at the last evaluation is gone: expired from
+/// the model or, with a duration, silent for that long.
+fn evaluate_disappears(
+    rule: &Rule,
+    state: &mut RuleState,
+    subjects: &[Subject],
+    now: Instant,
+) -> Vec<(String, Option<[u8; 6]>)> {
+    let window = rule.condition.duration;
+    let mut present = HashMap::new();
+    for subject in subjects {
+        let fresh = window.is_zero()
+            || subject
+                .last_seen
+                .is_some_and(|seen| now.saturating_duration_since(seen) < window);
+        if !fresh {
+            continue;
+        }
+        // An SSID is gone only once none of its APs are heard
+        match &rule.condition.target {
+            Target::Ssid(ssid) => present.insert(ssid.clone(), (format!("\"{ssid}\""), None)),
+            _ => present.insert(subject.key.clone(), (subject.label.clone(), subject.bssid)),
+        };
+    }
+
+    let mut fired: Vec<(String, Option<[u8; 6]>)> = state
+        .present
+        .iter()
+        .filter(|(key, _)| !present.contains_key(*key))
+        .map(|(_, (label, bssid))| {
+            let mut message = format!("{}: {label} disappeared", rule.name);
+            if !window.is_zero() {
+                message.push_str(&format!(" (silent for {})", format_duration(window)));
+            }
+            (message, *bssid)
+        })
+        .collect();
+    fired.sort();
+    state.present = present;
+    fired
+}
+
+fn subjects<'a>(target: &Target, st: &'a AppState) -> Vec<Subject<'a>> {
+    let ap_subject = |ap: &'a AccessPoint| Subject {
+        key: ap.bssid_str(),
+        label: format!("{} ({})", ap.display_ssid(), ap.bssid_str()),
+        bssid: Some(ap.bssid),
+        last_seen: Some(ap.last_seen),
+        kind: SubjectKind::Ap(ap),
+    };
+    let station_subject = |sta: &'a Station| Subject {
+        key: sta.mac_str(),
+        label: format!("station {}", sta.mac_str()),
+        bssid: sta.bssid,
+        last_seen: Some(sta.last_seen),
+        kind: SubjectKind::Station(sta),
+    };
+    match target {
+        Target::Ap(None) => st.access_points.values().map(ap_subject).collect(),
+        Target::Ap(Some(bssid)) => st.access_points.get(bssid).map(ap_subject).into_iter().collect(),
+        Target::Ssid(ssid) => st
+            .access_points
+            .values()
+            .filter(|ap| &ap.ssid == ssid)
+            .map(ap_subject)
+            .collect(),
+        Target::Station(None) => st.stations.values().map(station_subject).collect(),
+        Target::Station(Some(mac)) => st.stations.get(mac).map(station_subject).into_iter().collect(),
+        &Target::Channel(ch) => vec![Subject {
+            key: format!("channel {ch}"),
+            label: format!("channel {ch}"),
+            bssid: None,
+            last_seen: None,
+            kind: SubjectKind::Channel(ch),
+        }],
+        Target::Capture => vec![Subject {
+            key: "capture".to_string(),
+            label: "capture".to_string(),
+            bssid: None,
+            last_seen: None,
+            kind: SubjectKind::Capture,
+        }],
+    }
+}
+
+/// Current value of a rule field, or None if it isn't known yet.
+fn field(subject: &Subject, name: &str, st: &AppState) -> Option<Value> {
+    let number = |n: f64| Some(Value::Number(n));
+    let flag = |b: bool| Some(Value::Number(if b { 1.0 } else { 0.0 }));
+    match (&subject.kind, name) {
+        (SubjectKind::Ap(ap), "signal") => number(ap.signal_dbm as f64),
+        (SubjectKind::Ap(ap), "snr") => number(ap.snr_db()? as f64),
+        (SubjectKind::Ap(ap), "channel") => number(ap.channel as f64),
+        (SubjectKind::Ap(ap), "clients") => number(st.client_count(&ap.bssid) as f64),
+        (SubjectKind::Ap(ap), "retry") => number(ap.link.retry_percent()?),
+        (SubjectKind::Ap(ap), "loss") => number(ap.link.loss_percent()?),
+        (SubjectKind::Ap(ap), "missed") => {
+            ap.beacon_interval_tu?;
+            number(ap.missed_beacon_percent().unwrap_or(0.0))
+        }
+        (SubjectKind::Ap(ap), "utilization") => number(ap.bss_load?.utilization_percent()),
+        (SubjectKind::Ap(ap), "encryption") => Some(Value::Text(ap.encryption.to_string())),
+        (SubjectKind::Ap(ap), "ssid") => Some(Value::Text(ap.ssid.clone())),
+        (SubjectKind::Ap(ap), "hidden") => flag(ap.hidden),
+        (SubjectKind::Ap(ap), "karma") => flag(ap.is_karma()),
+        (SubjectKind::Station(sta), "signal") => number(sta.signal_dbm as f64),
+        (SubjectKind::Station(sta), "packets") => number(sta.packet_count as f64),
+        (SubjectKind::Station(sta), "retry") => number(sta.link.retry_percent()?),
+        (SubjectKind::Station(sta), "exposed") => flag(sta.is_exposed()),
+        (SubjectKind::Channel(ch), "pps") => number(st.channel_rate(*ch)?),
+        (SubjectKind::Channel(ch), "retry") => number(st.channel_link.get(ch)?.retry_percent()?),
+        (SubjectKind::Channel(ch), "aps") => {
+            number(st.access_points.values().filter(|ap| ap.channel == *ch).count() as f64)
+        }
+        (SubjectKind::Capture, "pps") => number(st.current_packet_rate() as f64),
+        (SubjectKind::Capture, "dropped") => number(st.pcap_stats.dropped as f64),
+        (SubjectKind::Capture, "aps") => number(st.access_points.len() as f64),
+        (SubjectKind::Capture, "stations") => number(st.stations.len() as f64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::alerting::rules;
+    use crate::model::EncryptionType;
+    use crate::util::clock::Clock;
+
+    const CORE: [u8; 6] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+
+    fn engine(text: &str) -> Engine {
+        Engine::new(rules::parse(text).unwrap())
+    }
+
+    fn add_ap(st: &mut AppState, bssid: [u8; 6], ssid: &str, signal_dbm: i8) {
+        let mut ap = AccessPoint::new(bssid, st.now(), SystemTime::now());
+        ap.ssid = ssid.to_string();
+        ap.signal_dbm = signal_dbm;
+        st.access_points.insert(bssid, ap);
+    }
+
+    fn messages(engine: &mut Engine, st: &AppState) -> Vec<String> {
+        engine.evaluate(st).into_iter().map(|firing| firing.message).collect()
+    }
+
+    #[test]
+    fn holds_for_fires_once_and_rearms() {
+        let t0 = Instant::now();
+        let mut st = AppState::new("wlan0".to_string(), Clock::fixed(t0));
+        let mut engine = engine("rule Core AP weak\n when ap 00:11:22:33:44:55 signal < -75 for 30s");
+        add_ap(&mut st, CORE, "Corp", -80);
+
+        assert!(messages(&mut engine, &st).is_empty());
+        st.clock.set(t0 + Duration::from_secs(29));
+        assert!(messages(&mut engine, &st).is_empty());
+        st.clock.set(t0 + Duration::from_secs(30));
+        assert_eq!(
+            messages(&mut engine, &st),
+            ["Core AP weak: Corp (00:11:22:33:44:55) signal -80 for 0m 30s"]
+        );
+        st.clock.set(t0 + Duration::from_secs(31));
+        assert!(messages(&mut engine, &st).is_empty());
+
+        // Recovering re-arms the rule and restarts the duration
+        st.access_points.get_mut(&CORE).unwrap().signal_dbm = -60;
+        assert!(messages(&mut engine, &st).is_empty());
+        st.access_points.get_mut(&CORE).unwrap().signal_dbm = -81;
+        st.clock.set(t0 + Duration::from_secs(40));
+        assert!(messages(&mut engine, &st).is_empty());
+        st.clock.set(t0 + Duration::from_secs(70));
+        assert_eq!(messages(&mut engine, &st).len(), 1);
+    }
+
+    #[test]
+    fn new_ignores_the_baseline_and_fires_once() {
+        let t0 = Instant::now();
+        let mut st = AppState::new("wlan0".to_string(), Clock::fixed(t0));
+        let mut engine = engine("rule Strong open AP\n when new ap encryption == open and signal > -60");
+        add_ap(&mut st, CORE, "Cafe", -50);
+        st.access_points.get_mut(&CORE).unwrap().encryption = EncryptionType::Open;
+
+        // Already heard when the rules were loaded
+        assert!(messages(&mut engine, &st).is_empty());
+        assert!(messages(&mut engine, &st).is_empty());
+
+        let other = [0x02, 0, 0, 0, 0, 1];
+        add_ap(&mut st, other, "Free", -50);
+        assert!(messages(&mut engine, &st).is_empty());
+        st.access_points.get_mut(&other).unwrap().encryption = EncryptionType::Open;
+        assert_eq!(
+            messages(&mut engine, &st),
+            ["Strong open AP: Free (02:00:00:00:00:01) encryption Open, signal -50"]
+        );
+
+        // Dropping out of the condition doesn't re-arm a `new` rule
+        st.access_points.get_mut(&other).unwrap().signal_dbm = -70;
+        assert!(messages(&mut engine, &st).is_empty());
+        st.access_points.get_mut(&other).unwrap().signal_dbm = -50;
+        assert!(messages(&mut engine, &st).is_empty());
+    }
+
+    #[test]
+    fn ssid_disappears_once_every_ap_is_silent() {
+        let t0 = Instant::now();
+        let mut st = AppState::new("wlan0".to_string(), Clock::fixed(t0));
+        let mut engine = engine("rule Guest network down\n when ssid \"Guest WiFi\" disappears for 60s");
+        let other = [0x02, 0, 0, 0, 0, 1];
+        add_ap(&mut st, CORE, "Guest WiFi", -60);
+        add_ap(&mut st, other, "Guest WiFi", -60);
+        assert!(messages(&mut engine, &st).is_empty());
+
+        st.clock.set(t0 + Duration::from_secs(30));
+        st.access_points.get_mut(&other).unwrap().last_seen = st.now();
+        st.clock.set(t0 + Duration::from_secs(61));
+        assert!(messages(&mut engine, &st).is_empty());
+
+        st.clock.set(t0 + Duration::from_secs(91));
+        assert_eq!(
+            messages(&mut engine, &st),
+            ["Guest network down: \"Guest WiFi\" disappeared (silent for 1m 0s)"]
+        );
+        st.clock.set(t0 + Duration::from_secs(92));
+        assert!(messages(&mut engine, &st).is_empty());
+
+        // Heard again, then gone again
+        st.access_points.get_mut(&CORE).unwrap().last_seen = st.now();
+        assert!(messages(&mut engine, &st).is_empty());
+        st.clock.set(t0 + Duration::from_secs(152));
+        assert_eq!(messages(&mut engine, &st).len(), 1);
+    }
+
+    #[test]
+    fn ap_disappears_when_it_expires() {
+        let t0 = Instant::now();
+        let mut st = AppState::new("wlan0".to_string(), Clock::fixed(t0));
+        let mut engine = engine("rule Gone\n when ap disappears");
+        add_ap(&mut st, CORE, "Corp", -60);
+        assert!(messages(&mut engine, &st).is_empty());
+        st.access_points.remove(&CORE);
+        assert_eq!(messages(&mut engine, &st), ["Gone: Corp (00:11:22:33:44:55) disappeared"]);
+        assert!(messages(&mut engine, &st).is_empty());
+    }
+
+    #[test]
+    fn channel_pps_counts_only_time_on_the_channel() {
+        let t0 = Instant::now();
+        let mut st = AppState::new("wlan0".to_string(), Clock::fixed(t0));
+        let mut engine = engine("rule Busy channel 6\n when channel 6 pps > 2000 for 10s");
+        st.set_current_channel(6, false);
+
+        // Half of every second on channel 6, hearing 1200 frames there
+        let mut fired = Vec::new();
+        for second in 1..=11 {
+            let start = t0 + Duration::from_secs(second - 1);
+            st.clock.set(start + Duration::from_millis(500));
+            st.set_current_channel(1, false);
+            st.channel_packets.insert(6, 1200 * second);
+            st.channel_packets_this_second.insert(6, 1200);
+            st.clock.set(start + Duration::from_secs(1));
+            st.tick_if_due();
+            st.set_current_channel(6, false);
+            assert_eq!(st.channel_rate(6), Some(2400.0));
+            fired.push(messages(&mut engine, &st));
+        }
+        // The first second over the threshold starts the 10 s
+        assert!(fired[..10].iter().all(Vec::is_empty));
+        assert_eq!(fired[10], ["Busy channel 6: channel 6 pps 2400 for 0m 10s"]);
+    }
+}
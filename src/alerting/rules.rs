@@ -0,0 +1,610 @@
+//! Alert rules, loaded from a text file.
+//!
+//! Each rule starts with `rule <name>` and is followed by one `when` line,
+//! an optional `severity` and any number of actions:
+//!
+//! ```text
+//! # Lines starting with '#' are comments
+//! rule Core AP weak
+//!   when ap 00:11:22:33:44:55 signal < -75 for 30s
+//!   severity warning
+//!   popup
+//!   bell
+//!
+//! rule Guest network down
+//!   when ssid "Guest WiFi" disappears for 60s
+//!   log /var/log/wifimonitor-alerts.log
+//!
+//! rule Strong open AP
+//!   when new ap encryption == open and signal > -60
+//!   severity high
+//!   command notify-send wifimonitor "$WIFIMONITOR_MESSAGE"
+//!
+//! rule Busy channel 6
+//!   when channel 6 pps > 2000 for 10s
+//!   post http://127.0.0.1:8080/alerts
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::model::Severity;
+use crate::util::mac::{format_mac, parse_mac};
+
+/// What a rule's condition is evaluated against.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    /// `ap` (every AP) or `ap <bssid>`.
+    Ap(Option<[u8; 6]>),
+    /// `ssid <name>`: every AP broadcasting it.
+    Ssid(String),
+    /// `station` (every client) or `station <mac>`.
+    Station(Option<[u8; 6]>),
+    /// `channel <n>`
+    Channel(u8),
+    /// `capture`: totals for the whole capture.
+    Capture,
+}
+
+impl Target {
+    /// Fields a comparison on this target may use.
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Target::Ap(_) | Target::Ssid(_) => &[
+                "signal",
+                "snr",
+                "channel",
+                "clients",
+                "retry",
+                "loss",
+                "missed",
+                "utilization",
+                "encryption",
+                "ssid",
+                "hidden",
+                "karma",
+            ],
+            Target::Station(_) => &["signal", "packets", "retry", "exposed"],
+            Target::Channel(_) => &["pps", "retry", "aps"],
+            Target::Capture => &["pps", "dropped", "aps", "stations"],
+        }
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Ap(None) => write!(f, "ap"),
+            Target::Ap(Some(bssid)) => write!(f, "ap {}", format_mac(bssid)),
+            Target::Ssid(ssid) => write!(f, "ssid \"{ssid}\""),
+            Target::Station(None) => write!(f, "station"),
+            Target::Station(Some(mac)) => write!(f, "station {}", format_mac(mac)),
+            Target::Channel(ch) => write!(f, "channel {ch}"),
+            Target::Capture => write!(f, "capture"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "<" => Some(Op::Lt),
+            "<=" => Some(Op::Le),
+            ">" => Some(Op::Gt),
+            ">=" => Some(Op::Ge),
+            "==" | "=" => Some(Op::Eq),
+            "!=" => Some(Op::Ne),
+            _ => None,
+        }
+    }
+}
+
+/// A field value: numbers (booleans are 1/0) or text such as an
+/// encryption type or SSID.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+}
+
+impl Value {
+    fn parse(word: &str) -> Self {
+        match word.to_ascii_lowercase().as_str() {
+            "true" | "yes" => Value::Number(1.0),
+            "false" | "no" => Value::Number(0.0),
+            _ => word
+                .parse()
+                .map(Value::Number)
+                .unwrap_or_else(|_| Value::Text(word.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) if n.fract() == 0.0 => write!(f, "{n:.0}"),
+            Value::Number(n) => write!(f, "{n:.1}"),
+            Value::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// `<field> <op> <value>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub field: &'static str,
+    pub op: Op,
+    pub value: Value,
+}
+
+impl Comparison {
+    /// Text compares case-insensitively and only for (in)equality.
+    pub fn matches(&self, actual: &Value) -> bool {
+        match (actual, &self.value) {
+            (Value::Number(a), Value::Number(b)) => match self.op {
+                Op::Lt => a < b,
+                Op::Le => a <= b,
+                Op::Gt => a > b,
+                Op::Ge => a >= b,
+                Op::Eq => a == b,
+                Op::Ne => a != b,
+            },
+            (Value::Text(a), Value::Text(b)) => match self.op {
+                Op::Eq => a.eq_ignore_ascii_case(b),
+                Op::Ne => !a.eq_ignore_ascii_case(b),
+                _ => false,
+            },
+            _ => self.op == Op::Ne,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trigger {
+    /// Every comparison holds.
+    Holds(Vec<Comparison>),
+    /// Nothing matching the target has been heard.
+    Disappears,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub target: Target,
+    /// Only things first heard after the rules were loaded.
+    pub new_only: bool,
+    pub trigger: Trigger,
+    /// How long the trigger must hold before the rule fires.
+    pub duration: Duration,
+}
+
+/// What to do when a rule fires, besides recording the alert.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Show the alert over the TUI until a key is pressed.
+    Popup,
+    /// Ring the terminal bell.
+    Bell,
+    /// Append a line to a file.
+    Log(PathBuf),
+    /// Run a shell command with the alert in its environment.
+    Command(String),
+    /// POST the alert as JSON to an `http://` endpoint.
+    Post(HttpEndpoint),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpEndpoint {
+    /// `host:port`
+    pub addr: String,
+    pub path: String,
+}
+
+impl HttpEndpoint {
+    fn parse(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("only http:// URLs are supported, got '{url}'"))?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in '{url}'"));
+        }
+        let addr = if host.contains(':') { host.to_string() } else { format!("{host}:80") };
+        Ok(Self { addr, path: path.to_string() })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub name: String,
+    pub condition: Condition,
+    pub severity: Severity,
+    pub actions: Vec<Action>,
+}
+
+/// Read and parse a rules file.
+pub fn load(path: &Path) -> Result<Vec<Rule>, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read rules {}: {e}", path.display()))?;
+    parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// A rule whose lines are still being read.
+struct PendingRule {
+    name: String,
+    condition: Option<Condition>,
+    severity: Severity,
+    actions: Vec<Action>,
+    /// Line of the `rule` keyword, for errors.
+    line_no: usize,
+}
+
+impl PendingRule {
+    fn finish(self) -> Result<Rule, String> {
+        let condition = self
+            .condition
+            .ok_or_else(|| format!("line {}: rule '{}' has no 'when'", self.line_no, self.name))?;
+        Ok(Rule {
+            name: self.name,
+            condition,
+            severity: self.severity,
+            actions: self.actions,
+        })
+    }
+}
+
+pub fn parse(text: &str) -> Result<Vec<Rule>, String> {
+    let mut rules = Vec::new();
+    let mut current: Option<PendingRule> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let at = |e: String| format!("line {line_no}: {e}");
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        if keyword == "rule" {
+            if rest.is_empty() {
+                return Err(at("rule needs a name".to_string()));
+            }
+            if let Some(rule) = current.take() {
+                rules.push(rule.finish()?);
+            }
+            current = Some(PendingRule {
+                name: rest.to_string(),
+                condition: None,
+                severity: Severity::Warning,
+                actions: Vec::new(),
+                line_no,
+            });
+            continue;
+        }
+        let Some(rule) = current.as_mut() else {
+            return Err(at(format!("'{keyword}' outside a rule")));
+        };
+        match keyword {
+            "when" => {
+                if rule.condition.is_some() {
+                    return Err(at("a rule has only one 'when'".to_string()));
+                }
+                rule.condition = Some(parse_condition(&tokenize(rest).map_err(at)?).map_err(at)?);
+            }
+            "severity" => rule.severity = rest.parse().map_err(at)?,
+            "popup" => rule.actions.push(Action::Popup),
+            "bell" => rule.actions.push(Action::Bell),
+            "log" if !rest.is_empty() => rule.actions.push(Action::Log(PathBuf::from(rest))),
+            "command" if !rest.is_empty() => rule.actions.push(Action::Command(rest.to_string())),
+            "post" if !rest.is_empty() => {
+                rule.actions.push(Action::Post(HttpEndpoint::parse(rest).map_err(at)?))
+            }
+            "log" | "command" | "post" => return Err(at(format!("'{keyword}' needs an argument"))),
+            _ => return Err(at(format!("unknown keyword '{keyword}'"))),
+        }
+    }
+    if let Some(rule) = current {
+        rules.push(rule.finish()?);
+    }
+    Ok(rules)
+}
+
+/// Split on whitespace, keeping "double-quoted strings" together.
+fn tokenize(text: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let quoted: String = chars.by_ref().take_while(|&c| c != '"').collect();
+            tokens.push(quoted);
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(word);
+        }
+    }
+    if !text.matches('"').count().is_multiple_of(2) {
+        return Err("unterminated quote".to_string());
+    }
+    Ok(tokens)
+}
+
+/// `[new] <target> (disappears | <field> <op> <value> [and ...]) [for <duration>]`
+fn parse_condition(tokens: &[String]) -> Result<Condition, String> {
+    let mut words: Vec<&str> = tokens.iter().map(String::as_str).collect();
+
+    let mut duration = Duration::ZERO;
+    if let [.., "for", span] = words.as_slice() {
+        duration = parse_duration(span)?;
+        words.truncate(words.len() - 2);
+    }
+    let new_only = words.first() == Some(&"new");
+    if new_only {
+        words.remove(0);
+    }
+
+    let (target, rest) = match words.as_slice() {
+        ["ap", mac, rest @ ..] if parse_mac(mac).is_some() => (Target::Ap(parse_mac(mac)), rest),
+        ["ap", rest @ ..] => (Target::Ap(None), rest),
+        ["ssid", ssid, rest @ ..] => (Target::Ssid(ssid.to_string()), rest),
+        ["station", mac, rest @ ..] if parse_mac(mac).is_some() => (Target::Station(parse_mac(mac)), rest),
+        ["station", rest @ ..] => (Target::Station(None), rest),
+        ["channel", ch, rest @ ..] => match ch.parse::<u8>() {
+            Ok(ch) if ch > 0 => (Target::Channel(ch), rest),
+            _ => return Err(format!("invalid channel '{ch}'")),
+        },
+        ["capture", rest @ ..] => (Target::Capture, rest),
+        [other, ..] => return Err(format!("unknown target '{other}' (ap, ssid, station, channel or capture)")),
+        [] => return Err("empty condition".to_string()),
+    };
+
+    let trigger = match rest {
+        ["disappears"] => {
+            if new_only || matches!(target, Target::Channel(_) | Target::Capture) {
+                return Err(format!("'{target}' can't disappear"));
+            }
+            Trigger::Disappears
+        }
+        [] => return Err(format!("'{target}' needs a comparison or 'disappears'")),
+        _ => {
+            let comparisons = rest
+                .split(|&word| word == "and")
+                .map(|words| parse_comparison(&target, words))
+                .collect::<Result<Vec<_>, _>>()?;
+            Trigger::Holds(comparisons)
+        }
+    };
+
+    Ok(Condition {
+        target,
+        new_only,
+        trigger,
+        duration,
+    })
+}
+
+fn parse_comparison(target: &Target, words: &[&str]) -> Result<Comparison, String> {
+    let [field, op, value] = words else {
+        return Err(format!("expected '<field> <op> <value>', got '{}'", words.join(" ")));
+    };
+    let field = target
+        .fields()
+        .iter()
+        .find(|&&name| name == *field)
+        .ok_or_else(|| format!("unknown field '{field}' for {target} ({})", target.fields().join(", ")))?;
+    let op = Op::parse(op).ok_or_else(|| format!("invalid operator '{op}'"))?;
+    let value = Value::parse(value);
+    if matches!(value, Value::Text(_)) && !matches!(op, Op::Eq | Op::Ne) {
+        return Err(format!("'{field}' compared to text needs == or !="));
+    }
+    Ok(Comparison { field, op, value })
+}
+
+/// `30`, `30s`, `5m` or `1h`.
+fn parse_duration(word: &str) -> Result<Duration, String> {
+    let (number, unit) = match word.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => word.split_at(i),
+        None => (word, "s"),
+    };
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("invalid duration '{word}'")),
+    };
+    let n = number.parse::<u64>().map_err(|_| format!("invalid duration '{word}'"))?;
+    n.checked_mul(scale)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration '{word}' is too long"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example from the module doc.
+    const EXAMPLE: &str = r#"
+# Lines starting with '#' are comments
+rule Core AP weak
+  when ap 00:11:22:33:44:55 signal < -75 for 30s
+  severity warning
+  popup
+  bell
+
+rule Guest network down
+  when ssid "Guest WiFi" disappears for 60s
+  log /var/log/wifimonitor-alerts.log
+
+rule Strong open AP
+  when new ap encryption == open and signal > -60
+  severity high
+  command notify-send wifimonitor "$WIFIMONITOR_MESSAGE"
+
+rule Busy channel 6
+  when channel 6 pps > 2000 for 10s
+  post http://127.0.0.1:8080/alerts
+"#;
+
+    fn condition(text: &str) -> Result<Condition, String> {
+        parse_condition(&tokenize(text)?)
+    }
+
+    fn comparison(field: &'static str, op: Op, value: Value) -> Comparison {
+        Comparison { field, op, value }
+    }
+
+    #[test]
+    fn parses_the_documented_example() {
+        let rules = parse(EXAMPLE).unwrap();
+        let names: Vec<&str> = rules.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(names, ["Core AP weak", "Guest network down", "Strong open AP", "Busy channel 6"]);
+
+        assert_eq!(
+            rules[0].condition,
+            Condition {
+                target: Target::Ap(Some([0x00, 0x11, 0x22, 0x33, 0x44, 0x55])),
+                new_only: false,
+                trigger: Trigger::Holds(vec![comparison("signal", Op::Lt, Value::Number(-75.0))]),
+                duration: Duration::from_secs(30),
+            }
+        );
+        assert_eq!(rules[0].severity, Severity::Warning);
+        assert_eq!(rules[0].actions, [Action::Popup, Action::Bell]);
+
+        assert_eq!(
+            rules[1].condition,
+            Condition {
+                target: Target::Ssid("Guest WiFi".to_string()),
+                new_only: false,
+                trigger: Trigger::Disappears,
+                duration: Duration::from_secs(60),
+            }
+        );
+        assert_eq!(rules[1].actions, [Action::Log(PathBuf::from("/var/log/wifimonitor-alerts.log"))]);
+
+        assert_eq!(
+            rules[2].condition,
+            Condition {
+                target: Target::Ap(None),
+                new_only: true,
+                trigger: Trigger::Holds(vec![
+                    comparison("encryption", Op::Eq, Value::Text("open".to_string())),
+                    comparison("signal", Op::Gt, Value::Number(-60.0)),
+                ]),
+                duration: Duration::ZERO,
+            }
+        );
+        assert_eq!(rules[2].severity, Severity::High);
+        assert_eq!(
+            rules[2].actions,
+            [Action::Command("notify-send wifimonitor \"$WIFIMONITOR_MESSAGE\"".to_string())]
+        );
+
+        assert_eq!(
+            rules[3].condition,
+            Condition {
+                target: Target::Channel(6),
+                new_only: false,
+                trigger: Trigger::Holds(vec![comparison("pps", Op::Gt, Value::Number(2000.0))]),
+                duration: Duration::from_secs(10),
+            }
+        );
+        assert_eq!(
+            rules[3].actions,
+            [Action::Post(HttpEndpoint {
+                addr: "127.0.0.1:8080".to_string(),
+                path: "/alerts".to_string(),
+            })]
+        );
+    }
+
+    #[test]
+    fn tokenizer_keeps_quoted_strings() {
+        assert_eq!(
+            tokenize(r#"ssid "Guest  WiFi" disappears"#).unwrap(),
+            ["ssid", "Guest  WiFi", "disappears"]
+        );
+        assert_eq!(tokenize(r#"ssid """#).unwrap(), ["ssid", ""]);
+        assert!(tokenize(r#"ssid "Guest"#).is_err());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("-5s").is_err());
+        assert!(parse_duration(&format!("{}h", u64::MAX / 60)).is_err());
+    }
+
+    #[test]
+    fn severity_and_actions() {
+        let rules = parse("rule a\n when capture pps > 1\n severity info\n log /tmp/a.log").unwrap();
+        assert_eq!(rules[0].severity, Severity::Info);
+        assert!(parse("rule a\n when capture pps > 1\n severity critical").is_err());
+        assert!(parse("rule a\n when capture pps > 1\n log").is_err());
+        assert!(parse("rule a\n when capture pps > 1\n post https://example.com/").is_err());
+        assert_eq!(
+            HttpEndpoint::parse("http://example.com").unwrap(),
+            HttpEndpoint {
+                addr: "example.com:80".to_string(),
+                path: "/".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn grammar_errors() {
+        assert!(condition("router signal < -70").is_err());
+        assert!(condition("ap").is_err());
+        assert!(condition("ap pps > 5").is_err());
+        assert!(condition("ap signal <> -70").is_err());
+        assert!(condition("ap signal < strong").is_err());
+        assert!(condition("ap signal < -70 and").is_err());
+        assert!(condition("channel 0 pps > 5").is_err());
+        assert!(condition("channel 6 disappears").is_err());
+        assert!(condition("new ap disappears").is_err());
+
+        assert_eq!(parse("when ap signal < -70").unwrap_err(), "line 1: 'when' outside a rule");
+        assert_eq!(parse("\nrule a\n popup").unwrap_err(), "line 2: rule 'a' has no 'when'");
+        assert!(parse("rule a\n when ap hidden == yes\n when ap karma == yes").is_err());
+    }
+
+    #[test]
+    fn text_compares_only_for_equality() {
+        let cmp = comparison("encryption", Op::Eq, Value::Text("open".to_string()));
+        assert!(cmp.matches(&Value::Text("Open".to_string())));
+        assert!(!cmp.matches(&Value::Number(0.0)));
+        let cmp = comparison("ssid", Op::Ne, Value::Text("corp".to_string()));
+        assert!(cmp.matches(&Value::Number(1.0)));
+        assert_eq!(
+            condition("ap hidden == yes").unwrap().trigger,
+            Trigger::Holds(vec![comparison("hidden", Op::Eq, Value::Number(1.0))])
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::api::command::Command;
+use crate::api::snapshot::Snapshot;
+use crate::model::AppState;
+
+/// How long to wait for the daemon to answer a command.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Command channel to a running daemon.
+pub struct DaemonClient {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl DaemonClient {
+    /// Send a command and wait for the daemon's one-line reply.
+    pub fn send(&mut self, cmd: &Command) -> Result<String, String> {
+        writeln!(self.writer, "{cmd}").map_err(|e| format!("Lost connection to daemon: {e}"))?;
+        let mut reply = String::new();
+        self.reader
+            .read_line(&mut reply)
+            .map_err(|e| format!("No reply from daemon: {e}"))?;
+        match reply.trim_end().split_once(' ') {
+            Some(("ok", msg)) => Ok(msg.to_string()),
+            Some(("err", msg)) => Err(msg.to_string()),
+            _ => Err(format!("Unexpected reply from daemon: {}", reply.trim_end())),
+        }
+    }
+}
+
+/// Attach to a daemon's control socket.
+///
+/// Opens one connection that streams snapshots into `state` on a separate
+/// thread, and a second one for sending commands.
+pub fn attach(
+    path: &Path,
+    state: Arc<Mutex<AppState>>,
+) -> Result<(thread::JoinHandle<()>, DaemonClient), String> {
+    let connect = || {
+        UnixStream::connect(path)
+            .map_err(|e| format!("Failed to connect to daemon at {}: {e}", path.display()))
+    };
+
+    let mut stream = connect()?;
+    writeln!(stream, "attach").map_err(|e| format!("Failed to attach: {e}"))?;
+
+    let commands = connect()?;
+    commands
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(|e| format!("Failed to configure daemon connection: {e}"))?;
+    let client = DaemonClient {
+        reader: BufReader::new(
+            commands
+                .try_clone()
+                .map_err(|e| format!("Failed to configure daemon connection: {e}"))?,
+        ),
+        writer: commands,
+    };
+
+    let handle = thread::spawn(move || {
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            if !state.lock().unwrap().is_running() {
+                return;
+            }
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => break,
+            };
+            match Snapshot::decode(&line) {
+                Ok(snapshot) => snapshot.apply(&mut state.lock().unwrap()),
+                Err(e) => {
+                    let mut st = state.lock().unwrap();
+                    st.error = Some(e);
+                    st.stop();
+                    return;
+                }
+            }
+        }
+        let mut st = state.lock().unwrap();
+        if st.is_running() {
+            st.error = Some("Daemon closed the connection".to_string());
+            st.stop();
+        }
+    });
+
+    Ok((handle, client))
+}
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::export::{self, ExportFormat};
+use crate::model::{AppState, BandFilter, CaptureRequest, ChannelLock};
+use crate::util::mac::{format_mac, parse_mac};
+
+/// A runtime command accepted on the control socket.
+///
+/// Each variant maps onto the same `AppState` change the matching TUI
+/// keybinding makes, so scripted and interactive use stay consistent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `set channel <n>` — stop hopping and stay on one channel.
+    SetChannel(u8),
+    /// `band <2.4|5|6|both>` — choose which band the hopper scans.
+    Band(BandFilter),
+    /// `lock <bssid>` — follow one AP's channel.
+    Lock([u8; 6]),
+    /// `unlock` or `set channel auto` — resume hopping.
+    Unlock,
+    /// `pin <bssid>` — keep an AP at the top of the table and graph.
+    Pin([u8; 6]),
+    /// `unpin <bssid>`
+    Unpin([u8; 6]),
+    /// `export <format> <path>` — write the current model to a file.
+    Export(ExportFormat, PathBuf),
+    /// `stats` — one-line capture summary.
+    Stats,
+    /// `survey mark <x> <y>` — record readings at a floor-plan cell.
+    SurveyMark(usize, usize),
+    /// `survey stop` — stop recording (e.g. while walking).
+    SurveyStop,
+    /// `trigger` — save the trigger ring's frames around this moment.
+    Trigger,
+    /// `ring export` — save what the trigger ring holds now.
+    ExportRing,
+    /// `extract <bssid>` — save frames to or from an AP and its clients.
+    Extract([u8; 6]),
+    /// `extract stop <bssid>`
+    ExtractStop([u8; 6]),
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["set", "channel", "auto"] | ["unlock"] => Ok(Command::Unlock),
+            ["set", "channel", ch] => ch
+                .parse::<u8>()
+                .ok()
+                .filter(|&ch| ch > 0)
+                .map(Command::SetChannel)
+                .ok_or_else(|| format!("invalid channel '{ch}'")),
+            ["band", band] => band.parse().map(Command::Band),
+            ["lock", bssid] => mac_arg(bssid).map(Command::Lock),
+            ["pin", bssid] => mac_arg(bssid).map(Command::Pin),
+            ["unpin", bssid] => mac_arg(bssid).map(Command::Unpin),
+            ["export", format, path] => Ok(Command::Export(format.parse()?, PathBuf::from(path))),
+            ["stats"] => Ok(Command::Stats),
+            ["survey", "mark", x, y] => match (x.parse(), y.parse()) {
+                (Ok(x), Ok(y)) => Ok(Command::SurveyMark(x, y)),
+                _ => Err(format!("invalid cell '{x} {y}'")),
+            },
+            ["survey", "stop"] => Ok(Command::SurveyStop),
+            ["trigger"] => Ok(Command::Trigger),
+            ["ring", "export"] => Ok(Command::ExportRing),
+            ["extract", "stop", bssid] => mac_arg(bssid).map(Command::ExtractStop),
+            ["extract", bssid] => mac_arg(bssid).map(Command::Extract),
+            [] => Err("empty command".to_string()),
+            _ => Err(format!("unknown command '{}'", line.trim())),
+        }
+    }
+}
+
+/// Formats the command in the same syntax `from_str` accepts.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::SetChannel(ch) => write!(f, "set channel {ch}"),
+            Command::Band(band) => match band {
+                BandFilter::Both => write!(f, "band both"),
+                BandFilter::Only2_4 => write!(f, "band 2.4"),
+                BandFilter::Only5 => write!(f, "band 5"),
+                BandFilter::Only6 => write!(f, "band 6"),
+            },
+            Command::Lock(bssid) => write!(f, "lock {}", format_mac(bssid)),
+            Command::Unlock => write!(f, "unlock"),
+            Command::Pin(bssid) => write!(f, "pin {}", format_mac(bssid)),
+            Command::Unpin(bssid) => write!(f, "unpin {}", format_mac(bssid)),
+            Command::Export(format, path) => write!(f, "export {format} {}", path.display()),
+            Command::Stats => write!(f, "stats"),
+            Command::SurveyMark(x, y) => write!(f, "survey mark {x} {y}"),
+            Command::SurveyStop => write!(f, "survey stop"),
+            Command::Trigger => write!(f, "trigger"),
+            Command::ExportRing => write!(f, "ring export"),
+            Command::Extract(bssid) => write!(f, "extract {}", format_mac(bssid)),
+            Command::ExtractStop(bssid) => write!(f, "extract stop {}", format_mac(bssid)),
+        }
+    }
+}
+
+fn mac_arg(s: &str) -> Result<[u8; 6], String> {
+    parse_mac(s).ok_or_else(|| format!("invalid BSSID '{s}'"))
+}
+
+impl Command {
+    /// Apply the command to the shared state and return a short reply.
+    pub fn execute(&self, state: &mut AppState) -> Result<String, String> {
+        match self {
+            Command::SetChannel(ch) => {
+                state.channel_lock = ChannelLock::Channel(*ch);
+                Ok(format!("locked to channel {ch}"))
+            }
+            Command::Band(band) => {
+                state.band_filter = *band;
+                Ok(format!("band {band}"))
+            }
+            Command::Lock(bssid) => {
+                state.channel_lock = ChannelLock::Bssid(*bssid);
+                match state.locked_channel() {
+                    Some((ch, six_ghz)) => Ok(format!(
+                        "locked to {} on {}channel {ch}",
+                        format_mac(bssid),
+                        if six_ghz { "6 GHz " } else { "" }
+                    )),
+                    None => Ok(format!("locked to {} (channel not yet known)", format_mac(bssid))),
+                }
+            }
+            Command::Unlock => {
+                state.channel_lock = ChannelLock::Hopping;
+                Ok("hopping".to_string())
+            }
+            Command::Pin(bssid) => {
+                state.pinned.insert(*bssid);
+                Ok(format!("pinned {}", format_mac(bssid)))
+            }
+            Command::Unpin(bssid) => {
+                state.pinned.remove(bssid);
+                Ok(format!("unpinned {}", format_mac(bssid)))
+            }
+            Command::Export(format, path) => {
+                export::export_to_file(*format, path, state)?;
+                Ok(format!("exported {format} to {}", path.display()))
+            }
+            Command::Stats => Ok(format!(
+                "packets={} rate={} aps={} stations={} channel={} band={} dropped={}",
+                state.total_packets,
+                state.current_packet_rate(),
+                state.access_points.len(),
+                state.stations.len(),
+                state.current_channel,
+                state.band_filter,
+                state.pcap_stats.dropped,
+            )),
+            Command::SurveyMark(x, y) => {
+                let survey = state.survey.as_mut().ok_or("no survey floor plan loaded")?;
+                survey.mark_position(*x, *y)?;
+                Ok(format!("recording at ({x},{y})"))
+            }
+            Command::SurveyStop => {
+                let survey = state.survey.as_mut().ok_or("no survey floor plan loaded")?;
+                survey.clear_position();
+                Ok("recording stopped".to_string())
+            }
+            Command::Trigger => {
+                if state.request_capture("manual trigger".to_string()) {
+                    Ok("capture triggered".to_string())
+                } else {
+                    Err("no trigger ring (start with --trigger-ring)".to_string())
+                }
+            }
+            Command::ExportRing => {
+                if state.capture_trigger.is_none() {
+                    return Err("no trigger ring (start with --trigger-ring)".to_string());
+                }
+                state.capture_requests.push(CaptureRequest::ExportRing);
+                Ok(format!("exporting ring to {}", state.capture_dir.display()))
+            }
+            Command::Extract(bssid) => {
+                if let Some(path) = state.extracting.get(bssid) {
+                    return Ok(format!("already saving {} to {}", format_mac(bssid), path.display()));
+                }
+                state.capture_requests.push(CaptureRequest::StartExtract(*bssid));
+                Ok(format!("saving {} to {}", format_mac(bssid), state.capture_dir.display()))
+            }
+            Command::ExtractStop(bssid) => {
+                if !state.extracting.contains_key(bssid) {
+                    return Err(format!("not saving {}", format_mac(bssid)));
+                }
+                state.capture_requests.push(CaptureRequest::StopExtract(*bssid));
+                Ok(format!("stopped saving {}", format_mac(bssid)))
+            }
+        }
+    }
+}
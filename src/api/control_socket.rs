@@ -0,0 +1,135 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::api::command::Command;
+use crate::api::snapshot::{self, RollupCursor};
+use crate::model::AppState;
+
+/// How long the accept loop sleeps when no client is waiting.
+const ACCEPT_POLL_MS: u64 = 100;
+
+/// Read timeout so idle clients notice shutdown.
+const CLIENT_POLL: Duration = Duration::from_millis(250);
+
+/// How often attached TUI clients receive a fresh snapshot.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often snapshots carry the long-term rollup tiers (the finest tier's slot width).
+const ROLLUP_SYNC_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Listen for line-based commands on a Unix socket.
+///
+/// Each line is parsed as a [`Command`]; the reply is a single line starting
+/// with `ok` or `err`. Clients may keep the connection open for many commands.
+///
+/// The special line `attach` turns the connection into a one-way stream of
+/// model snapshots (one JSON object per line) for `--attach` TUI clients.
+pub fn start_control_socket(
+    path: &Path,
+    state: Arc<Mutex<AppState>>,
+) -> Result<thread::JoinHandle<()>, String> {
+    remove_stale_socket(path)?;
+    let listener = UnixListener::bind(path)
+        .map_err(|e| format!("Failed to bind control socket {}: {e}", path.display()))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure control socket: {e}"))?;
+
+    let path: PathBuf = path.to_path_buf();
+    Ok(thread::spawn(move || {
+        while state.lock().unwrap().is_running() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let state = Arc::clone(&state);
+                    thread::spawn(move || {
+                        let _ = handle_client(stream, &state);
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(ACCEPT_POLL_MS));
+                }
+                Err(_) => continue,
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+    }))
+}
+
+/// Remove a socket left behind by a previous run, which would make bind()
+/// fail. Anything else at `path`, or a socket a daemon still answers on, is
+/// left alone.
+fn remove_stale_socket(path: &Path) -> Result<(), String> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Failed to check control socket {}: {e}", path.display())),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(format!("{} exists and is not a socket", path.display()));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(format!("{} is in use by another running daemon", path.display()));
+    }
+    std::fs::remove_file(path).map_err(|e| format!("Failed to remove stale socket {}: {e}", path.display()))
+}
+
+fn handle_client(stream: UnixStream, state: &Arc<Mutex<AppState>>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_POLL))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut writer = &stream;
+    // read_until keeps partial input across timeouts, unlike read_line
+    let mut line = Vec::new();
+
+    while state.lock().unwrap().is_running() {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                let text = String::from_utf8_lossy(&line);
+                if text.trim() == "attach" {
+                    return stream_snapshots(writer, state);
+                }
+                let reply = match text.parse::<Command>() {
+                    Ok(cmd) => cmd.execute(&mut state.lock().unwrap()),
+                    Err(e) => Err(e),
+                };
+                match reply {
+                    Ok(msg) => writeln!(writer, "ok {msg}")?,
+                    Err(msg) => writeln!(writer, "err {msg}")?,
+                }
+                line.clear();
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Push snapshots until the client disconnects or the daemon stops.
+fn stream_snapshots(mut writer: &UnixStream, state: &Arc<Mutex<AppState>>) -> std::io::Result<()> {
+    let mut last_rollup_sync: Option<Instant> = None;
+    let mut cursor = RollupCursor::default();
+    loop {
+        let include_rollups = last_rollup_sync.is_none_or(|t| t.elapsed() >= ROLLUP_SYNC_INTERVAL);
+        if include_rollups {
+            last_rollup_sync = Some(Instant::now());
+        }
+        let line = {
+            let st = state.lock().unwrap();
+            if !st.is_running() {
+                return Ok(());
+            }
+            snapshot::encode(&st, include_rollups.then_some(&mut cursor))
+        };
+        // Serialize under the lock, write outside it so a slow client can't stall capture
+        writeln!(writer, "{line}")?;
+        thread::sleep(SNAPSHOT_INTERVAL);
+    }
+}
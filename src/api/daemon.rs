@@ -0,0 +1,51 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::model::AppState;
+
+/// Default control socket path when running with `--daemon`.
+pub const DEFAULT_SOCKET_PATH: &str = "/run/wifimonitor-tui.sock";
+
+/// How often the daemon loop checks for shutdown and runs housekeeping.
+const POLL_MS: u64 = 100;
+
+/// Set from the signal handler; checked by the daemon loop.
+static SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_shutdown_signal(_: libc::c_int) {
+    SHUTDOWN.store(true, Ordering::Relaxed);
+}
+
+/// Run headless until SIGINT/SIGTERM or until a capture thread stops the app.
+///
+/// SIGHUP is ignored so closing the terminal or SSH session that started the
+/// daemon does not end the capture.
+pub fn run(state: &Arc<Mutex<AppState>>) {
+    run_until(state, None);
+}
+
+/// Like `run`, but also return once `deadline` passes.
+pub fn run_until(state: &Arc<Mutex<AppState>>, deadline: Option<Instant>) {
+    let handler = on_shutdown_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {
+        libc::signal(libc::SIGINT, handler);
+        libc::signal(libc::SIGTERM, handler);
+        libc::signal(libc::SIGHUP, libc::SIG_IGN);
+    }
+
+    loop {
+        {
+            let mut st = state.lock().unwrap();
+            if SHUTDOWN.load(Ordering::Relaxed) {
+                st.stop();
+            }
+            if !st.is_running() || deadline.is_some_and(|at| Instant::now() >= at) {
+                break;
+            }
+            st.tick_if_due();
+        }
+        thread::sleep(Duration::from_millis(POLL_MS));
+    }
+}
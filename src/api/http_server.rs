@@ -0,0 +1,97 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::export::{json, prometheus};
+use crate::model::AppState;
+
+/// How long the accept loop sleeps when no client is waiting.
+const ACCEPT_POLL_MS: u64 = 100;
+
+/// Per-connection read/write timeout so a stalled client can't block the server.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Bind the HTTP listener and serve requests on a separate thread.
+///
+/// Routes:
+/// - `GET /metrics` — Prometheus text format
+/// - `GET /api/aps`, `/api/stations`, `/api/alerts` — JSON
+pub fn start_http_server(
+    addr: &str,
+    state: Arc<Mutex<AppState>>,
+) -> Result<thread::JoinHandle<()>, String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|e| format!("Failed to bind HTTP server on {addr}: {e}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure HTTP listener: {e}"))?;
+
+    Ok(thread::spawn(move || {
+        while state.lock().unwrap().is_running() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    // Errors only affect this one client
+                    let _ = handle_client(stream, &state);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(ACCEPT_POLL_MS));
+                }
+                Err(_) => continue,
+            }
+        }
+    }))
+}
+
+fn handle_client(stream: TcpStream, state: &Arc<Mutex<AppState>>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Drain the headers; we don't need any of them
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 && line != "\r\n" && line != "\n" {
+        line.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("");
+    // Ignore any query string
+    let path = path.split('?').next().unwrap_or("");
+
+    let (status, content_type, body) = if method != "GET" {
+        ("405 Method Not Allowed", "text/plain", "Only GET is supported\n".to_string())
+    } else {
+        route(path, state)
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len(),
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn route(path: &str, state: &Arc<Mutex<AppState>>) -> (&'static str, &'static str, String) {
+    let st = state.lock().unwrap();
+    match path {
+        "/metrics" => (
+            "200 OK",
+            "text/plain; version=0.0.4",
+            prometheus::render_metrics(&st),
+        ),
+        "/api/aps" => ("200 OK", "application/json", json::aps_json(&st)),
+        "/api/stations" => ("200 OK", "application/json", json::stations_json(&st)),
+        "/api/alerts" => ("200 OK", "application/json", json::alerts_json(&st)),
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+    }
+}
@@ -0,0 +1,6 @@
+pub mod client;
+pub mod command;
+pub mod control_socket;
+pub mod daemon;
+pub mod http_server;
+pub mod snapshot;
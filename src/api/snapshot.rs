@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::app_state::PcapStats;
+use crate::model::frame_stats::FRAME_CATEGORY_COUNT;
+use crate::model::{
+    AccessPoint, Alert, AppState, BandFilter, ChannelLock, ChannelUsage, FrameStats, GpsFix, LinkStats,
+    SsidProbes, Station,
+    Survey,
+};
+use crate::util::ring_buffer::RingBuffer;
+use crate::util::time_series::{TiersDelta, TiersSent, TimedSample};
+
+/// Probed SSIDs sent to attached clients, most widely probed first. The
+/// daemon may hold thousands under a probe flood; nobody reads past these.
+const SNAPSHOT_PROBED_SSIDS: usize = 200;
+
+/// The part of `AppState` a daemon streams to attached TUI clients.
+///
+/// View-only state (scroll position, time window) stays with each client so
+/// several operators can look at the same sensor independently.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub interface: String,
+    pub access_points: Vec<AccessPoint>,
+    pub stations: Vec<Station>,
+    pub alerts: RingBuffer<Alert>,
+    pub total_packets: u64,
+    pub packet_rate_history: RingBuffer<TimedSample<u64>>,
+    pub channel_packets: HashMap<u8, u64>,
+    pub channel_history: HashMap<u8, RingBuffer<TimedSample<u64>>>,
+    pub channel_usage: HashMap<u8, ChannelUsage>,
+    pub channel_usage_6g: HashMap<u8, ChannelUsage>,
+    pub channel_noise: HashMap<u8, f32>,
+    pub channel_corrupt: HashMap<u8, u64>,
+    pub channel_link: HashMap<u8, LinkStats>,
+    pub frame_stats: FrameStats,
+    /// The most probed SSIDs only; see `SNAPSHOT_PROBED_SSIDS`.
+    pub probed_ssids: Vec<SsidProbes>,
+    pub extracting: HashMap<[u8; 6], PathBuf>,
+    pub current_channel: u8,
+    pub current_six_ghz: bool,
+    pub band_filter: BandFilter,
+    pub channel_lock: ChannelLock,
+    pub pinned: HashSet<[u8; 6]>,
+    pub pcap_stats: PcapStats,
+    pub gps_enabled: bool,
+    pub gps_fix: Option<GpsFix>,
+    pub survey: Option<Survey>,
+    pub hop_cycle: Duration,
+    /// Long-term history; only present in some snapshots (see `encode`).
+    pub rollups: Option<RollupSync>,
+}
+
+/// New rollups for every tiered series in the model since the previous sync.
+#[derive(Serialize, Deserialize)]
+pub struct RollupSync {
+    pub aps: Vec<([u8; 6], TiersDelta)>,
+    pub channels: Vec<(u8, TiersDelta)>,
+    /// Airtime and dwell tiers per channel.
+    pub usage: Vec<(u8, TiersDelta, TiersDelta)>,
+    pub usage_6g: Vec<(u8, TiersDelta, TiersDelta)>,
+    pub packet_rate: TiersDelta,
+    pub frame_categories: Vec<TiersDelta>,
+}
+
+/// How much of each rollup series one attached client has been sent.
+#[derive(Default)]
+pub struct RollupCursor {
+    aps: HashMap<[u8; 6], TiersSent>,
+    channels: HashMap<u8, TiersSent>,
+    usage: UsageCursor,
+    usage_6g: UsageCursor,
+    packet_rate: TiersSent,
+    frame_categories: [TiersSent; FRAME_CATEGORY_COUNT],
+}
+
+impl RollupCursor {
+    /// The rollups `state` gained since this cursor last synced.
+    fn sync(&mut self, state: &AppState) -> RollupSync {
+        // An AP that expires and comes back starts over with a full send
+        self.aps.retain(|bssid, _| state.access_points.contains_key(bssid));
+        RollupSync {
+            aps: state
+                .access_points
+                .values()
+                .map(|ap| (ap.bssid, ap.signal_rollups.delta(self.aps.entry(ap.bssid).or_default())))
+                .collect(),
+            channels: state
+                .channel_rollups
+                .iter()
+                .map(|(&ch, tiers)| (ch, tiers.delta(self.channels.entry(ch).or_default())))
+                .collect(),
+            usage: self.usage.sync(&state.channel_usage),
+            usage_6g: self.usage_6g.sync(&state.channel_usage_6g),
+            packet_rate: state.packet_rate_rollups.delta(&mut self.packet_rate),
+            frame_categories: state
+                .frame_stats
+                .category_rollups
+                .iter()
+                .zip(&mut self.frame_categories)
+                .map(|(tiers, sent)| tiers.delta(sent))
+                .collect(),
+        }
+    }
+}
+
+/// How much of each channel's airtime and dwell tiers a client has been sent.
+#[derive(Default)]
+struct UsageCursor {
+    airtime: HashMap<u8, TiersSent>,
+    dwell: HashMap<u8, TiersSent>,
+}
+
+impl UsageCursor {
+    fn sync(&mut self, usage: &HashMap<u8, ChannelUsage>) -> Vec<(u8, TiersDelta, TiersDelta)> {
+        usage
+            .iter()
+            .map(|(&ch, usage)| {
+                (
+                    ch,
+                    usage.airtime_rollups.delta(self.airtime.entry(ch).or_default()),
+                    usage.dwell_rollups.delta(self.dwell.entry(ch).or_default()),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Serialize the shared model as one JSON line.
+///
+/// Rollup tiers hold hours of history and only change every 10 s, so callers
+/// pass their client's cursor on the first snapshot and periodically after,
+/// and get just the rollups that client hasn't seen.
+pub fn encode(state: &AppState, rollups: Option<&mut RollupCursor>) -> String {
+    #[derive(Serialize)]
+    struct SnapshotRef<'a> {
+        interface: &'a str,
+        access_points: Vec<&'a AccessPoint>,
+        stations: Vec<&'a Station>,
+        alerts: &'a RingBuffer<Alert>,
+        total_packets: u64,
+        packet_rate_history: &'a RingBuffer<TimedSample<u64>>,
+        channel_packets: &'a HashMap<u8, u64>,
+        channel_history: &'a HashMap<u8, RingBuffer<TimedSample<u64>>>,
+        channel_usage: &'a HashMap<u8, ChannelUsage>,
+        channel_usage_6g: &'a HashMap<u8, ChannelUsage>,
+        channel_noise: &'a HashMap<u8, f32>,
+        channel_corrupt: &'a HashMap<u8, u64>,
+        channel_link: &'a HashMap<u8, LinkStats>,
+        frame_stats: &'a FrameStats,
+        probed_ssids: Vec<&'a SsidProbes>,
+        extracting: &'a HashMap<[u8; 6], PathBuf>,
+        current_channel: u8,
+        current_six_ghz: bool,
+        band_filter: BandFilter,
+        channel_lock: ChannelLock,
+        pinned: &'a HashSet<[u8; 6]>,
+        pcap_stats: PcapStats,
+        gps_enabled: bool,
+        gps_fix: Option<GpsFix>,
+        survey: &'a Option<Survey>,
+        hop_cycle: Duration,
+        rollups: Option<RollupSync>,
+    }
+
+    // Borrow rather than clone; this runs under the state lock. Rollup
+    // deltas are copied, but only hold what the client hasn't seen.
+    let rollups = rollups.map(|cursor| cursor.sync(state));
+    let snapshot = SnapshotRef {
+        interface: &state.interface,
+        access_points: state.access_points.values().collect(),
+        stations: state.stations.values().collect(),
+        alerts: &state.alerts,
+        total_packets: state.total_packets,
+        packet_rate_history: &state.packet_rate_history,
+        channel_packets: &state.channel_packets,
+        channel_history: &state.channel_history,
+        channel_usage: &state.channel_usage,
+        channel_usage_6g: &state.channel_usage_6g,
+        channel_noise: &state.channel_noise,
+        channel_corrupt: &state.channel_corrupt,
+        channel_link: &state.channel_link,
+        frame_stats: &state.frame_stats,
+        probed_ssids: state.most_probed().into_iter().take(SNAPSHOT_PROBED_SSIDS).collect(),
+        extracting: &state.extracting,
+        current_channel: state.current_channel,
+        current_six_ghz: state.current_six_ghz,
+        band_filter: state.band_filter,
+        channel_lock: state.channel_lock,
+        pinned: &state.pinned,
+        pcap_stats: state.pcap_stats,
+        gps_enabled: state.gps_enabled,
+        gps_fix: state.gps_fix,
+        survey: &state.survey,
+        hop_cycle: state.hop_cycle,
+        rollups,
+    };
+    serde_json::to_string(&snapshot).unwrap_or_default()
+}
+
+impl Snapshot {
+    pub fn decode(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|e| format!("Malformed snapshot: {e}"))
+    }
+
+    /// Replace the shared model in a client's local `AppState`.
+    pub fn apply(self, state: &mut AppState) {
+        // Snapshots without rollups keep the ones received earlier
+        let mut previous = std::mem::take(&mut state.access_points);
+        state.access_points = self
+            .access_points
+            .into_iter()
+            .map(|mut ap| {
+                if let Some(old) = previous.remove(&ap.bssid) {
+                    ap.signal_rollups = old.signal_rollups;
+                }
+                (ap.bssid, ap)
+            })
+            .collect();
+        replace_usage(&mut state.channel_usage, self.channel_usage);
+        replace_usage(&mut state.channel_usage_6g, self.channel_usage_6g);
+        if let Some(sync) = self.rollups {
+            for (bssid, delta) in sync.aps {
+                if let Some(ap) = state.access_points.get_mut(&bssid) {
+                    ap.signal_rollups.apply(delta);
+                }
+            }
+            for (ch, delta) in sync.channels {
+                state.channel_rollups.entry(ch).or_default().apply(delta);
+            }
+            apply_usage(&mut state.channel_usage, sync.usage);
+            apply_usage(&mut state.channel_usage_6g, sync.usage_6g);
+            state.packet_rate_rollups.apply(sync.packet_rate);
+            for (tiers, delta) in state.frame_stats.category_rollups.iter_mut().zip(sync.frame_categories) {
+                tiers.apply(delta);
+            }
+        }
+        let frame_rollups = std::mem::take(&mut state.frame_stats.category_rollups);
+        state.frame_stats = self.frame_stats;
+        state.frame_stats.category_rollups = frame_rollups;
+
+        state.interface = self.interface;
+        state.stations = self
+            .stations
+            .into_iter()
+            .map(|sta| (sta.mac, sta))
+            .collect();
+        state.alerts = self.alerts;
+        state.total_packets = self.total_packets;
+        state.packet_rate_history = self.packet_rate_history;
+        state.channel_packets = self.channel_packets;
+        state.channel_history = self.channel_history;
+        state.channel_noise = self.channel_noise;
+        state.channel_corrupt = self.channel_corrupt;
+        state.channel_link = self.channel_link;
+        state.probed_ssids = self
+            .probed_ssids
+            .into_iter()
+            .map(|probes| (probes.ssid.clone(), probes))
+            .collect();
+        state.extracting = self.extracting;
+        state.current_channel = self.current_channel;
+        state.current_six_ghz = self.current_six_ghz;
+        state.band_filter = self.band_filter;
+        state.channel_lock = self.channel_lock;
+        state.pinned = self.pinned;
+        state.pcap_stats = self.pcap_stats;
+        state.gps_enabled = self.gps_enabled;
+        state.gps_fix = self.gps_fix;
+        state.survey = self.survey;
+        state.hop_cycle = self.hop_cycle;
+
+        // The daemon owns the AP list; keep the cursor inside the table as shown
+        let max = state.table_len().saturating_sub(1);
+        state.table_scroll = state.table_scroll.min(max);
+    }
+}
+
+/// Take per-channel usage from a snapshot, keeping the rollups received earlier.
+fn replace_usage(usage: &mut HashMap<u8, ChannelUsage>, received: HashMap<u8, ChannelUsage>) {
+    let mut previous = std::mem::take(usage);
+    *usage = received
+        .into_iter()
+        .map(|(ch, mut new)| {
+            if let Some(old) = previous.remove(&ch) {
+                new.airtime_rollups = old.airtime_rollups;
+                new.dwell_rollups = old.dwell_rollups;
+            }
+            (ch, new)
+        })
+        .collect();
+}
+
+fn apply_usage(usage: &mut HashMap<u8, ChannelUsage>, deltas: Vec<(u8, TiersDelta, TiersDelta)>) {
+    for (ch, airtime, dwell) in deltas {
+        if let Some(usage) = usage.get_mut(&ch) {
+            usage.airtime_rollups.apply(airtime);
+            usage.dwell_rollups.apply(dwell);
+        }
+    }
+}
@@ -1,17 +1,23 @@
+use std::io::{self, Write};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 
-use crate::model::AppState;
+use crate::api::client::DaemonClient;
+use crate::api::command::Command;
+use crate::model::{AppState, ChannelLock};
 use crate::tui;
 use crate::tui::layout;
 
 const TICK_RATE: Duration = Duration::from_millis(250);
 
-pub fn run(state: Arc<Mutex<AppState>>) -> std::io::Result<()> {
+/// Run the TUI.
+///
+/// With `daemon` set, the model is fed by snapshots from a running daemon and
+/// commands are forwarded to it; otherwise they apply to the local state.
+pub fn run(state: Arc<Mutex<AppState>>, mut daemon: Option<DaemonClient>) -> std::io::Result<()> {
     let mut terminal = tui::init_terminal()?;
-    let mut last_rate_tick = Instant::now();
 
     loop {
         // Check if we should stop
@@ -22,53 +28,184 @@ pub fn run(state: Arc<Mutex<AppState>>) -> std::io::Result<()> {
             }
         }
 
-        // Tick packet rate counter every second
-        if last_rate_tick.elapsed() >= Duration::from_secs(1) {
-            let mut st = state.lock().unwrap();
-            st.tick_rate();
-            st.expire_aps();
-            last_rate_tick = Instant::now();
+        // Tick packet rate counter every second (the daemon does this when attached)
+        if daemon.is_none() {
+            state.lock().unwrap().tick_if_due();
         }
 
         // Render
         {
-            let st = state.lock().unwrap();
+            let mut st = state.lock().unwrap();
             terminal.draw(|frame| {
                 layout::draw(frame, &st);
             })?;
+            if st.alerts.iter().any(|alert| alert.bell && alert.raised_at > st.bell_rung_at) {
+                let mut stdout = io::stdout();
+                stdout.write_all(b"\x07")?;
+                stdout.flush()?;
+                st.bell_rung_at = st.now();
+            }
         }
 
         // Handle input with timeout
         if event::poll(TICK_RATE)? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
-                    match key.code {
+                    // Any key acknowledges a popup rather than acting
+                    {
+                        let mut st = state.lock().unwrap();
+                        if st.pending_popup().is_some() {
+                            st.popup_acknowledged_at = st.now();
+                            continue;
+                        }
+                    }
+                    // In the survey view the arrows move the floor-plan cursor
+                    let surveying = state.lock().unwrap().show_survey;
+                    let command = match key.code {
                         KeyCode::Char('q') | KeyCode::Esc => {
                             state.lock().unwrap().stop();
                             break;
                         }
-                        KeyCode::Up => {
+                        KeyCode::Up if surveying => {
+                            move_survey_cursor(&mut state.lock().unwrap(), 0, -1);
+                            None
+                        }
+                        KeyCode::Down if surveying => {
+                            move_survey_cursor(&mut state.lock().unwrap(), 0, 1);
+                            None
+                        }
+                        KeyCode::Left if surveying => {
+                            move_survey_cursor(&mut state.lock().unwrap(), -1, 0);
+                            None
+                        }
+                        KeyCode::Right if surveying => {
+                            move_survey_cursor(&mut state.lock().unwrap(), 1, 0);
+                            None
+                        }
+                        KeyCode::Up | KeyCode::PageUp => {
                             let mut st = state.lock().unwrap();
                             if st.table_scroll > 0 {
                                 st.table_scroll -= 1;
                             }
+                            None
                         }
-                        KeyCode::Down => {
+                        KeyCode::Down | KeyCode::PageDown => {
                             let mut st = state.lock().unwrap();
-                            let max = st.access_points.len().saturating_sub(1);
+                            let max = st.table_len().saturating_sub(1);
                             if st.table_scroll < max {
                                 st.table_scroll += 1;
                             }
+                            None
                         }
-                        KeyCode::Char('b') => {
+                        KeyCode::Char('s') => {
+                            let mut st = state.lock().unwrap();
+                            st.show_survey = !st.show_survey && st.survey.is_some();
+                            None
+                        }
+                        KeyCode::Enter if surveying => {
+                            let (x, y) = state.lock().unwrap().survey_cursor;
+                            Some(Command::SurveyMark(x, y))
+                        }
+                        KeyCode::Enter => {
+                            let mut st = state.lock().unwrap();
+                            if let Some(key) = st.selected_group() {
+                                if !st.expanded_groups.remove(&key) {
+                                    st.expanded_groups.insert(key);
+                                }
+                            }
+                            None
+                        }
+                        KeyCode::Char('g') => {
+                            let mut st = state.lock().unwrap();
+                            st.grouping = st.grouping.next();
+                            // Row indices mean different things in the two views
+                            st.table_scroll = 0;
+                            None
+                        }
+                        KeyCode::Char('x') if surveying => Some(Command::SurveyStop),
+                        KeyCode::Char('h') if surveying => {
+                            let mut st = state.lock().unwrap();
+                            st.heatmap_by_ssid = !st.heatmap_by_ssid;
+                            None
+                        }
+                        KeyCode::Char('d') => {
+                            let mut st = state.lock().unwrap();
+                            st.show_detail = !st.show_detail;
+                            None
+                        }
+                        KeyCode::Char('r') => {
+                            let mut st = state.lock().unwrap();
+                            st.show_recommend = !st.show_recommend;
+                            None
+                        }
+                        KeyCode::Char('c') => {
                             let mut st = state.lock().unwrap();
-                            st.band_filter = st.band_filter.next();
+                            st.show_spectrum = !st.show_spectrum;
+                            None
+                        }
+                        KeyCode::Char('n') => {
+                            let mut st = state.lock().unwrap();
+                            st.show_probes = !st.show_probes;
+                            None
+                        }
+                        KeyCode::Char('f') => {
+                            let mut st = state.lock().unwrap();
+                            st.show_frame_stats = !st.show_frame_stats;
+                            None
+                        }
+                        KeyCode::Char('w') => Some(Command::Trigger),
+                        KeyCode::Char('W') => Some(Command::ExportRing),
+                        KeyCode::Char('e') => {
+                            let st = state.lock().unwrap();
+                            st.selected_ap().map(|ap| {
+                                if st.extracting.contains_key(&ap.bssid) {
+                                    Command::ExtractStop(ap.bssid)
+                                } else {
+                                    Command::Extract(ap.bssid)
+                                }
+                            })
+                        }
+                        KeyCode::Char('b') => {
+                            let st = state.lock().unwrap();
+                            Some(Command::Band(st.band_filter.next()))
                         }
                         KeyCode::Char('t') => {
                             let mut st = state.lock().unwrap();
                             st.time_window = st.time_window.next();
+                            None
+                        }
+                        KeyCode::Char('m') => {
+                            let mut st = state.lock().unwrap();
+                            st.graph_stat = st.graph_stat.next();
+                            None
+                        }
+                        KeyCode::Char('l') => {
+                            let st = state.lock().unwrap();
+                            if st.channel_lock != ChannelLock::Hopping {
+                                Some(Command::Unlock)
+                            } else {
+                                st.selected_ap().map(|ap| Command::Lock(ap.bssid))
+                            }
+                        }
+                        KeyCode::Char('p') => {
+                            let st = state.lock().unwrap();
+                            st.selected_ap().map(|ap| {
+                                if st.is_pinned(&ap.bssid) {
+                                    Command::Unpin(ap.bssid)
+                                } else {
+                                    Command::Pin(ap.bssid)
+                                }
+                            })
                         }
-                        _ => {}
+                        _ => None,
+                    };
+
+                    if let Some(cmd) = command {
+                        // Keybinding commands can't fail in ways worth interrupting the TUI for
+                        let _ = match daemon.as_mut() {
+                            Some(client) => client.send(&cmd),
+                            None => cmd.execute(&mut state.lock().unwrap()),
+                        };
                     }
                 }
             }
@@ -78,3 +215,15 @@ pub fn run(state: Arc<Mutex<AppState>>) -> std::io::Result<()> {
     tui::restore_terminal(&mut terminal)?;
     Ok(())
 }
+
+fn move_survey_cursor(st: &mut AppState, dx: isize, dy: isize) {
+    let Some(survey) = &st.survey else {
+        return;
+    };
+    let (w, h) = (survey.plan.width, survey.plan.height);
+    let (x, y) = st.survey_cursor;
+    st.survey_cursor = (
+        x.saturating_add_signed(dx).min(w - 1),
+        y.saturating_add_signed(dy).min(h - 1),
+    );
+}
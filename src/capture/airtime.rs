@@ -0,0 +1,58 @@
+//! Estimate how long a frame occupied the channel from its PHY metadata.
+//!
+//! This is the PHY-level transmit time of the frame itself (preamble, headers
+//! and payload symbols). Inter-frame spaces and backoff aren't included, so
+//! utilization computed from it is a lower bound.
+
+use std::time::Duration;
+
+use crate::model::phy::{PhyInfo, PhyMode, PhyRate};
+
+/// Rate assumed when the capture carries none: the lowest OFDM rate, which
+/// management frames are commonly sent at.
+const DEFAULT_RATE_MBPS: f32 = 6.0;
+
+/// Legacy OFDM preamble plus SIGNAL field (µs).
+const OFDM_PREAMBLE_US: f32 = 20.0;
+/// OFDM signal extension on 2.4 GHz (µs).
+const SIGNAL_EXTENSION_US: f32 = 6.0;
+/// SERVICE field and tail bits added to every OFDM PSDU.
+const OFDM_OVERHEAD_BITS: f32 = 22.0;
+/// FCS bytes, for captures that strip it.
+const FCS_LEN: usize = 4;
+
+/// Airtime of one frame. `frame_len` is the 802.11 frame as captured, without
+/// the capture header.
+pub fn frame_airtime(phy: &PhyInfo, frame_len: usize) -> Duration {
+    let len = if phy.fcs_included { frame_len } else { frame_len + FCS_LEN };
+    let bits = len as f32 * 8.0;
+    let is_2ghz = phy.freq_mhz.is_some_and(|f| f < 5000);
+    let extension = if is_2ghz { SIGNAL_EXTENSION_US } else { 0.0 };
+    let rate = phy
+        .rate
+        .unwrap_or_else(|| PhyRate::legacy(DEFAULT_RATE_MBPS));
+
+    let nss = rate.nss as f32;
+    let preamble = match rate.mode {
+        // 802.11b: long preamble is 192 µs, short 96 µs, then the PSDU at `rate`
+        PhyMode::Legacy if rate.is_dsss() => {
+            let preamble = if phy.short_preamble { 96.0 } else { 192.0 };
+            return micros(preamble + bits / rate.mbps);
+        }
+        PhyMode::Legacy => OFDM_PREAMBLE_US,
+        // Legacy preamble, HT-SIG, HT-STF, one HT-LTF per stream
+        PhyMode::Ht => OFDM_PREAMBLE_US + 8.0 + 4.0 + 4.0 * nss,
+        // Legacy preamble, VHT-SIG-A, VHT-STF, one VHT-LTF per stream, VHT-SIG-B
+        PhyMode::Vht => OFDM_PREAMBLE_US + 8.0 + 4.0 + 4.0 * nss + 4.0,
+        // Legacy preamble, RL-SIG, HE-SIG-A, HE-STF, one 2x HE-LTF per stream
+        PhyMode::He => OFDM_PREAMBLE_US + 4.0 + 8.0 + 4.0 + 8.0 * nss,
+    };
+
+    let symbol_us = rate.symbol_us();
+    let symbols = ((bits + OFDM_OVERHEAD_BITS) / (rate.mbps * symbol_us)).ceil();
+    micros(preamble + symbols * symbol_us + extension)
+}
+
+fn micros(us: f32) -> Duration {
+    Duration::from_nanos((us * 1000.0) as u64)
+}
@@ -4,6 +4,7 @@ use std::thread;
 use std::time::Duration;
 
 use crate::model::app_state::BandFilter;
+use crate::model::spectrum::{channel_center_mhz_6g, CHANNELS_6G_PSC};
 use crate::model::AppState;
 
 const CHANNELS_2_4: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
@@ -12,15 +13,36 @@ const CHANNELS_5: &[u8] = &[36, 40, 44, 48, 149, 153, 157, 161, 165];
 /// Dwell time per channel in milliseconds.
 const DWELL_MS: u64 = 200;
 
-fn channels_for_band(band: BandFilter) -> Vec<u8> {
+/// Channels to hop, each with whether it is a 6 GHz channel number.
+fn channels_for_band(band: BandFilter) -> Vec<(u8, bool)> {
+    let legacy = |channels: &[u8]| channels.iter().map(|&ch| (ch, false)).collect::<Vec<_>>();
     match band {
         BandFilter::Both => {
-            let mut v = CHANNELS_2_4.to_vec();
-            v.extend_from_slice(CHANNELS_5);
+            let mut v = legacy(CHANNELS_2_4);
+            v.extend(legacy(CHANNELS_5));
             v
         }
-        BandFilter::Only2_4 => CHANNELS_2_4.to_vec(),
-        BandFilter::Only5 => CHANNELS_5.to_vec(),
+        BandFilter::Only2_4 => legacy(CHANNELS_2_4),
+        BandFilter::Only5 => legacy(CHANNELS_5),
+        BandFilter::Only6 => CHANNELS_6G_PSC.iter().map(|&ch| (ch, true)).collect(),
+    }
+}
+
+fn set_channel(iface: &str, channel: u8, six_ghz: bool, state: &Arc<Mutex<AppState>>) {
+    // iw reads bare channel numbers as 2.4/5 GHz, so 6 GHz goes by frequency
+    let result = match channel_center_mhz_6g(channel).filter(|_| six_ghz) {
+        Some(mhz) => Command::new("iw")
+            .args(["dev", iface, "set", "freq", &mhz.to_string()])
+            .output(),
+        None => Command::new("iw")
+            .args(["dev", iface, "set", "channel", &channel.to_string()])
+            .output(),
+    };
+
+    // Radios without 6 GHz support refuse the retune; don't credit dwell there
+    let tuned = result.is_ok_and(|out| out.status.success() || !six_ghz);
+    if tuned {
+        state.lock().unwrap().set_current_channel(channel, six_ghz);
     }
 }
 
@@ -41,16 +63,27 @@ pub fn start_hopper(interface: &str, state: Arc<Mutex<AppState>>) -> thread::Joi
                 idx = 0;
             }
 
-            let channel = channels[idx % channels.len()];
-
-            let result = Command::new("iw")
-                .args(["dev", &iface, "set", "channel", &channel.to_string()])
-                .output();
-
-            if result.is_ok() {
-                state.lock().unwrap().current_channel = channel;
+            // A locked channel overrides hopping; only retune when it changes
+            let locked = state.lock().unwrap().locked_channel();
+            state.lock().unwrap().hop_cycle = match locked {
+                Some(_) => Duration::from_millis(DWELL_MS),
+                None => Duration::from_millis(DWELL_MS * channels.len() as u64),
+            };
+            if let Some((channel, six_ghz)) = locked {
+                let current = {
+                    let st = state.lock().unwrap();
+                    (st.current_channel, st.current_six_ghz)
+                };
+                if current != (channel, six_ghz) {
+                    set_channel(&iface, channel, six_ghz, &state);
+                }
+                thread::sleep(Duration::from_millis(DWELL_MS));
+                continue;
             }
 
+            let (channel, six_ghz) = channels[idx % channels.len()];
+            set_channel(&iface, channel, six_ghz, &state);
+
             idx = (idx + 1) % channels.len();
             thread::sleep(Duration::from_millis(DWELL_MS));
         }
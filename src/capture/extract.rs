@@ -0,0 +1,196 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::capture::trigger_ring::create_capture_file;
+use crate::util::mac::format_mac;
+use crate::util::pcap_file::PcapWriter;
+
+/// A closed extraction file and how many frames went into it.
+pub type SavedFile = (PathBuf, u64);
+
+/// Frames to or from one AP and its clients, being written to a pcap file.
+struct Extraction {
+    writer: PcapWriter<BufWriter<File>>,
+    path: PathBuf,
+    frames: u64,
+    /// Stations associated with the AP, refreshed from the model.
+    clients: HashSet<[u8; 6]>,
+}
+
+/// Per-AP pcap files the user has started from the TUI or control socket.
+pub struct Extractions {
+    dlt: i32,
+    active: HashMap<[u8; 6], Extraction>,
+}
+
+impl Extractions {
+    pub fn new(dlt: i32) -> Self {
+        Self {
+            dlt,
+            active: HashMap::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+
+    pub fn bssids(&self) -> impl Iterator<Item = &[u8; 6]> {
+        self.active.keys()
+    }
+
+    /// Start a file for `bssid` in `dir`. None if one is already open.
+    pub fn start(&mut self, bssid: [u8; 6], dir: &Path, at: SystemTime) -> io::Result<Option<PathBuf>> {
+        if self.active.contains_key(&bssid) {
+            return Ok(None);
+        }
+        let prefix = format!("wifimonitor-{}", format_mac(&bssid).replace(':', ""));
+        let (path, file) = create_capture_file(dir, &prefix, "pcap", at)?;
+        let writer = PcapWriter::new(BufWriter::new(file), self.dlt)?;
+        self.active.insert(
+            bssid,
+            Extraction {
+                writer,
+                path: path.clone(),
+                frames: 0,
+                clients: HashSet::new(),
+            },
+        );
+        Ok(Some(path))
+    }
+
+    /// Close the file for `bssid`, returning its path and frame count.
+    pub fn stop(&mut self, bssid: &[u8; 6]) -> io::Result<Option<SavedFile>> {
+        let Some(mut extraction) = self.active.remove(bssid) else {
+            return Ok(None);
+        };
+        extraction.writer.flush()?;
+        Ok(Some((extraction.path, extraction.frames)))
+    }
+
+    pub fn set_clients(&mut self, bssid: &[u8; 6], clients: HashSet<[u8; 6]>) {
+        if let Some(extraction) = self.active.get_mut(bssid) {
+            extraction.clients = clients;
+        }
+    }
+
+    /// Write a raw frame to every file whose AP or clients it is addressed
+    /// to or from. `frame` is the 802.11 part of `raw`. Files that fail to
+    /// write are dropped and returned with their error.
+    pub fn write(&mut self, ts: SystemTime, raw: &[u8], frame: &[u8]) -> Vec<([u8; 6], PathBuf, io::Error)> {
+        // Control frames such as ACK and CTS carry only the receiver address
+        let addresses: Vec<[u8; 6]> = [4..10, 10..16, 16..22]
+            .into_iter()
+            .map_while(|range| frame.get(range)?.try_into().ok())
+            .collect();
+        let mut failed = Vec::new();
+        for (bssid, extraction) in &mut self.active {
+            let involved = addresses
+                .iter()
+                .any(|addr| addr == bssid || extraction.clients.contains(addr));
+            if !involved {
+                continue;
+            }
+            match extraction.writer.write_packet(ts, raw) {
+                Ok(()) => extraction.frames += 1,
+                Err(e) => failed.push((*bssid, extraction.path.clone(), e)),
+            }
+        }
+        for (bssid, _, _) in &failed {
+            self.active.remove(bssid);
+        }
+        failed
+    }
+
+    /// Flush and close every file, returning what was saved.
+    pub fn close_all(&mut self) -> Vec<([u8; 6], io::Result<SavedFile>)> {
+        self.active
+            .drain()
+            .map(|(bssid, mut extraction)| {
+                let result = extraction.writer.flush().map(|()| (extraction.path, extraction.frames));
+                (bssid, result)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    const AP: [u8; 6] = [0x02, 0, 0, 0, 0, 0xAA];
+    const CLIENT: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+    const OTHER_AP: [u8; 6] = [0x02, 0, 0, 0, 0, 0xBB];
+    const OTHER_CLIENT: [u8; 6] = [0x02, 0, 0, 0, 0, 0x02];
+
+    /// A To-DS data frame from `sta` through `bssid`, with no body.
+    fn data_frame(sta: [u8; 6], bssid: [u8; 6]) -> Vec<u8> {
+        let mut frame = vec![0x08, 0x01, 0, 0];
+        frame.extend_from_slice(&bssid);
+        frame.extend_from_slice(&sta);
+        frame.extend_from_slice(&[0xFF; 6]);
+        frame.extend_from_slice(&[0, 0]);
+        frame
+    }
+
+    /// An ACK, which names only its receiver.
+    fn ack(receiver: [u8; 6]) -> Vec<u8> {
+        let mut frame = vec![0xD4, 0x00, 0, 0];
+        frame.extend_from_slice(&receiver);
+        frame
+    }
+
+    /// Records in a saved pcap file, after checking its header.
+    fn records(path: &Path) -> Vec<Vec<u8>> {
+        let data = std::fs::read(path).unwrap();
+        assert_eq!(&data[..4], &0xA1B2_C3D4u32.to_le_bytes());
+        assert_eq!(&data[20..24], &127u32.to_le_bytes());
+        let mut rest = &data[24..];
+        let mut records = Vec::new();
+        while !rest.is_empty() {
+            let len = u32::from_le_bytes(rest[8..12].try_into().unwrap()) as usize;
+            records.push(rest[16..16 + len].to_vec());
+            rest = &rest[16 + len..];
+        }
+        records
+    }
+
+    #[test]
+    fn writes_only_frames_for_the_ap_and_its_clients() {
+        let dir = std::env::temp_dir().join(format!("wifimonitor-extract-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let ts = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let mut extractions = Extractions::new(127);
+        let path = extractions.start(AP, &dir, ts).unwrap().unwrap();
+        assert!(extractions.start(AP, &dir, ts).unwrap().is_none());
+        extractions.set_clients(&AP, HashSet::from([CLIENT]));
+
+        // A radiotap-sized prefix that must be written but not matched on
+        let header = [0u8; 8];
+        let frames = [
+            data_frame(CLIENT, AP),
+            ack(CLIENT),
+            data_frame(OTHER_CLIENT, OTHER_AP),
+            ack(OTHER_CLIENT),
+            // Too short to hold a receiver address
+            vec![0xD4, 0x00, 0, 0, 0x02],
+        ];
+        for frame in &frames {
+            let raw = [&header[..], frame].concat();
+            assert!(extractions.write(ts, &raw, frame).is_empty());
+        }
+
+        assert_eq!(extractions.stop(&AP).unwrap(), Some((path.clone(), 2)));
+        assert!(extractions.is_empty());
+        let expected = vec![[&header[..], &frames[0]].concat(), [&header[..], &frames[1]].concat()];
+        assert_eq!(records(&path), expected);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::model::{AppState, GpsFix};
+
+/// Default gpsd address.
+pub const DEFAULT_GPSD_ADDR: &str = "127.0.0.1:2947";
+
+/// Delay before reconnecting after gpsd goes away.
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Read timeout so the thread notices shutdown between reports.
+const READ_POLL: Duration = Duration::from_millis(500);
+
+/// The subset of a gpsd TPV (time-position-velocity) report we use.
+#[derive(Deserialize)]
+struct Tpv {
+    class: String,
+    #[serde(default)]
+    mode: u8,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    #[serde(rename = "altMSL")]
+    alt_msl: Option<f64>,
+    /// Older gpsd versions only report `alt`.
+    alt: Option<f64>,
+    eph: Option<f64>,
+}
+
+/// Follow gpsd on a separate thread and keep `AppState::gps_fix` current.
+pub fn start_gpsd(addr: &str, state: Arc<Mutex<AppState>>) -> thread::JoinHandle<()> {
+    let addr = addr.to_string();
+    state.lock().unwrap().gps_enabled = true;
+
+    thread::spawn(move || {
+        while state.lock().unwrap().is_running() {
+            // Connection errors are expected while gpsd restarts; just retry
+            let _ = follow_gpsd(&addr, &state);
+            state.lock().unwrap().gps_fix = None;
+
+            let retry_at = Instant::now() + RECONNECT_DELAY;
+            while Instant::now() < retry_at && state.lock().unwrap().is_running() {
+                thread::sleep(READ_POLL);
+            }
+        }
+    })
+}
+
+fn follow_gpsd(addr: &str, state: &Arc<Mutex<AppState>>) -> std::io::Result<()> {
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(READ_POLL))?;
+    stream.write_all(b"?WATCH={\"enable\":true,\"json\":true}\n")?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = Vec::new();
+
+    while state.lock().unwrap().is_running() {
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {
+                if let Ok(tpv) = serde_json::from_slice::<Tpv>(&line) {
+                    handle_tpv(tpv, state);
+                }
+                line.clear();
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn handle_tpv(tpv: Tpv, state: &Arc<Mutex<AppState>>) {
+    if tpv.class != "TPV" {
+        return;
+    }
+    let mut st = state.lock().unwrap();
+    // mode: 0/1 = no fix, 2 = 2D, 3 = 3D
+    st.gps_fix = match (tpv.mode, tpv.lat, tpv.lon) {
+        (2.., Some(lat), Some(lon)) => Some(GpsFix {
+            lat,
+            lon,
+            alt: if tpv.mode >= 3 { tpv.alt_msl.or(tpv.alt) } else { None },
+            accuracy_m: tpv.eph,
+            received_at: Instant::now(),
+        }),
+        _ => None,
+    };
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+
+use crate::capture::{ppi, prism, radiotap};
+use crate::model::phy::PhyInfo;
+
+const DLT_IEEE802_11: i32 = 105;
+const DLT_PRISM_HEADER: i32 = 119;
+const DLT_IEEE802_11_RADIO: i32 = 127;
+const DLT_IEEE802_11_RADIO_AVS: i32 = 163;
+const DLT_PPI: i32 = 192;
+
+/// How 802.11 frames are wrapped by the capture source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkType {
+    /// Bare 802.11 frames with no receive metadata.
+    Ieee80211,
+    Radiotap,
+    Ppi,
+    Prism,
+    Avs,
+}
+
+impl LinkType {
+    pub fn from_dlt(dlt: i32) -> Option<Self> {
+        match dlt {
+            DLT_IEEE802_11 => Some(LinkType::Ieee80211),
+            DLT_IEEE802_11_RADIO => Some(LinkType::Radiotap),
+            DLT_PPI => Some(LinkType::Ppi),
+            DLT_PRISM_HEADER => Some(LinkType::Prism),
+            DLT_IEEE802_11_RADIO_AVS => Some(LinkType::Avs),
+            _ => None,
+        }
+    }
+
+    pub fn dlt(self) -> i32 {
+        match self {
+            LinkType::Ieee80211 => DLT_IEEE802_11,
+            LinkType::Radiotap => DLT_IEEE802_11_RADIO,
+            LinkType::Ppi => DLT_PPI,
+            LinkType::Prism => DLT_PRISM_HEADER,
+            LinkType::Avs => DLT_IEEE802_11_RADIO_AVS,
+        }
+    }
+
+    /// Decode the metadata header, returning it and where the 802.11 frame starts.
+    pub fn parse(self, raw: &[u8]) -> Option<(PhyInfo, usize)> {
+        match self {
+            LinkType::Ieee80211 => Some((PhyInfo::default(), 0)),
+            LinkType::Radiotap => radiotap::parse(raw),
+            LinkType::Ppi => ppi::parse(raw),
+            // Some drivers write AVS headers under the Prism link type
+            LinkType::Prism => prism::parse_avs(raw).or_else(|| prism::parse_prism(raw)),
+            LinkType::Avs => prism::parse_avs(raw),
+        }
+    }
+}
+
+impl fmt::Display for LinkType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkType::Ieee80211 => write!(f, "802.11"),
+            LinkType::Radiotap => write!(f, "radiotap"),
+            LinkType::Ppi => write!(f, "PPI"),
+            LinkType::Prism => write!(f, "Prism"),
+            LinkType::Avs => write!(f, "AVS"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [LinkType; 5] = [
+        LinkType::Ieee80211,
+        LinkType::Radiotap,
+        LinkType::Ppi,
+        LinkType::Prism,
+        LinkType::Avs,
+    ];
+
+    /// An AVS header with every field zero.
+    fn avs_header() -> Vec<u8> {
+        let mut raw = vec![0u8; 64];
+        raw[0..4].copy_from_slice(&0x8021_1001u32.to_be_bytes());
+        raw[4..8].copy_from_slice(&64u32.to_be_bytes());
+        raw
+    }
+
+    /// A Prism header with every item zero.
+    fn prism_header() -> Vec<u8> {
+        let mut raw = vec![0u8; 144];
+        raw[4..8].copy_from_slice(&144u32.to_le_bytes());
+        raw
+    }
+
+    #[test]
+    fn dlt_round_trip() {
+        for link in ALL {
+            assert_eq!(LinkType::from_dlt(link.dlt()), Some(link));
+        }
+        // Ethernet
+        assert_eq!(LinkType::from_dlt(1), None);
+    }
+
+    #[test]
+    fn bare_frames_start_at_zero() {
+        let (phy, offset) = LinkType::Ieee80211.parse(&[0x80, 0x00]).unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(phy.signal_dbm, None);
+    }
+
+    #[test]
+    fn prism_link_accepts_avs_headers() {
+        assert_eq!(LinkType::Prism.parse(&avs_header()).map(|(_, offset)| offset), Some(64));
+        assert_eq!(LinkType::Prism.parse(&prism_header()).map(|(_, offset)| offset), Some(144));
+        assert_eq!(LinkType::Avs.parse(&avs_header()).map(|(_, offset)| offset), Some(64));
+        assert!(LinkType::Avs.parse(&prism_header()).is_none());
+    }
+
+    #[test]
+    fn ppi_header_length() {
+        let mut raw = vec![0, 0, 8, 0];
+        raw.extend_from_slice(&(DLT_IEEE802_11 as u32).to_le_bytes());
+        assert_eq!(LinkType::Ppi.parse(&raw).map(|(_, offset)| offset), Some(8));
+    }
+}
@@ -1,3 +1,11 @@
+pub mod airtime;
 pub mod channel_hopper;
+pub mod extract;
+pub mod gps;
 pub mod interface;
+pub mod link_type;
 pub mod packet_handler;
+pub mod ppi;
+pub mod prism;
+pub mod radiotap;
+pub mod trigger_ring;
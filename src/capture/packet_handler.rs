@@ -1,122 +1,675 @@
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use libwifi::frame::components::{MacAddress, RsnAkmSuite};
+use libwifi::frame::components::{DataHeader, MacAddress, RsnAkmSuite, StationInfo};
 use libwifi::parse_frame;
 use libwifi::Frame;
-use radiotap::Radiotap;
 
-use crate::model::{AccessPoint, AppState, EncryptionType};
+use crate::model::app_state::PcapStats;
+use crate::model::link_stats::SequenceInfo;
+use crate::model::phy::PhyInfo;
+use crate::model::spectrum::ChannelSpan;
+use crate::capture::airtime::frame_airtime;
+use crate::capture::link_type::LinkType;
+use crate::capture::extract::{Extractions, SavedFile};
+use crate::capture::trigger_ring::{RingConfig, RingEvent, TriggerRing};
+use crate::model::{AccessPoint, Alert, AppState, BeaconTiming, BssLoad, CaptureRequest, DecloakSource, EncryptionType, FrameKind, Severity, Station, TsfEvent};
+use crate::util::crc32::crc32;
+use crate::util::mac::{format_mac, is_multicast};
+use crate::util::time::format_duration;
+use crate::util::time_series::TimedSample;
 
-/// Parse a raw captured packet (with radiotap header) and update AppState.
-pub fn handle_packet(raw: &[u8], state: &Arc<Mutex<AppState>>) {
-    // Parse radiotap header
-    let radiotap = match Radiotap::from_bytes(raw) {
-        Ok(rt) => rt,
-        Err(_) => return,
-    };
+/// How often the capture thread refreshes libpcap drop counters.
+const PCAP_STATS_INTERVAL_SECS: u64 = 1;
+
+/// Length of the 802.11 frame check sequence.
+const FCS_LEN: usize = 4;
+
+/// BSS Load information element ID.
+const IE_BSS_LOAD: u8 = 11;
+
+/// VHT Operation information element ID.
+const IE_VHT_OPERATION: u8 = 192;
+
+/// Reduced Neighbor Report information element ID.
+const IE_REDUCED_NEIGHBOR_REPORT: u8 = 201;
+
+/// Element ID extension, and the extension ID of HE Operation.
+const IE_EXTENSION: u8 = 255;
+const EXT_HE_OPERATION: u8 = 36;
+
+/// HE Operation parameters: which optional fields follow the fixed ones.
+const HE_VHT_INFO_PRESENT: u32 = 1 << 14;
+const HE_COHOSTED_BSS: u32 = 1 << 15;
+const HE_6GHZ_INFO_PRESENT: u32 = 1 << 17;
 
-    let signal_dbm = radiotap
-        .antenna_signal
-        .map(|s| s.value)
-        .unwrap_or(-100);
+/// RNR BSS parameters: the neighbor is in our multiple BSSID set.
+const RNR_MULTIPLE_BSSID: u8 = 0x04;
+/// RNR BSS parameters: the neighbor is another radio of this AP.
+const RNR_COLOCATED: u8 = 0x40;
 
-    let channel_freq = radiotap.channel.map(|c| c.freq);
+/// LLC/SNAP header announcing an 802.1X (EAPOL) payload.
+const LLC_SNAP_EAPOL: [u8; 8] = [0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00, 0x88, 0x8E];
 
-    // The 802.11 frame starts after the radiotap header
-    let frame_start = radiotap.header.length as usize;
+/// EAPOL packet type of the key messages that make up a WPA handshake.
+const EAPOL_KEY: u8 = 3;
+
+/// What a beacon or probe response tells us about the AP that sent it.
+struct ApSighting {
+    bssid: [u8; 6],
+    /// Empty when the network is hidden.
+    ssid: String,
+    hidden: bool,
+    ssid_len_hint: Option<u8>,
+    channel: u8,
+    six_ghz: bool,
+    encryption: EncryptionType,
+    bss_load: Option<BssLoad>,
+    operating_span: Option<ChannelSpan>,
+    multi_bssid_indicator: Option<u8>,
+    colocated: Vec<[u8; 6]>,
+}
+
+/// Parse a raw captured packet and update AppState.
+///
+/// `link` says which metadata header wraps the frame, and `ts` is the
+/// capture timestamp from the pcap header. Returns where the 802.11 frame
+/// starts in `raw`, or None if there is no frame after the header.
+pub fn handle_packet(raw: &[u8], link: LinkType, ts: SystemTime, state: &Arc<Mutex<AppState>>) -> Option<usize> {
+    // Parse the metadata header; the 802.11 frame starts after it
+    let (phy, frame_start) = link.parse(raw)?;
     if frame_start >= raw.len() {
-        return;
+        return None;
     }
-    let frame_bytes = &raw[frame_start..];
+    handle_frame(&raw[frame_start..], phy, ts, state);
+    Some(frame_start)
+}
+
+/// Update AppState from one 802.11 frame and its receive metadata.
+fn handle_frame(frame_bytes: &[u8], phy: PhyInfo, ts: SystemTime, state: &Arc<Mutex<AppState>>) {
+    // A frame that fails its checksum still took airtime, but nothing in it can be trusted
+    let corrupt = phy.bad_fcs || (phy.fcs_included && !fcs_valid(frame_bytes));
+    let frame_body = if phy.fcs_included && !corrupt {
+        &frame_bytes[..frame_bytes.len() - FCS_LEN]
+    } else {
+        frame_bytes
+    };
+
+    let signal_dbm = phy.signal_dbm.unwrap_or(-100);
+    let channel = phy.freq_mhz.map(freq_to_channel).filter(|&ch| ch > 0);
+    let channel_6g = phy.freq_mhz.and_then(freq_to_channel_6g);
+    let airtime = frame_airtime(&phy, frame_bytes.len());
+
+    // Some captures carry no header timestamps; the radio's TSF timer still
+    // gives correct spacing between frames
+    let ts = match phy.tsft {
+        Some(tsft) if ts == UNIX_EPOCH => UNIX_EPOCH + Duration::from_micros(tsft),
+        _ => ts,
+    };
 
     // Update packet counters
-    {
+    let now = {
         let mut st = state.lock().unwrap();
+        let now = st.clock.packet_time(ts);
+        // Close out earlier seconds before counting this frame into the current one
+        st.tick_if_due();
         st.total_packets += 1;
         st.packets_this_second += 1;
+        if corrupt {
+            st.frame_stats.corrupt += 1;
+        } else {
+            st.frame_stats.record(FrameKind::from_frame_control(frame_bytes[0]));
+        }
 
         // Track channel usage
-        if let Some(freq) = channel_freq {
-            let ch = freq_to_channel(freq);
-            if ch > 0 {
-                *st.channel_packets.entry(ch).or_insert(0) += 1;
+        if let Some(ch) = channel {
+            if corrupt {
+                *st.channel_corrupt.entry(ch).or_insert(0) += 1;
+            }
+            if let Some(noise) = phy.noise_dbm {
+                st.record_noise(ch, noise);
             }
+            *st.channel_packets.entry(ch).or_insert(0) += 1;
+            *st.channel_packets_this_second.entry(ch).or_insert(0) += 1;
+            if st.channel_from_capture && (ch, false) != (st.current_channel, st.current_six_ghz) {
+                st.set_current_channel(ch, false);
+            }
+            st.record_airtime(ch, false, airtime);
+        }
+        // The other per-channel counters are keyed by 2.4/5 GHz numbers only
+        if let Some(ch) = channel_6g {
+            if st.channel_from_capture && (ch, true) != (st.current_channel, st.current_six_ghz) {
+                st.set_current_channel(ch, true);
+            }
+            st.record_airtime(ch, true, airtime);
+        }
+
+        if let Some(info) = sequence_info(frame_body).filter(|_| !corrupt) {
+            st.record_sequence(channel, &info, now);
+        }
+        now
+    };
+
+    if corrupt {
+        return;
+    }
+
+    if let Some((station, bssid)) = eapol_key_pair(frame_body) {
+        let mut st = state.lock().unwrap();
+        if st.record_handshake(station, bssid, now) {
+            let message = format!(
+                "EAPOL handshake between {} and {}",
+                format_mac(&station),
+                format_mac(&bssid)
+            );
+            st.request_capture(message.clone());
+            st.raise_alert(Severity::Info, message, Some(bssid));
         }
     }
 
-    // Try to parse as 802.11 frame (assume no FCS at end)
-    let frame = match parse_frame(frame_bytes, false) {
+    // Try to parse as 802.11 frame (FCS already checked and stripped)
+    let frame = match parse_frame(frame_body, false) {
         Ok(f) => f,
         Err(_) => return,
     };
 
-    // We only care about beacon and probe response frames for AP discovery
+    // Beacons and probe responses drive AP discovery; client frames drive stations
     match &frame {
         Frame::Beacon(beacon) => {
-            let bssid = mac_to_bytes(&beacon.header.address_3);
-            let ssid = beacon
-                .station_info
-                .ssid
-                .as_ref()
-                .map(|s| s.to_string())
-                .unwrap_or_default();
-            let channel = beacon
-                .station_info
-                .ds_parameter_set
-                .unwrap_or(0);
-            let encryption = determine_encryption_from_station_info(
+            let sighting = ap_sighting(
+                &beacon.header.address_3,
                 &beacon.station_info,
                 beacon.capability_info,
             );
-
-            update_ap(state, bssid, ssid, channel, encryption, signal_dbm);
+            let timing = BeaconTiming {
+                interval_tu: beacon.beacon_interval,
+                tsf: beacon.timestamp,
+                local_tsft: phy.tsft,
+            };
+            update_ap(state, sighting, Some(timing), &phy, now);
         }
         Frame::ProbeResponse(probe_resp) => {
-            let bssid = mac_to_bytes(&probe_resp.header.address_3);
-            let ssid = probe_resp
-                .station_info
-                .ssid
-                .as_ref()
-                .map(|s| s.to_string())
-                .unwrap_or_default();
-            let channel = probe_resp
-                .station_info
-                .ds_parameter_set
-                .unwrap_or(0);
-            let encryption = determine_encryption_from_station_info(
+            let sighting = ap_sighting(
+                &probe_resp.header.address_3,
                 &probe_resp.station_info,
                 probe_resp.capability_info,
             );
+            update_ap(state, sighting, None, &phy, now);
+        }
+        Frame::ProbeRequest(probe_req) => {
+            let mac = mac_to_bytes(&probe_req.header.address_2);
+            update_station(state, mac, None, Some(signal_dbm), now);
+            // Wildcard probes carry an empty SSID and say nothing about the client
+            if let Some(ssid) = real_ssid(&probe_req.station_info) {
+                state.lock().unwrap().record_probe(mac, ssid, now);
+            }
+        }
+        Frame::AssociationRequest(assoc) => {
+            let mac = mac_to_bytes(&assoc.header.address_2);
+            let bssid = mac_to_bytes(&assoc.header.address_3);
+            update_station(state, mac, Some(bssid), Some(signal_dbm), now);
+            decloak_from_client(state, bssid, &assoc.station_info, DecloakSource::AssociationRequest);
+        }
+        Frame::ReassociationRequest(reassoc) => {
+            let mac = mac_to_bytes(&reassoc.header.address_2);
+            let bssid = mac_to_bytes(&reassoc.header.address_3);
+            update_station(state, mac, Some(bssid), Some(signal_dbm), now);
+            decloak_from_client(state, bssid, &reassoc.station_info, DecloakSource::ReassociationRequest);
+        }
+        _ => {
+            if let Some(header) = data_header(&frame) {
+                handle_data_header(state, header, signal_dbm, now);
+            }
+        }
+    }
+}
 
-            update_ap(state, bssid, ssid, channel, encryption, signal_dbm);
+/// Data frames that carry a header we can use to attribute stations to a BSS.
+fn data_header(frame: &Frame) -> Option<&DataHeader> {
+    match frame {
+        Frame::Data(f) => Some(&f.header),
+        Frame::QosData(f) => Some(&f.header),
+        Frame::NullData(f) => Some(&f.header),
+        Frame::QosNull(f) => Some(&f.header),
+        _ => None,
+    }
+}
+
+fn handle_data_header(
+    state: &Arc<Mutex<AppState>>,
+    header: &DataHeader,
+    signal_dbm: i8,
+    now: Instant,
+) {
+    let fc = &header.frame_control;
+    match (fc.to_ds(), fc.from_ds()) {
+        // Station → AP: the transmitter is the station, so the signal is theirs
+        (true, false) => {
+            let mac = mac_to_bytes(&header.address_2);
+            let bssid = mac_to_bytes(&header.address_1);
+            update_station(state, mac, Some(bssid), Some(signal_dbm), now);
+        }
+        // AP → station: we only learn the association, not the station's signal
+        (false, true) => {
+            let mac = mac_to_bytes(&header.address_1);
+            let bssid = mac_to_bytes(&header.address_2);
+            if !is_multicast(&mac) {
+                update_station(state, mac, Some(bssid), None, now);
+            }
         }
         _ => {}
     }
 }
 
+fn update_station(
+    state: &Arc<Mutex<AppState>>,
+    mac: [u8; 6],
+    bssid: Option<[u8; 6]>,
+    signal_dbm: Option<i8>,
+    now: Instant,
+) {
+    if is_multicast(&mac) {
+        return;
+    }
+    let mut st = state.lock().unwrap();
+    // Frames between APs (e.g. WDS) would otherwise list the AP as its own client
+    if st.access_points.contains_key(&mac) {
+        return;
+    }
+    let sta = st.stations.entry(mac).or_insert_with(|| Station::new(mac, now));
+
+    if bssid.is_some() {
+        sta.bssid = bssid;
+    }
+    if let Some(dbm) = signal_dbm {
+        sta.signal_dbm = dbm;
+    }
+    sta.last_seen = now;
+    sta.packet_count += 1;
+}
+
+fn ap_sighting(bssid: &MacAddress, info: &StationInfo, capability_info: u16) -> ApSighting {
+    let ssid = real_ssid(info);
+    // Some APs hide by zeroing the SSID bytes, which still gives away its length
+    let ssid_len_hint = info.ssid_length.filter(|&len| ssid.is_none() && len > 0);
+    // 6 GHz APs send no DS Parameter Set; their channel is in HE Operation
+    let he_6g = parse_he_6ghz(info);
+    ApSighting {
+        bssid: mac_to_bytes(bssid),
+        hidden: ssid.is_none(),
+        ssid: ssid.unwrap_or_default(),
+        ssid_len_hint: ssid_len_hint.map(|len| len.min(u8::MAX as usize) as u8),
+        channel: he_6g.map_or(info.ds_parameter_set.unwrap_or(0), |(primary, _)| primary),
+        six_ghz: he_6g.is_some(),
+        encryption: determine_encryption_from_station_info(info, capability_info),
+        bss_load: parse_bss_load(info),
+        operating_span: he_6g.map_or_else(|| parse_operating_span(info), |(_, span)| span),
+        multi_bssid_indicator: info.multiple_bssid.as_ref().map(|m| m.max_bssid_indicator),
+        colocated: parse_colocated(info),
+    }
+}
+
+/// The SSID element, unless it is missing, empty or zeroed out.
+fn real_ssid(info: &StationInfo) -> Option<String> {
+    info.ssid
+        .as_ref()
+        .filter(|ssid| !ssid.chars().all(|c| c == '\0'))
+        .cloned()
+}
+
+/// Fill in a hidden AP's SSID from a client's (re)association request.
+fn decloak_from_client(state: &Arc<Mutex<AppState>>, bssid: [u8; 6], info: &StationInfo, source: DecloakSource) {
+    let Some(ssid) = real_ssid(info) else {
+        return;
+    };
+    let mut st = state.lock().unwrap();
+    let Some(ap) = st.access_points.get_mut(&bssid) else {
+        return;
+    };
+    // A client may ask for the wrong network; a length hint rules that out
+    if !ap.ssid.is_empty() || ap.hidden_ssid_len.is_some_and(|len| len as usize != ssid.len()) {
+        return;
+    }
+    ap.ssid = ssid;
+    ap.decloaked_by = Some(source);
+    if ap.hidden {
+        let message = decloak_message(ap);
+        st.raise_alert(Severity::Info, message, Some(bssid));
+    }
+}
+
+fn decloak_message(ap: &AccessPoint) -> String {
+    format!(
+        "Hidden network {} is \"{}\" (from {})",
+        ap.bssid_str(),
+        ap.ssid,
+        ap.decloaked_by.map(|source| source.to_string()).unwrap_or_default()
+    )
+}
+
+/// BSS Load element: station count (u16), channel utilization (u8), admission capacity (u16).
+fn parse_bss_load(info: &StationInfo) -> Option<BssLoad> {
+    let (_, body) = info.data.iter().find(|(id, _)| *id == IE_BSS_LOAD)?;
+    if body.len() < 3 {
+        return None;
+    }
+    Some(BssLoad {
+        station_count: u16::from_le_bytes([body[0], body[1]]),
+        channel_utilization: body[2],
+    })
+}
+
+/// `beacon` is given only for beacons, which are sent on a schedule.
 fn update_ap(
     state: &Arc<Mutex<AppState>>,
-    bssid: [u8; 6],
-    ssid: String,
-    channel: u8,
-    encryption: EncryptionType,
-    signal_dbm: i8,
+    sighting: ApSighting,
+    beacon: Option<BeaconTiming>,
+    phy: &PhyInfo,
+    now: Instant,
 ) {
+    let ApSighting {
+        bssid,
+        ssid,
+        hidden,
+        ssid_len_hint,
+        channel,
+        six_ghz,
+        encryption,
+        bss_load,
+        operating_span,
+        multi_bssid_indicator,
+        colocated,
+    } = sighting;
+    let signal_dbm = phy.signal_dbm.unwrap_or(-100);
     let mut st = state.lock().unwrap();
-    let ap = st.access_points.entry(bssid).or_insert_with(|| AccessPoint::new(bssid));
+    let fix = st.current_fix().copied();
+    let seen_at = st.clock.system_time(now);
+    let tuned = (st.current_channel, st.current_six_ghz);
+    let listening_since = st.listening_since;
+    if !st.access_points.contains_key(&bssid) {
+        let ap = st.new_access_point(bssid, now, seen_at);
+        st.access_points.insert(bssid, ap);
+    }
+    let Some(ap) = st.access_points.get_mut(&bssid) else {
+        return;
+    };
 
+    if beacon.is_some() {
+        ap.hidden = hidden;
+        if hidden {
+            ap.hidden_ssid_len = ssid_len_hint.or(ap.hidden_ssid_len);
+        }
+    }
+    let mut decloaked = false;
     if !ssid.is_empty() {
-        ap.ssid = ssid;
+        if beacon.is_some() {
+            ap.record_beaconed_ssid(&ssid);
+            ap.ssid = ssid;
+        } else {
+            ap.record_responded_ssid(&ssid);
+            // A Karma responder answers with whatever was asked; keep the first
+            if ap.ssid.is_empty() {
+                ap.decloaked_by = Some(DecloakSource::ProbeResponse);
+                decloaked = ap.hidden;
+                ap.ssid = ssid;
+            }
+        }
     }
-    if channel > 0 {
-        ap.channel = channel;
+    // Without HE Operation, a frame heard on 6 GHz still places the AP there
+    let radio_6g = phy.freq_mhz.and_then(freq_to_channel_6g);
+    match radio_6g {
+        Some(ch) if channel == 0 => {
+            ap.channel = ch;
+            ap.six_ghz = true;
+        }
+        _ if channel > 0 => {
+            ap.channel = channel;
+            ap.six_ghz = six_ghz;
+        }
+        _ => {}
     }
     ap.encryption = encryption;
+    if bss_load.is_some() {
+        ap.bss_load = bss_load;
+    }
+    ap.operating_span = operating_span;
+    if multi_bssid_indicator.is_some() {
+        ap.multi_bssid_indicator = multi_bssid_indicator;
+    }
+    if !colocated.is_empty() {
+        ap.colocated = colocated;
+    }
     ap.signal_dbm = signal_dbm;
-    ap.signal_history.push(signal_dbm);
-    ap.last_seen = Instant::now();
+    ap.noise_dbm = phy.noise_dbm.or(ap.noise_dbm);
+    ap.rate = phy.rate.or(ap.rate);
+    ap.chain_signal.clone_from(&phy.chain_signal);
+    ap.signal_history.push(TimedSample {
+        at: now,
+        value: signal_dbm,
+    });
+    ap.signal_rollups.add(now, signal_dbm as f32);
+    ap.last_seen = now;
     ap.beacon_count += 1;
+    let mut tsf_event = None;
+    if let Some(timing) = beacon {
+        // Gaps only count as missed beacons if we were on the AP's channel throughout
+        let listening = ((ap.channel, ap.six_ghz) == tuned).then_some(listening_since);
+        tsf_event = ap.record_beacon(timing, now, listening).filter(|_| ap.tsf_alert_due(now));
+    }
+    if let Some(fix) = fix {
+        ap.location.observe(&fix, signal_dbm);
+    }
+
+    let karma = (ap.is_karma() && ap.karma_alert_due(now)).then(|| ap.impersonated_ssids().join(", "));
+    let (ssid, channel) = (ap.ssid.clone(), ap.channel);
+    if decloaked {
+        let message = decloak_message(ap);
+        st.raise_alert(Severity::Info, message, Some(bssid));
+    }
+    if let Some(impersonated) = karma {
+        st.raise_alert(
+            Severity::High,
+            format!(
+                "{} answers probes for SSIDs it never beacons (Karma/MANA?): {impersonated}",
+                format_mac(&bssid)
+            ),
+            Some(bssid),
+        );
+    }
+    if let Some(event) = tsf_event {
+        let label = if ssid.is_empty() { format_mac(&bssid) } else { ssid.clone() };
+        let (severity, message) = match event {
+            TsfEvent::Reset(prev_tsf) => (
+                Severity::Warning,
+                format!(
+                    "{label} rebooted (TSF reset after {} uptime)",
+                    format_duration(Duration::from_micros(prev_tsf))
+                ),
+            ),
+            TsfEvent::Conflict => (
+                Severity::High,
+                format!(
+                    "{label} ({}) is beaconed by more than one radio (TSF clocks disagree): spoofed BSSID or evil twin?",
+                    format_mac(&bssid)
+                ),
+            ),
+        };
+        st.raise_alert(severity, message, Some(bssid));
+    }
+    if let Some(survey) = st.survey.as_mut() {
+        survey.record(bssid, &ssid, channel, signal_dbm, now, seen_at);
+    }
+}
+
+/// Bonded channel from the VHT operation element (width, centre segment 0,
+/// centre segment 1) or, failing that, the HT operation element's secondary
+/// channel offset. None for a plain 20 MHz channel.
+fn parse_operating_span(info: &StationInfo) -> Option<ChannelSpan> {
+    if let Some((_, vht)) = info.data.iter().find(|(id, _)| *id == IE_VHT_OPERATION) {
+        if vht.len() >= 3 && vht[0] != 0 {
+            let (ccfs0, ccfs1) = (vht[1], vht[2]);
+            return match vht[0] {
+                // 160 MHz is signalled by a second segment 8 channels from the first
+                1 if ccfs1 != 0 && ccfs1.abs_diff(ccfs0) == 8 => ChannelSpan::bonded(ccfs1, 160),
+                2 => ChannelSpan::bonded(ccfs0, 160),
+                // 80 MHz, or the first segment of 80+80
+                _ => ChannelSpan::bonded(ccfs0, 80),
+            };
+        }
+    }
+
+    let ht = info.ht_information.as_ref()?;
+    let flags = *ht.other_data.first()?;
+    // STA channel width bit clear: 20 MHz only
+    if flags & 0x04 == 0 {
+        return None;
+    }
+    match flags & 0x03 {
+        1 => ChannelSpan::bonded(ht.primary_channel.checked_add(2)?, 40),
+        3 => ChannelSpan::bonded(ht.primary_channel.checked_sub(2)?, 40),
+        _ => None,
+    }
+}
+
+/// Primary channel and bonded span from the HE Operation element's 6 GHz
+/// Operation Information, which 6 GHz APs send instead of DS Parameters.
+///
+/// After the extension ID come 3 bytes of HE operation parameters, the BSS
+/// colour and the basic HE-MCS set (3 bytes), then optional VHT operation
+/// info (3) and co-hosted BSSID indicator (1), then the 6 GHz info: primary
+/// channel, control (low 2 bits: 20/40/80/160 MHz), CCFS0, CCFS1, min rate.
+fn parse_he_6ghz(info: &StationInfo) -> Option<(u8, Option<ChannelSpan>)> {
+    let (_, he) = info
+        .data
+        .iter()
+        .find(|(id, body)| *id == IE_EXTENSION && body.first() == Some(&EXT_HE_OPERATION))?;
+    let params = u32::from_le_bytes([*he.get(1)?, *he.get(2)?, *he.get(3)?, 0]);
+    if params & HE_6GHZ_INFO_PRESENT == 0 {
+        return None;
+    }
+    let mut pos = 7;
+    if params & HE_VHT_INFO_PRESENT != 0 {
+        pos += 3;
+    }
+    if params & HE_COHOSTED_BSS != 0 {
+        pos += 1;
+    }
+    let op = he.get(pos..pos + 5)?;
+    let (primary, ccfs0, ccfs1) = (op[0], op[2], op[3]);
+    let span = match op[1] & 0x03 {
+        0 => None,
+        1 => ChannelSpan::bonded_6g(ccfs0, 40),
+        2 => ChannelSpan::bonded_6g(ccfs0, 80),
+        // 160 MHz puts its centre in CCFS1; 80+80 is read as its first segment
+        _ if ccfs1 != 0 && ccfs1.abs_diff(ccfs0) == 8 => ChannelSpan::bonded_6g(ccfs1, 160),
+        _ => ChannelSpan::bonded_6g(ccfs0, 80),
+    };
+    Some((primary, span))
+}
+
+/// BSSIDs a Reduced Neighbor Report marks as co-located or in our multiple
+/// BSSID set.
+///
+/// Each neighbor entry is a TBTT header (u16: type, filtered, count - 1,
+/// info length), operating class, channel, then the TBTT infos. Infos of 7+
+/// bytes carry a BSSID after the TBTT offset, followed by BSS parameters at
+/// byte 7 (no short SSID) or 11 (with short SSID).
+fn parse_colocated(info: &StationInfo) -> Vec<[u8; 6]> {
+    let mut bssids = Vec::new();
+    for (_, body) in info.data.iter().filter(|(id, _)| *id == IE_REDUCED_NEIGHBOR_REPORT) {
+        let mut pos = 0;
+        while pos + 4 <= body.len() {
+            let header = u16::from_le_bytes([body[pos], body[pos + 1]]);
+            let count = ((header >> 4) & 0x0f) as usize + 1;
+            let info_len = (header >> 8) as usize;
+            pos += 4;
+            for _ in 0..count {
+                let Some(tbtt) = body.get(pos..pos + info_len) else {
+                    return bssids;
+                };
+                pos += info_len;
+                let params = match info_len {
+                    8 | 9 => tbtt[7],
+                    12 | 13 => tbtt[11],
+                    _ => continue,
+                };
+                if params & (RNR_COLOCATED | RNR_MULTIPLE_BSSID) != 0 {
+                    bssids.push(tbtt[1..7].try_into().unwrap_or_default());
+                }
+            }
+        }
+    }
+    bssids
+}
+
+/// Sequence control and retry flag from a management or data frame header.
+fn sequence_info(frame: &[u8]) -> Option<SequenceInfo> {
+    if frame.len() < 24 {
+        return None;
+    }
+    let (fc0, fc1) = (frame[0], frame[1]);
+    let stream = match (fc0 >> 2) & 0b11 {
+        0 => SequenceInfo::STREAM_MANAGEMENT,
+        // QoS data subtypes keep a counter per TID, from the QoS control field
+        2 if fc0 & 0x80 != 0 => {
+            let qos_offset = if fc1 & 0x03 == 0x03 { 30 } else { 24 };
+            *frame.get(qos_offset)? & 0x0f
+        }
+        2 => SequenceInfo::STREAM_DATA,
+        _ => return None,
+    };
+    let seq_ctrl = u16::from_le_bytes([frame[22], frame[23]]);
+    Some(SequenceInfo {
+        transmitter: frame[10..16].try_into().ok()?,
+        stream,
+        seq: seq_ctrl >> 4,
+        fragment: (seq_ctrl & 0x0f) as u8,
+        retry: fc1 & 0x08 != 0,
+    })
+}
+
+/// Station and BSSID of an unprotected data frame carrying an EAPOL-Key
+/// message, i.e. part of a WPA handshake.
+fn eapol_key_pair(frame: &[u8]) -> Option<([u8; 6], [u8; 6])> {
+    let (fc0, fc1) = (*frame.first()?, *frame.get(1)?);
+    // Data frames only, and not yet encrypted
+    if (fc0 >> 2) & 0x03 != 2 || fc1 & 0x40 != 0 {
+        return None;
+    }
+    let (to_ds, from_ds) = (fc1 & 0x01 != 0, fc1 & 0x02 != 0);
+    let qos = fc0 & 0x80 != 0;
+    let mut header_len = 24;
+    if to_ds && from_ds {
+        header_len += 6;
+    }
+    if qos {
+        header_len += 2;
+        // The order bit on QoS data means an HT Control field follows
+        if fc1 & 0x80 != 0 {
+            header_len += 4;
+        }
+    }
+    let body = frame.get(header_len..)?;
+    if body.get(..LLC_SNAP_EAPOL.len())? != LLC_SNAP_EAPOL || *body.get(LLC_SNAP_EAPOL.len() + 1)? != EAPOL_KEY {
+        return None;
+    }
+    let addr = |i: usize| -> Option<[u8; 6]> { frame.get(4 + 6 * i..10 + 6 * i)?.try_into().ok() };
+    let (addr1, addr2) = (addr(0)?, addr(1)?);
+    match (to_ds, from_ds) {
+        (true, false) => Some((addr2, addr1)),
+        (false, true) => Some((addr1, addr2)),
+        _ => None,
+    }
+}
+
+/// Check the trailing FCS (little-endian CRC-32 over the rest of the frame).
+fn fcs_valid(frame: &[u8]) -> bool {
+    let Some(split) = frame.len().checked_sub(FCS_LEN) else {
+        return false;
+    };
+    let (body, fcs) = frame.split_at(split);
+    crc32(body) == u32::from_le_bytes([fcs[0], fcs[1], fcs[2], fcs[3]])
 }
 
 fn mac_to_bytes(mac: &MacAddress) -> [u8; 6] {
@@ -124,7 +677,7 @@ fn mac_to_bytes(mac: &MacAddress) -> [u8; 6] {
 }
 
 fn determine_encryption_from_station_info(
-    station_info: &libwifi::frame::components::StationInfo,
+    station_info: &StationInfo,
     capability_info: u16,
 ) -> EncryptionType {
     if let Some(rsn) = &station_info.rsn_information {
@@ -194,12 +747,40 @@ fn freq_to_channel(freq: u16) -> u8 {
     }
 }
 
+/// Convert a 6 GHz frequency (MHz) to its channel number, which overlaps
+/// the 2.4 GHz numbering.
+fn freq_to_channel_6g(freq: u16) -> Option<u8> {
+    match freq {
+        5935 => Some(2),
+        5955..=7115 if (freq - 5955).is_multiple_of(20) => Some(((freq - 5950) / 5) as u8),
+        _ => None,
+    }
+}
+
+fn update_pcap_stats(state: &Arc<Mutex<AppState>>, stat: pcap::Stat) {
+    let mut st = state.lock().unwrap();
+    let newly_dropped = stat.dropped.saturating_sub(st.pcap_stats.dropped);
+    if newly_dropped > 0 {
+        st.raise_alert(
+            Severity::Warning,
+            format!("Capture dropped {newly_dropped} packets (kernel buffer full)"),
+            None,
+        );
+    }
+    st.pcap_stats = PcapStats {
+        received: stat.received,
+        dropped: stat.dropped,
+        if_dropped: stat.if_dropped,
+    };
+}
+
 /// Start the capture loop on a separate thread.
 /// `use_rfmon`: if true, ask pcap to enable monitor mode (only when we haven't done it via iw).
 pub fn start_capture(
     interface: &str,
     state: Arc<Mutex<AppState>>,
     use_rfmon: bool,
+    ring: Option<RingConfig>,
 ) -> std::thread::JoinHandle<()> {
     let iface = interface.to_string();
     std::thread::spawn(move || {
@@ -228,19 +809,265 @@ pub fn start_capture(
             }
         };
 
-        while state.lock().unwrap().is_running() {
-            match cap.next_packet() {
-                Ok(packet) => {
-                    handle_packet(packet.data, &state);
+        // Drivers offering several headers usually default to the poorest one
+        if let Ok(links) = cap.list_datalinks() {
+            if let Some(&radiotap) = links.iter().find(|l| l.0 == LinkType::Radiotap.dlt()) {
+                let _ = cap.set_datalink(radiotap);
+            }
+        }
+
+        run_capture(&mut cap, &state, ring, true);
+    })
+}
+
+/// Replay a pcap file through the packet handler as fast as it can be read.
+///
+/// The state's clock should be a fixed clock so ages and rates follow the
+/// capture timestamps. The thread ends at the end of the file and leaves the
+/// results on screen.
+pub fn start_replay(
+    path: &Path,
+    state: Arc<Mutex<AppState>>,
+    ring: Option<RingConfig>,
+) -> std::thread::JoinHandle<()> {
+    let path = path.to_path_buf();
+    std::thread::spawn(move || {
+        let mut cap = match pcap::Capture::from_file(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                let msg = format!("Failed to open capture file '{}': {}", path.display(), e);
+                let mut st = state.lock().unwrap();
+                st.error = Some(msg);
+                st.stop();
+                return;
+            }
+        };
+
+        run_capture(&mut cap, &state, ring, false);
+    })
+}
+
+/// Feed packets from `cap` to the handler until stopped or out of packets,
+/// keeping the trigger ring (if any) filled and per-AP extractions written.
+/// `live` captures time trigger captures by the wall clock between frames.
+fn run_capture<T: pcap::Activated + ?Sized>(
+    cap: &mut pcap::Capture<T>,
+    state: &Arc<Mutex<AppState>>,
+    ring: Option<RingConfig>,
+    live: bool,
+) {
+    let dlt = cap.get_datalink();
+    let Some(link) = LinkType::from_dlt(dlt.0) else {
+        let name = dlt.get_name().unwrap_or_else(|_| dlt.0.to_string());
+        let mut st = state.lock().unwrap();
+        st.error = Some(format!("Unsupported link type {name}; expected 802.11 frames"));
+        st.stop();
+        return;
+    };
+    let mut ring = ring.map(|config| TriggerRing::new(config, link.dlt()));
+    let mut extractions = Extractions::new(link.dlt());
+    let mut last_stats = Instant::now();
+    let mut last_clients = Instant::now();
+    // Names new files after capture time, so replays match their source
+    let mut last_ts = SystemTime::now();
+
+    loop {
+        let (requests, dir) = {
+            let mut st = state.lock().unwrap();
+            if !st.is_running() {
+                break;
+            }
+            if !extractions.is_empty() && last_clients.elapsed() >= EXTRACT_CLIENT_REFRESH {
+                refresh_extract_clients(&st, &mut extractions);
+                last_clients = Instant::now();
+            }
+            let requests = std::mem::take(&mut st.capture_requests);
+            let dir = (!requests.is_empty()).then(|| st.capture_dir.clone());
+            (requests, dir)
+        };
+        if let Some(dir) = dir {
+            for request in requests {
+                handle_capture_request(request, &dir, last_ts, &mut ring, &mut extractions, state);
+            }
+        }
+
+        // Live captures can go quiet, so the post-event window runs on wall time
+        if let Some(ring) = ring.as_mut() {
+            let now = if live { SystemTime::now() } else { last_ts };
+            if let Some(event) = ring.poll(now).transpose() {
+                report_ring_event(state, event, None, ring);
+            }
+        }
+
+        if last_stats.elapsed().as_secs() >= PCAP_STATS_INTERVAL_SECS {
+            if let Ok(stat) = cap.stats() {
+                update_pcap_stats(state, stat);
+            }
+            last_stats = Instant::now();
+        }
+
+        match cap.next_packet() {
+            Ok(packet) => {
+                let ts = timeval_to_system_time(&packet.header.ts);
+                last_ts = ts;
+                if let Some(ring) = ring.as_mut() {
+                    let event = ring.push(ts, packet.data).transpose();
+                    if let Some(event) = event {
+                        report_ring_event(state, event, None, ring);
+                    }
                 }
-                Err(pcap::Error::TimeoutExpired) => continue,
-                Err(e) => {
-                    let mut st = state.lock().unwrap();
-                    st.error = Some(format!("Capture error: {e}"));
-                    st.stop();
-                    break;
+                let frame_start = handle_packet(packet.data, link, ts, state);
+                if let Some(frame_start) = frame_start.filter(|_| !extractions.is_empty()) {
+                    write_extractions(&mut extractions, ts, packet.data, frame_start, state);
                 }
             }
+            Err(pcap::Error::TimeoutExpired) => continue,
+            Err(pcap::Error::NoMorePackets) => break,
+            Err(e) => {
+                let mut st = state.lock().unwrap();
+                st.error = Some(format!("Capture error: {e}"));
+                st.stop();
+                break;
+            }
         }
-    })
+    }
+
+    // A capture still collecting post-event frames keeps what it has
+    if let Some(ring) = ring.as_mut() {
+        if let Some(event) = ring.close().transpose() {
+            report_ring_event(state, event, None, ring);
+        }
+    }
+    for (bssid, result) in extractions.close_all() {
+        report_extract_stopped(state, bssid, result);
+    }
+}
+
+/// How often extractions pick up newly associated clients.
+const EXTRACT_CLIENT_REFRESH: Duration = Duration::from_secs(1);
+
+fn handle_capture_request(
+    request: CaptureRequest,
+    dir: &Path,
+    now: SystemTime,
+    ring: &mut Option<TriggerRing>,
+    extractions: &mut Extractions,
+    state: &Arc<Mutex<AppState>>,
+) {
+    match request {
+        CaptureRequest::Trigger(reason) => {
+            if let Some(ring) = ring.as_mut() {
+                let event = ring.trigger(dir);
+                report_ring_event(state, event, Some(&reason), ring);
+            }
+        }
+        CaptureRequest::ExportRing => {
+            if let Some(ring) = ring.as_ref() {
+                let message = match ring.export(dir) {
+                    Ok((path, frames)) => format!("Exported {frames} ring frames to {}", path.display()),
+                    Err(e) => format!("Ring export failed: {e}"),
+                };
+                report_capture(state, message, None);
+            }
+        }
+        CaptureRequest::StartExtract(bssid) => match extractions.start(bssid, dir, now) {
+            Ok(Some(path)) => {
+                let mut st = state.lock().unwrap();
+                refresh_extract_clients(&st, extractions);
+                let message = format!("Saving frames for {} to {}", format_mac(&bssid), path.display());
+                st.extracting.insert(bssid, path);
+                let now = st.now();
+                st.alerts.push(Alert::new(Severity::Info, message, Some(bssid), now));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let message = format!("Extraction for {} failed: {e}", format_mac(&bssid));
+                report_capture(state, message, Some(bssid));
+            }
+        },
+        CaptureRequest::StopExtract(bssid) => {
+            if let Some(result) = extractions.stop(&bssid).transpose() {
+                report_extract_stopped(state, bssid, result);
+            }
+        }
+    }
+}
+
+/// Point each extraction at the stations currently associated with its AP.
+fn refresh_extract_clients(st: &AppState, extractions: &mut Extractions) {
+    let bssids: Vec<[u8; 6]> = extractions.bssids().copied().collect();
+    for bssid in bssids {
+        let clients = st
+            .stations
+            .values()
+            .filter(|sta| sta.bssid == Some(bssid))
+            .map(|sta| sta.mac)
+            .collect();
+        extractions.set_clients(&bssid, clients);
+    }
+}
+
+fn write_extractions(
+    extractions: &mut Extractions,
+    ts: SystemTime,
+    raw: &[u8],
+    frame_start: usize,
+    state: &Arc<Mutex<AppState>>,
+) {
+    for (bssid, path, e) in extractions.write(ts, raw, &raw[frame_start..]) {
+        state.lock().unwrap().extracting.remove(&bssid);
+        let message = format!("Extraction to {} failed: {e}", path.display());
+        report_capture(state, message, Some(bssid));
+    }
+}
+
+fn report_extract_stopped(
+    state: &Arc<Mutex<AppState>>,
+    bssid: [u8; 6],
+    result: std::io::Result<SavedFile>,
+) {
+    let message = match result {
+        Ok((path, frames)) => format!("Saved {frames} frames for {} to {}", format_mac(&bssid), path.display()),
+        Err(e) => format!("Extraction for {} failed: {e}", format_mac(&bssid)),
+    };
+    state.lock().unwrap().extracting.remove(&bssid);
+    report_capture(state, message, Some(bssid));
+}
+
+/// Note trigger captures in the alert history.
+fn report_ring_event(
+    state: &Arc<Mutex<AppState>>,
+    event: std::io::Result<RingEvent>,
+    reason: Option<&str>,
+    ring: &TriggerRing,
+) {
+    let message = match event {
+        Ok(RingEvent::Started(path)) => format!(
+            "Saving {}s before and {}s after to {} ({})",
+            ring.config().pre.as_secs(),
+            ring.config().post.as_secs(),
+            path.display(),
+            reason.unwrap_or("trigger")
+        ),
+        // Repeated triggers during one incident belong in the same file
+        Ok(RingEvent::Extended) => return,
+        Ok(RingEvent::Saved(path, frames)) => format!("Saved {frames} frames to {}", path.display()),
+        Err(e) => format!("Trigger capture failed: {e}"),
+    };
+    report_capture(state, message, None);
+}
+
+/// Record a capture file notice as an Info alert.
+///
+/// These bypass `push_alert` so notices about the ring never trigger it again.
+fn report_capture(state: &Arc<Mutex<AppState>>, message: String, bssid: Option<[u8; 6]>) {
+    let mut st = state.lock().unwrap();
+    let now = st.now();
+    st.alerts.push(Alert::new(Severity::Info, message, bssid, now));
+}
+
+fn timeval_to_system_time(tv: &libc::timeval) -> SystemTime {
+    UNIX_EPOCH
+        + Duration::from_secs(tv.tv_sec.max(0) as u64)
+        + Duration::from_micros(tv.tv_usec.max(0) as u64)
 }
@@ -0,0 +1,169 @@
+//! Per-Packet Information (PPI) header decoder.
+//!
+//! PPI wraps 802.11 frames in a list of typed fields; we read the 802.11
+//! common field and the 802.11n MAC+PHY extension, and skip the rest.
+
+use crate::model::phy::{PhyInfo, PhyMode, PhyRate};
+
+const DLT_IEEE802_11: u32 = 105;
+
+const FIELD_80211_COMMON: u16 = 2;
+const FIELD_80211N_MAC_PHY: u16 = 4;
+
+const COMMON_FLAG_FCS: u16 = 0x0001;
+const COMMON_FLAG_BAD_FCS: u16 = 0x0004;
+
+const MAC_FLAG_HT40: u32 = 0x0002;
+const MAC_FLAG_SHORT_GI: u32 = 0x0004;
+
+/// Decode a PPI header, returning the metadata and where the 802.11 frame starts.
+pub fn parse(raw: &[u8]) -> Option<(PhyInfo, usize)> {
+    if raw.len() < 8 || raw[0] != 0 {
+        return None;
+    }
+    let len = u16::from_le_bytes([raw[2], raw[3]]) as usize;
+    let dlt = u32::from_le_bytes(raw[4..8].try_into().ok()?);
+    if len < 8 || len > raw.len() || dlt != DLT_IEEE802_11 {
+        return None;
+    }
+
+    let mut phy = PhyInfo::default();
+    let mut pos = 8;
+    while pos + 4 <= len {
+        let kind = u16::from_le_bytes([raw[pos], raw[pos + 1]]);
+        let size = u16::from_le_bytes([raw[pos + 2], raw[pos + 3]]) as usize;
+        let Some(data) = raw.get(pos + 4..pos + 4 + size).filter(|_| pos + 4 + size <= len) else {
+            break;
+        };
+        match kind {
+            FIELD_80211_COMMON => read_common(&mut phy, data),
+            FIELD_80211N_MAC_PHY => read_mac_phy(&mut phy, data),
+            _ => {}
+        }
+        pos += 4 + size;
+    }
+
+    Some((phy, len))
+}
+
+/// 802.11-Common: TSF (u64), flags (u16), rate (u16), frequency (u16),
+/// channel flags (u16), FHSS (2 × u8), signal (i8), noise (i8).
+fn read_common(phy: &mut PhyInfo, data: &[u8]) {
+    if data.len() < 20 {
+        return;
+    }
+    let word = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+    phy.tsft = Some(u64::from_le_bytes(data[0..8].try_into().unwrap_or_default()));
+    let flags = word(8);
+    phy.fcs_included = flags & COMMON_FLAG_FCS != 0;
+    phy.bad_fcs = flags & COMMON_FLAG_BAD_FCS != 0;
+    // Units of 500 kbps, zero for HT frames
+    if word(10) > 0 {
+        phy.rate = Some(PhyRate::legacy(word(10) as f32 / 2.0));
+    }
+    if word(12) > 0 {
+        phy.freq_mhz = Some(word(12));
+    }
+    // Zero means the driver didn't fill it in
+    phy.signal_dbm = Some(data[18] as i8).filter(|&dbm| dbm != 0);
+    phy.noise_dbm = Some(data[19] as i8).filter(|&dbm| dbm != 0);
+}
+
+/// 802.11n MAC+PHY (48 bytes): flags (u32), A-MPDU ID (u32), delimiters
+/// (u8), MCS (u8), streams (u8), combined RSSI (u8), control and extension
+/// RSSI per antenna (4 × u8 each), extension channel frequency and flags
+/// (2 × u16), signal and noise in dBm per antenna (4 × 2 × i8), then EVM.
+fn read_mac_phy(phy: &mut PhyInfo, data: &[u8]) {
+    if data.len() < 48 {
+        return;
+    }
+    let flags = u32::from_le_bytes(data[0..4].try_into().unwrap_or_default());
+    let (mcs, nss) = (data[9], data[10]);
+    let bandwidth = if flags & MAC_FLAG_HT40 != 0 { 40 } else { 20 };
+    let gi = if flags & MAC_FLAG_SHORT_GI != 0 { 400 } else { 800 };
+    let nss = if nss > 0 { nss } else { mcs / 8 + 1 };
+    if let Some(rate) = PhyRate::from_mcs(PhyMode::Ht, mcs % 8, nss, bandwidth, gi) {
+        phy.rate = Some(rate);
+    }
+    for (antenna, pair) in data[24..32].chunks(2).enumerate() {
+        if pair[0] != 0 {
+            phy.chain_signal.push((antenna as u8, pair[0] as i8));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ppi_header(fields: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let len = 8 + fields.iter().map(|(_, data)| 4 + data.len()).sum::<usize>();
+        let mut raw = vec![0, 0];
+        raw.extend_from_slice(&(len as u16).to_le_bytes());
+        raw.extend_from_slice(&DLT_IEEE802_11.to_le_bytes());
+        for (kind, data) in fields {
+            raw.extend_from_slice(&kind.to_le_bytes());
+            raw.extend_from_slice(&(data.len() as u16).to_le_bytes());
+            raw.extend_from_slice(data);
+        }
+        raw
+    }
+
+    fn common_field() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1234u64.to_le_bytes());
+        data.extend_from_slice(&COMMON_FLAG_FCS.to_le_bytes());
+        data.extend_from_slice(&12u16.to_le_bytes());
+        data.extend_from_slice(&5180u16.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(&[0, 0, -55i8 as u8, -92i8 as u8]);
+        data
+    }
+
+    fn mac_phy_field() -> Vec<u8> {
+        let mut data = vec![0u8; 48];
+        data[0..4].copy_from_slice(&(MAC_FLAG_HT40 | MAC_FLAG_SHORT_GI).to_le_bytes());
+        data[8] = 3;
+        data[9] = 15;
+        data[10] = 2;
+        // Combined and per-antenna RSSI that must not be read as MCS or dBm
+        data[11..20].copy_from_slice(&[40, 41, 42, 0, 0, 43, 44, 0, 0]);
+        data[22..24].copy_from_slice(&0x00a0u16.to_le_bytes());
+        data[24..28].copy_from_slice(&[-50i8 as u8, -90i8 as u8, -53i8 as u8, -91i8 as u8]);
+        data
+    }
+
+    #[test]
+    fn common_field_values() {
+        let raw = ppi_header(&[(FIELD_80211_COMMON, common_field())]);
+        let (phy, len) = parse(&raw).unwrap();
+        assert_eq!(len, raw.len());
+        assert_eq!(phy.tsft, Some(1234));
+        assert!(phy.fcs_included);
+        assert_eq!(phy.rate.map(|r| r.mbps), Some(6.0));
+        assert_eq!(phy.freq_mhz, Some(5180));
+        assert_eq!(phy.signal_dbm, Some(-55));
+        assert_eq!(phy.noise_dbm, Some(-92));
+    }
+
+    #[test]
+    fn mac_phy_mcs_and_chain_signal() {
+        let raw = ppi_header(&[(FIELD_80211_COMMON, common_field()), (FIELD_80211N_MAC_PHY, mac_phy_field())]);
+        let (phy, _) = parse(&raw).unwrap();
+        let rate = phy.rate.unwrap();
+        assert_eq!(rate.mode, PhyMode::Ht);
+        assert_eq!(rate.mcs, Some(7));
+        assert_eq!(rate.nss, 2);
+        assert_eq!(rate.bandwidth_mhz, 40);
+        assert_eq!(rate.gi_ns, 400);
+        assert_eq!(phy.chain_signal, vec![(0, -50), (1, -53)]);
+    }
+
+    #[test]
+    fn short_mac_phy_field_ignored() {
+        let raw = ppi_header(&[(FIELD_80211N_MAC_PHY, mac_phy_field()[..27].to_vec())]);
+        let (phy, _) = parse(&raw).unwrap();
+        assert!(phy.rate.is_none());
+        assert!(phy.chain_signal.is_empty());
+    }
+}
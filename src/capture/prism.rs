@@ -0,0 +1,161 @@
+//! Prism (wlan-ng) and AVS header decoders, used by older drivers.
+
+use crate::model::phy::{PhyInfo, PhyRate};
+use crate::model::spectrum::channel_center_mhz;
+
+/// Prism header: message code, length, device name, then ten items.
+const PRISM_HEADER_LEN: usize = 144;
+const PRISM_ITEMS_START: usize = 24;
+const PRISM_ITEM_LEN: usize = 12;
+
+const PRISM_MACTIME: usize = 1;
+const PRISM_CHANNEL: usize = 2;
+const PRISM_SIGNAL: usize = 5;
+const PRISM_NOISE: usize = 6;
+const PRISM_RATE: usize = 7;
+
+/// Item status value meaning the data is present.
+const PRISM_STATUS_OK: u16 = 0;
+
+const AVS_MAGIC: u32 = 0x8021_1001;
+const AVS_MIN_LEN: usize = 64;
+const AVS_SSI_DBM: u32 = 2;
+const AVS_PREAMBLE_SHORT: u32 = 1;
+
+/// Decode a Prism header, returning the metadata and where the 802.11 frame starts.
+pub fn parse_prism(raw: &[u8]) -> Option<(PhyInfo, usize)> {
+    if raw.len() < PRISM_HEADER_LEN {
+        return None;
+    }
+    let len = (u32::from_le_bytes(raw[4..8].try_into().ok()?) as usize).max(PRISM_HEADER_LEN);
+    if len > raw.len() {
+        return None;
+    }
+
+    // Items are (DID u32, status u16, length u16, data u32)
+    let item = |index: usize| {
+        let pos = PRISM_ITEMS_START + index * PRISM_ITEM_LEN;
+        let status = u16::from_le_bytes([raw[pos + 4], raw[pos + 5]]);
+        (status == PRISM_STATUS_OK)
+            .then(|| u32::from_le_bytes(raw[pos + 8..pos + 12].try_into().unwrap_or_default()))
+    };
+
+    let phy = PhyInfo {
+        tsft: item(PRISM_MACTIME).map(u64::from),
+        freq_mhz: item(PRISM_CHANNEL).and_then(channel_to_freq),
+        // Drivers store negative dBm as the two's complement in a u32
+        signal_dbm: item(PRISM_SIGNAL).map(|v| v as i32 as i8),
+        noise_dbm: item(PRISM_NOISE).map(|v| v as i32 as i8).filter(|&dbm| dbm != 0),
+        rate: item(PRISM_RATE)
+            .filter(|&r| r > 0)
+            .map(|r| PhyRate::legacy(r as f32 / 2.0)),
+        ..PhyInfo::default()
+    };
+    Some((phy, len))
+}
+
+/// Decode an AVS header (big-endian), returning the metadata and where the
+/// 802.11 frame starts.
+pub fn parse_avs(raw: &[u8]) -> Option<(PhyInfo, usize)> {
+    if raw.len() < AVS_MIN_LEN {
+        return None;
+    }
+    let word = |pos: usize| u32::from_be_bytes(raw[pos..pos + 4].try_into().unwrap_or_default());
+    if word(0) != AVS_MAGIC {
+        return None;
+    }
+    let len = word(4) as usize;
+    if len < AVS_MIN_LEN || len > raw.len() {
+        return None;
+    }
+
+    // Layout after version and length: mactime (u64) at 8, hosttime (u64) at
+    // 16, then u32s: phy type 24, channel 28, data rate (100 kbps) 32, antenna
+    // 36, priority 40, SSI type 44, signal 48, noise 52, preamble 56, encoding 60
+    let dbm = word(44) == AVS_SSI_DBM;
+    let rate = word(32);
+    let phy = PhyInfo {
+        tsft: Some(u64::from_be_bytes(raw[8..16].try_into().ok()?)),
+        freq_mhz: channel_to_freq(word(28)),
+        signal_dbm: dbm.then(|| word(48) as i32 as i8),
+        noise_dbm: dbm.then(|| word(52) as i32 as i8).filter(|&n| n != 0),
+        rate: (rate > 0).then(|| PhyRate::legacy(rate as f32 / 10.0)),
+        short_preamble: word(56) == AVS_PREAMBLE_SHORT,
+        ..PhyInfo::default()
+    };
+    Some((phy, len))
+}
+
+fn channel_to_freq(channel: u32) -> Option<u16> {
+    channel_center_mhz(u8::try_from(channel).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An AVS header with the given big-endian u32 fields from offset 24.
+    fn avs_header(fields: [u32; 10]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(&AVS_MAGIC.to_be_bytes());
+        raw.extend_from_slice(&(AVS_MIN_LEN as u32).to_be_bytes());
+        raw.extend_from_slice(&0x0102_0304_0506_0708u64.to_be_bytes());
+        raw.extend_from_slice(&0u64.to_be_bytes());
+        for field in fields {
+            raw.extend_from_slice(&field.to_be_bytes());
+        }
+        raw
+    }
+
+    #[test]
+    fn avs_fields_at_documented_offsets() {
+        // phy type, channel, rate, antenna, priority, SSI type, signal, noise, preamble, encoding
+        let mut raw = avs_header([4, 6, 540, 1, 3, AVS_SSI_DBM, -42i32 as u32, -95i32 as u32, 1, 0]);
+        raw.extend_from_slice(&[0x80, 0x00]);
+
+        let (phy, len) = parse_avs(&raw).unwrap();
+        assert_eq!(len, AVS_MIN_LEN);
+        assert_eq!(phy.tsft, Some(0x0102_0304_0506_0708));
+        assert_eq!(phy.freq_mhz, Some(2437));
+        assert_eq!(phy.rate.map(|r| r.mbps), Some(54.0));
+        assert_eq!(phy.signal_dbm, Some(-42));
+        assert_eq!(phy.noise_dbm, Some(-95));
+        assert!(phy.short_preamble);
+    }
+
+    #[test]
+    fn avs_signal_needs_dbm_ssi_type() {
+        let raw = avs_header([4, 6, 540, 1, 3, 1, 60, 10, 0, 0]);
+        let (phy, _) = parse_avs(&raw).unwrap();
+        assert_eq!(phy.signal_dbm, None);
+        assert_eq!(phy.noise_dbm, None);
+    }
+
+    #[test]
+    fn avs_rejects_wrong_magic() {
+        let mut raw = avs_header([0; 10]);
+        raw[3] = 0;
+        assert!(parse_avs(&raw).is_none());
+    }
+
+    #[test]
+    fn prism_items() {
+        let mut raw = vec![0u8; PRISM_HEADER_LEN];
+        raw[4..8].copy_from_slice(&(PRISM_HEADER_LEN as u32).to_le_bytes());
+        let mut set = |index: usize, value: u32| {
+            let pos = PRISM_ITEMS_START + index * PRISM_ITEM_LEN;
+            raw[pos + 8..pos + 12].copy_from_slice(&value.to_le_bytes());
+        };
+        set(PRISM_CHANNEL, 11);
+        set(PRISM_SIGNAL, -60i32 as u32);
+        set(PRISM_NOISE, 0);
+        set(PRISM_RATE, 11);
+
+        let (phy, len) = parse_prism(&raw).unwrap();
+        assert_eq!(len, PRISM_HEADER_LEN);
+        assert_eq!(phy.freq_mhz, Some(2462));
+        assert_eq!(phy.signal_dbm, Some(-60));
+        assert_eq!(phy.noise_dbm, None);
+        assert_eq!(phy.rate.map(|r| r.mbps), Some(5.5));
+    }
+}
@@ -0,0 +1,322 @@
+//! Radiotap header decoder.
+//!
+//! Walks every presence word, including extended radiotap and vendor
+//! namespaces, so per-chain signal and HE fields are picked up and fields
+//! after them stay aligned.
+
+use crate::model::phy::{PhyInfo, PhyMode, PhyRate};
+
+const FIELD_TSFT: u32 = 0;
+const FIELD_FLAGS: u32 = 1;
+const FIELD_RATE: u32 = 2;
+const FIELD_CHANNEL: u32 = 3;
+const FIELD_ANTENNA_SIGNAL: u32 = 5;
+const FIELD_ANTENNA_NOISE: u32 = 6;
+const FIELD_ANTENNA: u32 = 11;
+const FIELD_MCS: u32 = 19;
+const FIELD_VHT: u32 = 21;
+const FIELD_HE: u32 = 23;
+
+const FLAG_SHORT_PREAMBLE: u8 = 0x02;
+const FLAG_FCS: u8 = 0x10;
+const FLAG_BAD_FCS: u8 = 0x40;
+
+const PRESENT_RADIOTAP_NS: u32 = 1 << 29;
+const PRESENT_VENDOR_NS: u32 = 1 << 30;
+const PRESENT_EXT: u32 = 1 << 31;
+
+/// (alignment, size) of radiotap fields 0-27. Fields beyond this table are
+/// variable-length or unassigned, so decoding stops there.
+const FIELD_LAYOUT: [(usize, usize); 28] = [
+    (8, 8),  // TSFT
+    (1, 1),  // Flags
+    (1, 1),  // Rate
+    (2, 4),  // Channel
+    (1, 2),  // FHSS
+    (1, 1),  // Antenna signal
+    (1, 1),  // Antenna noise
+    (2, 2),  // Lock quality
+    (2, 2),  // TX attenuation
+    (2, 2),  // dB TX attenuation
+    (1, 1),  // dBm TX power
+    (1, 1),  // Antenna
+    (1, 1),  // dB antenna signal
+    (1, 1),  // dB antenna noise
+    (2, 2),  // RX flags
+    (2, 2),  // TX flags
+    (1, 1),  // RTS retries
+    (1, 1),  // Data retries
+    (4, 8),  // XChannel
+    (1, 3),  // MCS
+    (4, 8),  // A-MPDU status
+    (2, 12), // VHT
+    (8, 12), // Timestamp
+    (2, 12), // HE
+    (2, 12), // HE-MU
+    (2, 6),  // HE-MU-other-user
+    (1, 1),  // 0-length PSDU
+    (2, 4),  // L-SIG
+];
+
+/// Decode a radiotap header, returning the metadata and the header length
+/// (where the 802.11 frame starts).
+pub fn parse(raw: &[u8]) -> Option<(PhyInfo, usize)> {
+    if raw.len() < 8 || raw[0] != 0 {
+        return None;
+    }
+    let len = u16::from_le_bytes([raw[2], raw[3]]) as usize;
+    if len < 8 || len > raw.len() {
+        return None;
+    }
+    let header = &raw[..len];
+
+    // Presence words run until one without the extension bit
+    let mut words = Vec::new();
+    let mut pos = 4;
+    loop {
+        let word = read_u32(header, pos)?;
+        words.push(word);
+        pos += 4;
+        if word & PRESENT_EXT == 0 {
+            break;
+        }
+    }
+
+    let mut phy = PhyInfo::default();
+    // A field we can't size ends decoding; what came before it is still good
+    let _ = read_fields(header, &words, pos, &mut phy);
+
+    Some((phy, len))
+}
+
+/// Walk the fields announced by `words`, which start at `fields_start`.
+fn read_fields(header: &[u8], words: &[u32], fields_start: usize, phy: &mut PhyInfo) -> Option<()> {
+    let mut cursor = fields_start;
+    let mut in_vendor_ns = false;
+    let mut ns_index = 0;
+    let mut word_in_ns = 0;
+    // Per-chain fields come in pairs within each extra radiotap namespace
+    let mut chain: (Option<u8>, Option<i8>) = (None, None);
+
+    for &word in words {
+        if !in_vendor_ns {
+            for bit in 0..29 {
+                if word & (1 << bit) == 0 {
+                    continue;
+                }
+                let field = word_in_ns * 32 + bit;
+                let &(align, size) = FIELD_LAYOUT.get(field as usize)?;
+                cursor = cursor.next_multiple_of(align);
+                let data = header.get(cursor..cursor + size)?;
+                cursor += size;
+
+                if ns_index == 0 {
+                    read_field(phy, field, data);
+                } else {
+                    match field {
+                        FIELD_ANTENNA_SIGNAL => chain.1 = Some(data[0] as i8),
+                        FIELD_ANTENNA => chain.0 = Some(data[0]),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        if word & (PRESENT_RADIOTAP_NS | PRESENT_VENDOR_NS) != 0 {
+            if let (Some(antenna), Some(dbm)) = chain {
+                phy.chain_signal.push((antenna, dbm));
+            }
+            chain = (None, None);
+            word_in_ns = 0;
+        } else {
+            word_in_ns += 1;
+        }
+
+        if word & PRESENT_RADIOTAP_NS != 0 {
+            in_vendor_ns = false;
+            ns_index += 1;
+        } else if word & PRESENT_VENDOR_NS != 0 {
+            // OUI (3), sub-namespace (1), then the length of the vendor data to skip
+            cursor = cursor.next_multiple_of(2);
+            let skip = u16::from_le_bytes([*header.get(cursor + 4)?, *header.get(cursor + 5)?]);
+            cursor += 6 + skip as usize;
+            in_vendor_ns = true;
+        }
+    }
+    if let (Some(antenna), Some(dbm)) = chain {
+        phy.chain_signal.push((antenna, dbm));
+    }
+
+    Some(())
+}
+
+fn read_field(phy: &mut PhyInfo, field: u32, data: &[u8]) {
+    match field {
+        FIELD_TSFT => phy.tsft = Some(u64::from_le_bytes(data.try_into().unwrap_or_default())),
+        FIELD_FLAGS => {
+            phy.short_preamble = data[0] & FLAG_SHORT_PREAMBLE != 0;
+            phy.fcs_included = data[0] & FLAG_FCS != 0;
+            phy.bad_fcs = data[0] & FLAG_BAD_FCS != 0;
+        }
+        // Units of 500 kbps; HT/VHT/HE frames set this to 0 or leave it out
+        FIELD_RATE if data[0] > 0 => {
+            phy.rate = Some(PhyRate::legacy(data[0] as f32 / 2.0));
+        }
+        FIELD_CHANNEL => phy.freq_mhz = Some(u16::from_le_bytes([data[0], data[1]])),
+        FIELD_ANTENNA_SIGNAL => phy.signal_dbm = Some(data[0] as i8),
+        FIELD_ANTENNA_NOISE => phy.noise_dbm = Some(data[0] as i8),
+        FIELD_MCS => phy.rate = parse_mcs(data).or(phy.rate),
+        FIELD_VHT => phy.rate = parse_vht(data).or(phy.rate),
+        FIELD_HE => phy.rate = parse_he(data).or(phy.rate),
+        _ => {}
+    }
+}
+
+/// MCS field: known (u8), flags (u8), MCS index (u8).
+fn parse_mcs(data: &[u8]) -> Option<PhyRate> {
+    let (known, flags, index) = (data[0], data[1], data[2]);
+    if known & 0x02 == 0 {
+        return None;
+    }
+    let bandwidth = if known & 0x01 != 0 && flags & 0x03 == 1 { 40 } else { 20 };
+    let gi = if known & 0x04 != 0 && flags & 0x04 != 0 { 400 } else { 800 };
+    PhyRate::from_mcs(PhyMode::Ht, index % 8, index / 8 + 1, bandwidth, gi)
+}
+
+/// VHT field: known (u16), flags (u8), bandwidth (u8), per-user MCS/NSS (4 × u8), ...
+fn parse_vht(data: &[u8]) -> Option<PhyRate> {
+    let known = u16::from_le_bytes([data[0], data[1]]);
+    let gi = if known & 0x0004 != 0 && data[2] & 0x04 != 0 { 400 } else { 800 };
+    let bandwidth = match data[3] {
+        0 => 20,
+        1..=3 => 40,
+        4..=10 => 80,
+        _ => 160,
+    };
+    // First user with a rate; NSS 0 means the user slot is empty
+    let mcs_nss = data[4..8].iter().copied().find(|b| b & 0x0f != 0)?;
+    PhyRate::from_mcs(PhyMode::Vht, mcs_nss >> 4, mcs_nss & 0x0f, bandwidth, gi)
+}
+
+/// HE field: six u16 words of known bits and values (radiotap.org/fields/HE).
+fn parse_he(data: &[u8]) -> Option<PhyRate> {
+    let word = |i: usize| u16::from_le_bytes([data[i * 2], data[i * 2 + 1]]);
+    let (data1, data2, data3, data5, data6) = (word(0), word(1), word(2), word(4), word(5));
+    // Data MCS must be known to compute a rate
+    if data1 & 0x0020 == 0 {
+        return None;
+    }
+    let mcs = ((data3 >> 8) & 0x0f) as u8;
+    let bandwidth = if data1 & 0x4000 != 0 {
+        match data5 & 0x0f {
+            1 => 40,
+            2 => 80,
+            3 => 160,
+            _ => 20,
+        }
+    } else {
+        20
+    };
+    let gi = if data2 & 0x0002 != 0 {
+        match (data5 >> 4) & 0x03 {
+            1 => 1600,
+            2 => 3200,
+            _ => 800,
+        }
+    } else {
+        800
+    };
+    let nss = (data6 & 0x0f).max(1) as u8;
+    PhyRate::from_mcs(PhyMode::He, mcs, nss, bandwidth, gi)
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(pos..pos + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A header with `present` words followed by `fields`, which must already
+    /// be padded to their alignment.
+    fn header(present: &[u32], fields: &[u8]) -> Vec<u8> {
+        let len = 4 + present.len() * 4 + fields.len();
+        let mut raw = vec![0, 0];
+        raw.extend_from_slice(&(len as u16).to_le_bytes());
+        for word in present {
+            raw.extend_from_slice(&word.to_le_bytes());
+        }
+        raw.extend_from_slice(fields);
+        raw
+    }
+
+    #[test]
+    fn common_fields() {
+        let present = 1 << FIELD_TSFT
+            | 1 << FIELD_FLAGS
+            | 1 << FIELD_RATE
+            | 1 << FIELD_CHANNEL
+            | 1 << FIELD_ANTENNA_SIGNAL
+            | 1 << FIELD_ANTENNA_NOISE;
+        // One presence word leaves TSFT at offset 8, already aligned
+        let mut fields = 99u64.to_le_bytes().to_vec();
+        fields.push(FLAG_FCS | FLAG_SHORT_PREAMBLE);
+        fields.push(108); // 54 Mbps
+        fields.extend_from_slice(&5240u16.to_le_bytes());
+        fields.extend_from_slice(&0x0140u16.to_le_bytes());
+        fields.push(-48i8 as u8);
+        fields.push(-96i8 as u8);
+        let mut raw = header(&[present], &fields);
+        let len = raw.len();
+        raw.extend_from_slice(&[0x80, 0x00]);
+
+        let (phy, frame_start) = parse(&raw).unwrap();
+        assert_eq!(frame_start, len);
+        assert_eq!(phy.tsft, Some(99));
+        assert!(phy.fcs_included);
+        assert!(phy.short_preamble);
+        assert!(!phy.bad_fcs);
+        assert_eq!(phy.rate.map(|r| r.mbps), Some(54.0));
+        assert_eq!(phy.freq_mhz, Some(5240));
+        assert_eq!(phy.signal_dbm, Some(-48));
+        assert_eq!(phy.noise_dbm, Some(-96));
+    }
+
+    #[test]
+    fn per_chain_signal_in_extra_namespaces() {
+        let common = 1 << FIELD_FLAGS | 1 << FIELD_ANTENNA_SIGNAL | PRESENT_RADIOTAP_NS | PRESENT_EXT;
+        let chain0 = 1 << FIELD_ANTENNA_SIGNAL | 1 << FIELD_ANTENNA | PRESENT_RADIOTAP_NS | PRESENT_EXT;
+        let chain1 = 1 << FIELD_ANTENNA_SIGNAL | 1 << FIELD_ANTENNA;
+        let fields = [0, -50i8 as u8, -52i8 as u8, 0, -55i8 as u8, 1];
+        let raw = header(&[common, chain0, chain1], &fields);
+
+        let (phy, _) = parse(&raw).unwrap();
+        assert_eq!(phy.signal_dbm, Some(-50));
+        assert_eq!(phy.chain_signal, vec![(0, -52), (1, -55)]);
+    }
+
+    #[test]
+    fn ht_mcs_rate() {
+        // Bandwidth, MCS and GI known; 40 MHz, short GI; MCS 15
+        let raw = header(&[1 << FIELD_MCS], &[0x07, 0x05, 15]);
+        let rate = parse(&raw).unwrap().0.rate.unwrap();
+        assert_eq!(rate.mode, PhyMode::Ht);
+        assert_eq!((rate.mcs, rate.nss, rate.bandwidth_mhz, rate.gi_ns), (Some(7), 2, 40, 400));
+        assert!((rate.mbps - 300.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        assert!(parse(&[0, 0, 8]).is_none());
+        assert_eq!(parse(&header(&[0], &[])).map(|(_, len)| len), Some(8));
+        // Version must be 0
+        let mut raw = header(&[0], &[]);
+        raw[0] = 1;
+        assert!(parse(&raw).is_none());
+        // Length past the end of the packet
+        let mut raw = header(&[0], &[]);
+        raw[2] = 64;
+        assert!(parse(&raw).is_none());
+    }
+}
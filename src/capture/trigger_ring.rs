@@ -0,0 +1,248 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::util::pcapng::PcapngWriter;
+use crate::util::time::format_utc;
+
+/// Upper bound on buffered frame bytes, whatever the pre-event window.
+const RING_MAX_BYTES: usize = 128 * 1024 * 1024;
+
+/// Settings for the pre/post-event capture buffer.
+#[derive(Debug, Clone)]
+pub struct RingConfig {
+    /// Frames kept from before a trigger.
+    pub pre: Duration,
+    /// Frames still written after a trigger.
+    pub post: Duration,
+}
+
+/// A trigger capture still collecting post-event frames.
+struct OpenDump {
+    writer: PcapngWriter<BufWriter<File>>,
+    path: PathBuf,
+    until: SystemTime,
+    frames: u64,
+}
+
+/// The last `pre` seconds of raw frames, written out with the following
+/// `post` seconds whenever something triggers it.
+///
+/// Times are capture timestamps, so replayed files trigger the same way as
+/// live capture.
+pub struct TriggerRing {
+    config: RingConfig,
+    dlt: i32,
+    frames: VecDeque<(SystemTime, Vec<u8>)>,
+    bytes: usize,
+    open: Option<OpenDump>,
+}
+
+/// What happened to a trigger capture, for reporting.
+pub enum RingEvent {
+    /// A new file was started.
+    Started(PathBuf),
+    /// A trigger arrived while a file was open and extended it.
+    Extended,
+    /// A file reached the end of its post-event window.
+    Saved(PathBuf, u64),
+}
+
+impl TriggerRing {
+    pub fn new(config: RingConfig, dlt: i32) -> Self {
+        Self {
+            config,
+            dlt,
+            frames: VecDeque::new(),
+            bytes: 0,
+            open: None,
+        }
+    }
+
+    /// Buffer a frame and pass it to an open trigger capture. Returns the
+    /// finished capture if this frame closed its window.
+    pub fn push(&mut self, ts: SystemTime, data: &[u8]) -> io::Result<Option<RingEvent>> {
+        let saved = self.poll(ts)?;
+        if let Some(dump) = self.open.as_mut() {
+            dump.writer.write_packet(ts, data)?;
+            dump.frames += 1;
+        }
+
+        self.frames.push_back((ts, data.to_vec()));
+        self.bytes += data.len();
+        while let Some((oldest, frame)) = self.frames.front() {
+            let expired = ts.duration_since(*oldest).is_ok_and(|age| age > self.config.pre);
+            if !expired && self.bytes <= RING_MAX_BYTES {
+                break;
+            }
+            self.bytes -= frame.len();
+            self.frames.pop_front();
+        }
+        Ok(saved)
+    }
+
+    /// Close the open trigger capture if `now` is past its window. Called
+    /// between frames too, so a quiet channel doesn't hold it open.
+    pub fn poll(&mut self, now: SystemTime) -> io::Result<Option<RingEvent>> {
+        match &self.open {
+            Some(dump) if now > dump.until => self.close(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Write the buffered frames to a new file in `dir` and keep writing
+    /// until `post` after the latest frame. A trigger during an open capture
+    /// extends it.
+    pub fn trigger(&mut self, dir: &Path) -> io::Result<RingEvent> {
+        let now = self.frames.back().map(|(ts, _)| *ts).unwrap_or_else(SystemTime::now);
+        let until = now + self.config.post;
+        if let Some(dump) = self.open.as_mut() {
+            dump.until = dump.until.max(until);
+            return Ok(RingEvent::Extended);
+        }
+
+        let (path, file) = create_capture_file(dir, "wifimonitor-trigger", "pcapng", now)?;
+        let mut writer = PcapngWriter::new(BufWriter::new(file), self.dlt)?;
+        for (ts, data) in &self.frames {
+            writer.write_packet(*ts, data)?;
+        }
+        self.open = Some(OpenDump {
+            writer,
+            path: path.clone(),
+            until,
+            frames: self.frames.len() as u64,
+        });
+        Ok(RingEvent::Started(path))
+    }
+
+    /// Write what is buffered now to a new file in `dir`, without waiting
+    /// for further frames. Returns the path and frame count.
+    pub fn export(&self, dir: &Path) -> io::Result<(PathBuf, u64)> {
+        let now = self.frames.back().map(|(ts, _)| *ts).unwrap_or_else(SystemTime::now);
+        let (path, file) = create_capture_file(dir, "wifimonitor-ring", "pcapng", now)?;
+        let mut writer = PcapngWriter::new(BufWriter::new(file), self.dlt)?;
+        for (ts, data) in &self.frames {
+            writer.write_packet(*ts, data)?;
+        }
+        writer.flush()?;
+        Ok((path, self.frames.len() as u64))
+    }
+
+    /// Flush and close the open capture, if any.
+    pub fn close(&mut self) -> io::Result<Option<RingEvent>> {
+        let Some(mut dump) = self.open.take() else {
+            return Ok(None);
+        };
+        dump.writer.flush()?;
+        Ok(Some(RingEvent::Saved(dump.path, dump.frames)))
+    }
+
+    pub fn config(&self) -> &RingConfig {
+        &self.config
+    }
+}
+
+/// Create `<prefix>-<UTC time>.<ext>` in `dir`, numbered if that exists.
+pub fn create_capture_file(dir: &Path, prefix: &str, ext: &str, at: SystemTime) -> io::Result<(PathBuf, File)> {
+    let stamp = format_utc(at).replace(':', "").replace(' ', "-");
+    for n in 1.. {
+        let name = match n {
+            1 => format!("{prefix}-{stamp}.{ext}"),
+            _ => format!("{prefix}-{stamp}-{n}.{ext}"),
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("ran out of file numbers")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+    use crate::util::pcapng::read_blocks;
+
+    const ENHANCED_PACKET_BLOCK: u32 = 6;
+
+    /// An empty directory under the system temp dir, unique to the test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wifimonitor-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Frame payloads in a saved capture.
+    fn packets(path: &Path) -> Vec<Vec<u8>> {
+        let data = std::fs::read(path).unwrap();
+        read_blocks(&data)
+            .into_iter()
+            .filter(|(block_type, _)| *block_type == ENHANCED_PACKET_BLOCK)
+            .map(|(_, body)| {
+                let len = u32::from_le_bytes(body[12..16].try_into().unwrap()) as usize;
+                body[20..20 + len].to_vec()
+            })
+            .collect()
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000 + secs)
+    }
+
+    #[test]
+    fn trigger_saves_pre_and_post_frames() {
+        let dir = scratch_dir("trigger");
+        let config = RingConfig {
+            pre: Duration::from_secs(5),
+            post: Duration::from_secs(5),
+        };
+        let mut ring = TriggerRing::new(config, 127);
+        for secs in 0..10 {
+            assert!(ring.push(at(secs), &[secs as u8; 3]).unwrap().is_none());
+        }
+        let Ok(RingEvent::Started(path)) = ring.trigger(&dir) else {
+            panic!("trigger didn't start a capture");
+        };
+        assert!(matches!(ring.trigger(&dir), Ok(RingEvent::Extended)));
+        assert!(ring.push(at(12), &[12; 1]).unwrap().is_none());
+
+        // Nothing more is heard, but the window still ends
+        assert!(ring.poll(at(14)).unwrap().is_none());
+        let Ok(Some(RingEvent::Saved(saved, frames))) = ring.poll(at(15)) else {
+            panic!("window didn't close");
+        };
+        assert_eq!(saved, path);
+        assert_eq!(frames, 7);
+        let expected: Vec<Vec<u8>> = (4..10).map(|secs| vec![secs as u8; 3]).chain([vec![12]]).collect();
+        assert_eq!(packets(&path), expected);
+
+        // Later frames go to the ring only
+        assert!(ring.push(at(16), &[16]).unwrap().is_none());
+        assert_eq!(packets(&path).len(), 7);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn late_frame_closes_the_window_before_it_is_written() {
+        let dir = scratch_dir("late-frame");
+        let config = RingConfig {
+            pre: Duration::from_secs(1),
+            post: Duration::from_secs(1),
+        };
+        let mut ring = TriggerRing::new(config, 127);
+        ring.push(at(0), &[0]).unwrap();
+        ring.trigger(&dir).unwrap();
+        let Ok(Some(RingEvent::Saved(path, 1))) = ring.push(at(5), &[5]) else {
+            panic!("late frame didn't close the window");
+        };
+        assert_eq!(packets(&path), [vec![0]]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
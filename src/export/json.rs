@@ -0,0 +1,188 @@
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::model::{AccessPoint, Alert, AppState, Station};
+use crate::util::mac::format_mac;
+
+#[derive(Serialize)]
+pub struct ApJson {
+    pub bssid: String,
+    pub ssid: String,
+    pub hidden: bool,
+    /// Frame type the SSID of a hidden network was recovered from.
+    pub decloaked_by: Option<String>,
+    /// SSIDs answered in probe responses but never beaconed.
+    pub impersonated_ssids: Vec<String>,
+    /// Suspected Karma/MANA responder.
+    pub karma: bool,
+    pub channel: u8,
+    /// "2.4", "5" or "6" (GHz); 6 GHz channel numbers overlap the others.
+    pub band: &'static str,
+    pub encryption: String,
+    pub signal_dbm: i8,
+    pub noise_dbm: Option<i8>,
+    pub snr_db: Option<i8>,
+    pub beacon_count: u64,
+    pub missed_beacons: u64,
+    pub retry_percent: Option<f64>,
+    pub loss_percent: Option<f64>,
+    pub beacon_interval_tu: Option<u16>,
+    pub uptime_secs: Option<u64>,
+    pub reboots: u64,
+    pub tsf_conflicts: u64,
+    pub last_seen_secs: u64,
+    pub clients: usize,
+    /// Estimated position (signal-weighted centroid), when GPS is in use.
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct StationJson {
+    pub mac: String,
+    pub bssid: Option<String>,
+    pub signal_dbm: i8,
+    pub packet_count: u64,
+    pub retry_percent: Option<f64>,
+    /// Preferred-network list from directed probe requests.
+    pub probed_ssids: Vec<String>,
+    /// Probed for a corporate SSID away from corporate coverage.
+    pub exposed: bool,
+    pub last_seen_secs: u64,
+}
+
+#[derive(Serialize)]
+pub struct AlertJson {
+    pub severity: String,
+    pub message: String,
+    pub bssid: Option<String>,
+    pub age_secs: u64,
+}
+
+impl ApJson {
+    pub fn from_ap(ap: &AccessPoint, state: &AppState) -> Self {
+        Self {
+            bssid: ap.bssid_str(),
+            ssid: ap.ssid.clone(),
+            hidden: ap.hidden,
+            decloaked_by: ap.decloak_source().map(|source| source.to_string()),
+            impersonated_ssids: ap.impersonated_ssids().into_iter().map(String::from).collect(),
+            karma: ap.is_karma(),
+            channel: ap.channel,
+            band: ap.band_label(),
+            encryption: ap.encryption.to_string(),
+            signal_dbm: ap.signal_dbm,
+            noise_dbm: ap.noise_dbm,
+            snr_db: ap.snr_db(),
+            beacon_count: ap.beacon_count,
+            missed_beacons: ap.missed_beacons,
+            retry_percent: ap.link.retry_percent(),
+            loss_percent: ap.link.loss_percent(),
+            beacon_interval_tu: ap.beacon_interval_tu,
+            uptime_secs: ap.uptime(state.now()).map(|up| up.as_secs()),
+            reboots: ap.reboots,
+            tsf_conflicts: ap.tsf_conflicts,
+            last_seen_secs: ap.seconds_since_seen(state.now()),
+            clients: state.client_count(&ap.bssid),
+            lat: ap.location.centroid().map(|(lat, _)| lat),
+            lon: ap.location.centroid().map(|(_, lon)| lon),
+        }
+    }
+}
+
+impl StationJson {
+    pub fn from_station(sta: &Station, now: Instant) -> Self {
+        Self {
+            mac: sta.mac_str(),
+            bssid: sta.bssid.as_ref().map(format_mac),
+            signal_dbm: sta.signal_dbm,
+            packet_count: sta.packet_count,
+            retry_percent: sta.link.retry_percent(),
+            probed_ssids: sta.probed_ssids.iter().map(|probe| probe.ssid.clone()).collect(),
+            exposed: sta.is_exposed(),
+            last_seen_secs: sta.seconds_since_seen(now),
+        }
+    }
+}
+
+impl AlertJson {
+    pub fn from_alert(alert: &Alert, now: Instant) -> Self {
+        Self {
+            severity: alert.severity.to_string(),
+            message: alert.message.clone(),
+            bssid: alert.bssid.as_ref().map(format_mac),
+            age_secs: now.saturating_duration_since(alert.raised_at).as_secs(),
+        }
+    }
+}
+
+/// Everything the API exposes, in one document (used by file export).
+#[derive(Serialize)]
+pub struct SnapshotJson {
+    pub interface: String,
+    pub total_packets: u64,
+    pub access_points: Vec<ApJson>,
+    pub stations: Vec<StationJson>,
+    pub alerts: Vec<AlertJson>,
+}
+
+/// All APs, strongest first.
+pub fn aps_json(state: &AppState) -> String {
+    to_json(&ap_list(state))
+}
+
+/// All stations, most recently seen first.
+pub fn stations_json(state: &AppState) -> String {
+    to_json(&station_list(state))
+}
+
+/// Alert history, newest first.
+pub fn alerts_json(state: &AppState) -> String {
+    to_json(&alert_list(state))
+}
+
+pub fn snapshot_json(state: &AppState) -> String {
+    to_json(&SnapshotJson {
+        interface: state.interface.clone(),
+        total_packets: state.total_packets,
+        access_points: ap_list(state),
+        stations: station_list(state),
+        alerts: alert_list(state),
+    })
+}
+
+fn ap_list(state: &AppState) -> Vec<ApJson> {
+    state
+        .sorted_aps()
+        .into_iter()
+        .map(|ap| ApJson::from_ap(ap, state))
+        .collect()
+}
+
+fn station_list(state: &AppState) -> Vec<StationJson> {
+    let mut stations: Vec<&Station> = state.stations.values().collect();
+    stations.sort_by_key(|sta| sta.last_seen);
+    let now = state.now();
+    stations
+        .into_iter()
+        .rev()
+        .map(|sta| StationJson::from_station(sta, now))
+        .collect()
+}
+
+fn alert_list(state: &AppState) -> Vec<AlertJson> {
+    let now = state.now();
+    let mut alerts: Vec<AlertJson> = state
+        .alerts
+        .iter()
+        .map(|alert| AlertJson::from_alert(alert, now))
+        .collect();
+    alerts.reverse();
+    alerts
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    // Serializing plain structs of strings and numbers cannot fail
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -0,0 +1,52 @@
+use std::fmt::Write;
+
+use crate::model::AppState;
+
+/// Render geotagged APs as a KML document, one placemark per AP.
+///
+/// Placemarks use the signal-weighted centroid of all sightings, which is a
+/// better estimate of the AP's position than any single sighting.
+pub fn render_kml(state: &AppState) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n\
+         <Document>\n\
+         <name>wifimonitor-tui survey</name>\n",
+    );
+
+    for ap in state.sorted_aps() {
+        let Some((lat, lon)) = ap.location.centroid() else {
+            continue;
+        };
+        let strongest = ap
+            .location
+            .strongest
+            .map(|s| format!("{} dBm", s.signal_dbm))
+            .unwrap_or_default();
+        let _ = write!(
+            out,
+            "<Placemark>\n\
+             <name>{}</name>\n\
+             <description>BSSID: {}\nChannel: {}\nEncryption: {}\nStrongest: {}\nSightings: {}</description>\n\
+             <Point><coordinates>{lon:.7},{lat:.7}</coordinates></Point>\n\
+             </Placemark>\n",
+            xml_escape(ap.display_ssid()),
+            ap.bssid_str(),
+            ap.channel,
+            ap.encryption,
+            strongest,
+            ap.location.sample_count,
+        );
+    }
+
+    out.push_str("</Document>\n</kml>\n");
+    out
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,61 @@
+pub mod json;
+pub mod kml;
+pub mod prometheus;
+pub mod survey_csv;
+pub mod wigle;
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::model::AppState;
+
+/// File formats the current model can be written out as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Wigle,
+    Kml,
+    Survey,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ExportFormat::Json),
+            "wigle" => Ok(ExportFormat::Wigle),
+            "kml" => Ok(ExportFormat::Kml),
+            "survey" => Ok(ExportFormat::Survey),
+            _ => Err(format!(
+                "unknown export format '{s}' (expected json, wigle, kml or survey)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Json => write!(f, "json"),
+            ExportFormat::Wigle => write!(f, "wigle"),
+            ExportFormat::Kml => write!(f, "kml"),
+            ExportFormat::Survey => write!(f, "survey"),
+        }
+    }
+}
+
+/// Render the model in `format` and write it to `path`.
+pub fn export_to_file(format: ExportFormat, path: &Path, state: &AppState) -> Result<(), String> {
+    let contents = match format {
+        ExportFormat::Json => json::snapshot_json(state),
+        ExportFormat::Wigle => wigle::render_wigle_csv(state),
+        ExportFormat::Kml => kml::render_kml(state),
+        ExportFormat::Survey => match &state.survey {
+            Some(survey) => survey_csv::render_survey_csv(survey),
+            None => return Err("no survey floor plan loaded".to_string()),
+        },
+    };
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
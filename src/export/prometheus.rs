@@ -0,0 +1,156 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use crate::model::spectrum::{self, CHANNELS_2G, CHANNELS_5G};
+use crate::model::{AppState, LinkStats};
+
+/// Window the utilization gauge is averaged over.
+const UTILIZATION_WINDOW: Duration = Duration::from_secs(60);
+
+/// Render the current state in the Prometheus text exposition format.
+pub fn render_metrics(state: &AppState) -> String {
+    let mut out = String::new();
+
+    metric_header(&mut out, "wifimonitor_packets_total", "counter", "Total frames captured.");
+    let _ = writeln!(out, "wifimonitor_packets_total {}", state.total_packets);
+
+    metric_header(&mut out, "wifimonitor_packet_rate", "gauge", "Frames captured in the last second.");
+    let _ = writeln!(out, "wifimonitor_packet_rate {}", state.current_packet_rate());
+
+    metric_header(&mut out, "wifimonitor_current_channel", "gauge", "Channel the interface is tuned to.");
+    let _ = writeln!(out, "wifimonitor_current_channel {}", state.current_channel);
+
+    metric_header(
+        &mut out,
+        "wifimonitor_channel_packets_total",
+        "counter",
+        "Frames captured per channel.",
+    );
+    let mut channels: Vec<(&u8, &u64)> = state.channel_packets.iter().collect();
+    channels.sort_by_key(|(ch, _)| **ch);
+    for (ch, count) in channels {
+        let _ = writeln!(out, "wifimonitor_channel_packets_total{{channel=\"{ch}\"}} {count}");
+    }
+
+    metric_header(
+        &mut out,
+        "wifimonitor_channel_corrupt_frames_total",
+        "counter",
+        "Frames per channel that failed their FCS check.",
+    );
+    let mut corrupt: Vec<(&u8, &u64)> = state.channel_corrupt.iter().collect();
+    corrupt.sort_by_key(|(ch, _)| **ch);
+    for (ch, count) in corrupt {
+        let _ = writeln!(out, "wifimonitor_channel_corrupt_frames_total{{channel=\"{ch}\"}} {count}");
+    }
+
+    metric_header(
+        &mut out,
+        "wifimonitor_channel_retries_total",
+        "counter",
+        "Management and data frames per channel with the retry flag set.",
+    );
+    let mut links: Vec<(&u8, &LinkStats)> = state.channel_link.iter().collect();
+    links.sort_by_key(|(ch, _)| **ch);
+    for (ch, link) in &links {
+        let _ = writeln!(out, "wifimonitor_channel_retries_total{{channel=\"{ch}\"}} {}", link.retries);
+    }
+
+    metric_header(
+        &mut out,
+        "wifimonitor_channel_utilization_percent",
+        "gauge",
+        "Estimated airtime share per channel over the last minute, relative to time listened.",
+    );
+    let now = state.now();
+    let mut usage: Vec<(u8, f64)> = state
+        .channel_usage
+        .iter()
+        .filter_map(|(&ch, usage)| Some((ch, usage.utilization_percent(now, UTILIZATION_WINDOW)?)))
+        .collect();
+    usage.sort_by_key(|(ch, _)| *ch);
+    for (ch, percent) in usage {
+        let _ = writeln!(out, "wifimonitor_channel_utilization_percent{{channel=\"{ch}\"}} {percent:.1}");
+    }
+
+    metric_header(
+        &mut out,
+        "wifimonitor_channel_interference_score",
+        "gauge",
+        "Signal-weighted overlap of all APs with each 20 MHz channel.",
+    );
+    let channels: Vec<u8> = CHANNELS_2G.iter().chain(CHANNELS_5G.iter()).copied().collect();
+    for (ch, score) in spectrum::interference_scores(state.access_points.values(), &channels) {
+        let _ = writeln!(out, "wifimonitor_channel_interference_score{{channel=\"{ch}\"}} {score:.2}");
+    }
+
+    metric_header(&mut out, "wifimonitor_access_points", "gauge", "Access points currently tracked.");
+    let _ = writeln!(out, "wifimonitor_access_points {}", state.access_points.len());
+
+    metric_header(&mut out, "wifimonitor_stations", "gauge", "Client stations currently tracked.");
+    let _ = writeln!(out, "wifimonitor_stations {}", state.stations.len());
+
+    metric_header(&mut out, "wifimonitor_ap_signal_dbm", "gauge", "Last observed signal per AP.");
+    for ap in state.sorted_aps() {
+        let _ = writeln!(
+            out,
+            "wifimonitor_ap_signal_dbm{{bssid=\"{}\",ssid=\"{}\",channel=\"{}\"}} {}",
+            ap.bssid_str(),
+            escape_label(&ap.ssid),
+            ap.channel,
+            ap.signal_dbm,
+        );
+    }
+
+    metric_header(
+        &mut out,
+        "wifimonitor_ap_retry_percent",
+        "gauge",
+        "Share of frames sent by each AP with the retry flag set.",
+    );
+    for ap in state.sorted_aps() {
+        if let Some(retry) = ap.link.retry_percent() {
+            let _ = writeln!(
+                out,
+                "wifimonitor_ap_retry_percent{{bssid=\"{}\",ssid=\"{}\",channel=\"{}\"}} {retry:.1}",
+                ap.bssid_str(),
+                escape_label(&ap.ssid),
+                ap.channel,
+            );
+        }
+    }
+
+    metric_header(&mut out, "wifimonitor_pcap_received_total", "counter", "Packets received by libpcap.");
+    let _ = writeln!(out, "wifimonitor_pcap_received_total {}", state.pcap_stats.received);
+
+    metric_header(
+        &mut out,
+        "wifimonitor_pcap_dropped_total",
+        "counter",
+        "Packets dropped because the kernel buffer was full.",
+    );
+    let _ = writeln!(out, "wifimonitor_pcap_dropped_total {}", state.pcap_stats.dropped);
+
+    metric_header(
+        &mut out,
+        "wifimonitor_pcap_if_dropped_total",
+        "counter",
+        "Packets dropped by the interface or driver.",
+    );
+    let _ = writeln!(out, "wifimonitor_pcap_if_dropped_total {}", state.pcap_stats.if_dropped);
+
+    out
+}
+
+fn metric_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escape a label value per the exposition format (backslash, quote, newline).
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
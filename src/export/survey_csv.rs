@@ -0,0 +1,29 @@
+use std::fmt::Write;
+
+use crate::model::Survey;
+use crate::util::mac::format_mac;
+use crate::util::time::format_utc;
+
+/// Render every recorded survey reading as CSV, one row per sample.
+pub fn render_survey_csv(survey: &Survey) -> String {
+    let mut out = String::from("timestamp,x,y,bssid,ssid,channel,signal_dbm\n");
+    for s in &survey.samples {
+        let ssid = if s.ssid.contains([',', '"', '\n']) {
+            format!("\"{}\"", s.ssid.replace('"', "\"\""))
+        } else {
+            s.ssid.clone()
+        };
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            format_utc(s.taken_at),
+            s.x,
+            s.y,
+            format_mac(&s.bssid),
+            ssid,
+            s.channel,
+            s.signal_dbm,
+        );
+    }
+    out
+}
@@ -0,0 +1,61 @@
+use std::fmt::Write;
+
+use crate::model::{AccessPoint, AppState, EncryptionType};
+use crate::util::time::format_utc;
+
+const HEADER_COLUMNS: &str =
+    "MAC,SSID,AuthMode,FirstSeen,Channel,RSSI,CurrentLatitude,CurrentLongitude,AltitudeMeters,AccuracyMeters,Type";
+
+/// Render geotagged APs as a WiGLE 1.4 CSV upload.
+///
+/// Each AP is written once, at the position of its strongest sighting; APs
+/// never seen with a GPS fix are skipped.
+pub fn render_wigle_csv(state: &AppState) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+    let mut out = format!(
+        "WigleWifi-1.4,appRelease={version},model=wifimonitor-tui,release={version},device={},display=,board=,brand=\n",
+        state.interface,
+    );
+    out.push_str(HEADER_COLUMNS);
+    out.push('\n');
+
+    for ap in state.sorted_aps() {
+        let Some(best) = ap.location.strongest else {
+            continue;
+        };
+        let _ = writeln!(
+            out,
+            "{},{},{},{},{},{},{:.7},{:.7},{},{},WIFI",
+            ap.bssid_str().to_lowercase(),
+            csv_field(&ap.ssid),
+            auth_mode(ap),
+            format_utc(ap.first_seen),
+            ap.channel,
+            best.signal_dbm,
+            best.lat,
+            best.lon,
+            best.alt.map(|a| format!("{a:.1}")).unwrap_or_default(),
+            best.accuracy_m.map(|a| format!("{a:.1}")).unwrap_or_default(),
+        );
+    }
+    out
+}
+
+/// WiGLE's capability string, in the Android scan-result style it expects.
+fn auth_mode(ap: &AccessPoint) -> &'static str {
+    match ap.encryption {
+        EncryptionType::Open | EncryptionType::Unknown => "[ESS]",
+        EncryptionType::WEP => "[WEP][ESS]",
+        EncryptionType::WPA => "[WPA-PSK-TKIP][ESS]",
+        EncryptionType::WPA2 => "[WPA2-PSK-CCMP][ESS]",
+        EncryptionType::WPA3 => "[WPA3-SAE-CCMP][ESS]",
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,17 +1,28 @@
+mod alerting;
+mod api;
 mod app;
 mod capture;
+mod export;
 mod model;
 mod tui;
 mod util;
 
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use clap::Parser;
 
+use alerting::rules::Rule;
+use api::daemon::DEFAULT_SOCKET_PATH;
 use capture::channel_hopper;
+use capture::gps::DEFAULT_GPSD_ADDR;
 use capture::interface;
 use capture::packet_handler;
-use model::AppState;
+use model::recommend;
+use capture::trigger_ring::RingConfig;
+use model::{AppState, FloorPlan, Severity, Survey};
+use util::clock::Clock;
 
 #[derive(Parser)]
 #[command(name = "wifimonitor-tui")]
@@ -24,20 +35,142 @@ struct Cli {
     /// Skip enabling monitor mode (assume already in monitor mode)
     #[arg(long)]
     no_monitor: bool,
+
+    /// Serve Prometheus metrics and a JSON API on this address (e.g. 127.0.0.1:9100)
+    #[arg(long, value_name = "ADDR")]
+    http: Option<String>,
+
+    /// Accept runtime commands on this Unix socket (e.g. /run/wifimonitor.sock)
+    #[arg(long, value_name = "PATH")]
+    control_socket: Option<PathBuf>,
+
+    /// Run headless as a capture daemon; TUIs attach with --attach
+    #[arg(long, conflicts_with = "attach")]
+    daemon: bool,
+
+    /// Attach a TUI to a running daemon instead of capturing locally
+    #[arg(
+        long,
+        value_name = "PATH",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_SOCKET_PATH,
+    )]
+    attach: Option<PathBuf>,
+
+    /// Tag AP sightings with positions from gpsd (default 127.0.0.1:2947)
+    #[arg(
+        long,
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_GPSD_ADDR,
+    )]
+    gpsd: Option<String>,
+
+    /// Replay a pcap file instead of capturing live (no root or monitor mode needed)
+    #[arg(
+        short,
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["interface", "no_monitor", "daemon", "attach", "gpsd"],
+    )]
+    read: Option<PathBuf>,
+
+    /// Capture for SECS (default 60) without the TUI, then print channel
+    /// recommendations for a new AP (with --read, use the whole file)
+    #[arg(
+        long,
+        value_name = "SECS",
+        num_args = 0..=1,
+        default_missing_value = "60",
+        conflicts_with_all = ["daemon", "attach"],
+    )]
+    recommend: Option<u64>,
+
+    /// SSID of a network under assessment (repeatable); clients probing for
+    /// it while none of its APs are in range are flagged
+    #[arg(long = "corporate-ssid", value_name = "SSID")]
+    corporate_ssids: Vec<String>,
+
+    /// Raise alerts and run actions from the rules in this file
+    #[arg(long, value_name = "FILE")]
+    rules: Option<PathBuf>,
+
+    /// Keep the last SECS of frames in memory and save them, plus what
+    /// follows, to a pcapng file when an alert fires or on demand
+    #[arg(long, value_name = "SECS", conflicts_with = "attach")]
+    trigger_ring: Option<u64>,
+
+    /// Seconds of frames to keep saving after a trigger
+    #[arg(long, value_name = "SECS", default_value_t = 10, requires = "trigger_ring")]
+    trigger_post: u64,
+
+    /// Directory for trigger captures, ring exports and per-AP extractions
+    // `--trigger-dir` is its name from before extractions shared it
+    #[arg(long, alias = "trigger-dir", value_name = "DIR", default_value = ".", conflicts_with = "attach")]
+    capture_dir: PathBuf,
+
+    /// Lowest alert severity that triggers a capture (info, warning or high)
+    #[arg(long, value_name = "LEVEL", default_value = "warning", requires = "trigger_ring")]
+    trigger_severity: Severity,
+
+    /// Enable floor-plan survey mode from an ASCII map file or WxH grid size (e.g. 40x20)
+    #[arg(long, value_name = "FILE|WxH")]
+    survey_map: Option<String>,
 }
 
 fn main() {
     let cli = Cli::parse();
 
+    // Attaching only needs access to the socket, not root
+    if let Some(path) = &cli.attach {
+        run_attached(path);
+        return;
+    }
+
     // Check for root privileges
-    if unsafe { libc::geteuid() } != 0 {
+    if cli.read.is_none() && unsafe { libc::geteuid() } != 0 {
         eprintln!("Error: wifimonitor-tui requires root privileges for monitor mode.");
         eprintln!("Run with: sudo wifimonitor-tui");
         std::process::exit(1);
     }
 
+    // Load the floor plan before touching the interface so a bad path fails cleanly
+    let floor_plan = match cli.survey_map.as_deref().map(FloorPlan::load).transpose() {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let rules = match cli.rules.as_deref().map(alerting::rules::load).transpose() {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let ring = cli.trigger_ring.map(|secs| RingConfig {
+        pre: Duration::from_secs(secs),
+        post: Duration::from_secs(cli.trigger_post),
+    });
+
+    if let Some(path) = &cli.read {
+        let options = ReplayOptions {
+            rules,
+            ring,
+            trigger_severity: cli.trigger_severity,
+            capture_dir: cli.capture_dir.clone(),
+            corporate_ssids: &cli.corporate_ssids,
+            recommend: cli.recommend.is_some(),
+        };
+        run_replay(path, floor_plan, options);
+        return;
+    }
+
     // Determine interface
-    let iface = match cli.interface {
+    let iface = match cli.interface.clone() {
         Some(i) => i,
         None => {
             let interfaces = interface::detect_wireless_interfaces();
@@ -74,20 +207,80 @@ fn main() {
         }
     };
 
-    let state = Arc::new(Mutex::new(AppState::new(monitor_iface.clone())));
+    let mut app_state = AppState::new(monitor_iface.clone(), Clock::wall());
+    app_state.corporate_ssids = cli.corporate_ssids.iter().cloned().collect();
+    app_state.capture_trigger = ring.is_some().then_some(cli.trigger_severity);
+    app_state.capture_dir = cli.capture_dir.clone();
+    if let Some(plan) = floor_plan {
+        app_state.survey = Some(Survey::new(plan));
+        app_state.show_survey = true;
+    }
+    let state = Arc::new(Mutex::new(app_state));
 
     // If we already set monitor mode via iw, don't ask pcap to also set rfmon.
     // Only use pcap rfmon if --no-monitor was passed (user manages it themselves).
     let use_rfmon = false;
 
     // Start capture thread
-    let capture_handle = packet_handler::start_capture(&monitor_iface, Arc::clone(&state), use_rfmon);
+    let capture_handle = packet_handler::start_capture(&monitor_iface, Arc::clone(&state), use_rfmon, ring);
 
     // Start channel hopper thread
     let hopper_handle = channel_hopper::start_hopper(&monitor_iface, Arc::clone(&state));
 
-    // Run TUI on main thread
-    let result = app::run(Arc::clone(&state));
+    // Start gpsd reader if requested
+    let gps_handle = cli
+        .gpsd
+        .as_deref()
+        .map(|addr| capture::gps::start_gpsd(addr, Arc::clone(&state)));
+
+    // Start the rules engine if requested
+    let alerting_handle = rules.map(|rules| alerting::engine::start_alerting(rules, Arc::clone(&state)));
+
+    // Start HTTP metrics/API server if requested
+    let http_handle = match &cli.http {
+        Some(addr) => match api::http_server::start_http_server(addr, Arc::clone(&state)) {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                state.lock().unwrap().error = Some(e);
+                state.lock().unwrap().stop();
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Start control socket if requested; a daemon always needs one for clients
+    let socket_path = match (&cli.control_socket, cli.daemon) {
+        (Some(path), _) => Some(path.clone()),
+        (None, true) => Some(PathBuf::from(DEFAULT_SOCKET_PATH)),
+        (None, false) => None,
+    };
+    let control_handle = match &socket_path {
+        Some(path) => match api::control_socket::start_control_socket(path, Arc::clone(&state)) {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                state.lock().unwrap().error = Some(e);
+                state.lock().unwrap().stop();
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Run TUI on main thread, or wait for a shutdown signal as a daemon
+    let result = if cli.daemon {
+        if let Some(path) = &socket_path {
+            eprintln!("Capturing on {monitor_iface}; attach with: wifimonitor-tui --attach {}", path.display());
+        }
+        api::daemon::run(&state);
+        Ok(())
+    } else if let Some(secs) = cli.recommend {
+        eprintln!("Surveying channels on {monitor_iface} for {secs}s...");
+        api::daemon::run_until(&state, Some(Instant::now() + Duration::from_secs(secs)));
+        Ok(())
+    } else {
+        app::run(Arc::clone(&state), None)
+    };
 
     // Signal threads to stop
     state.lock().unwrap().stop();
@@ -95,6 +288,18 @@ fn main() {
     // Wait for threads
     let _ = capture_handle.join();
     let _ = hopper_handle.join();
+    if let Some(handle) = gps_handle {
+        let _ = handle.join();
+    }
+    if let Some(handle) = alerting_handle {
+        let _ = handle.join();
+    }
+    if let Some(handle) = http_handle {
+        let _ = handle.join();
+    }
+    if let Some(handle) = control_handle {
+        let _ = handle.join();
+    }
 
     // Restore managed mode unless --no-monitor was used
     if !cli.no_monitor {
@@ -112,4 +317,122 @@ fn main() {
         eprintln!("TUI error: {e}");
         std::process::exit(1);
     }
+
+    if let Some(secs) = cli.recommend {
+        print_recommendations(&state.lock().unwrap(), Duration::from_secs(secs));
+    }
+}
+
+/// Print ranked channel recommendations, grouped by band.
+fn print_recommendations(state: &AppState, window: Duration) {
+    println!("Channel recommendations ({} APs heard):", state.access_points.len());
+    let recommendations = recommend::recommend(state, window);
+    if recommendations.is_empty() {
+        println!("\nNo channels were listened to long enough to judge.");
+    }
+    let mut band = "";
+    for rec in recommendations {
+        if rec.band != band {
+            band = rec.band;
+            println!("\n{band}");
+        }
+        println!("  {rec}");
+    }
+}
+
+/// Run the TUI against a daemon's socket. Capture keeps running when this exits.
+fn run_attached(path: &Path) {
+    let state = Arc::new(Mutex::new(AppState::new(String::new(), Clock::wall())));
+
+    let (stream_handle, client) = match api::client::attach(path, Arc::clone(&state)) {
+        Ok(attached) => attached,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let result = app::run(Arc::clone(&state), Some(client));
+
+    state.lock().unwrap().stop();
+    let _ = stream_handle.join();
+
+    if let Some(err) = &state.lock().unwrap().error {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = result {
+        eprintln!("TUI error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Settings from the command line that apply to a replay.
+struct ReplayOptions<'a> {
+    rules: Option<Vec<Rule>>,
+    ring: Option<RingConfig>,
+    trigger_severity: Severity,
+    capture_dir: PathBuf,
+    corporate_ssids: &'a [String],
+    recommend: bool,
+}
+
+/// Run the TUI over a pcap file, with time taken from the capture timestamps.
+///
+/// With `recommend`, skip the TUI: read the whole file and print channel
+/// recommendations instead.
+fn run_replay(path: &Path, floor_plan: Option<FloorPlan>, options: ReplayOptions) {
+    let ReplayOptions {
+        rules,
+        ring,
+        trigger_severity,
+        capture_dir,
+        corporate_ssids,
+        recommend,
+    } = options;
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut app_state = AppState::new(name, Clock::fixed(Instant::now()));
+    app_state.corporate_ssids = corporate_ssids.iter().cloned().collect();
+    app_state.capture_trigger = ring.is_some().then_some(trigger_severity);
+    app_state.capture_dir = capture_dir;
+    app_state.channel_from_capture = true;
+    if let Some(plan) = floor_plan {
+        app_state.survey = Some(Survey::new(plan));
+        app_state.show_survey = true;
+    }
+    let state = Arc::new(Mutex::new(app_state));
+
+    let replay_handle = packet_handler::start_replay(path, Arc::clone(&state), ring);
+    let alerting_handle = rules.map(|rules| alerting::engine::start_alerting(rules, Arc::clone(&state)));
+
+    if recommend {
+        let _ = replay_handle.join();
+        let st = state.lock().unwrap();
+        if let Some(err) = &st.error {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+        let span = st.now().saturating_duration_since(st.started_at);
+        print_recommendations(&st, span.max(Duration::from_secs(1)));
+        return;
+    }
+
+    let result = app::run(Arc::clone(&state), None);
+
+    state.lock().unwrap().stop();
+    let _ = replay_handle.join();
+    if let Some(handle) = alerting_handle {
+        let _ = handle.join();
+    }
+
+    if let Some(err) = &state.lock().unwrap().error {
+        eprintln!("Error: {err}");
+        std::process::exit(1);
+    }
+
+    if let Err(e) = result {
+        eprintln!("TUI error: {e}");
+        std::process::exit(1);
+    }
 }
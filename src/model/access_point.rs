@@ -1,12 +1,49 @@
+use std::collections::BTreeSet;
 use std::fmt;
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 
+use serde::{Deserialize, Serialize};
+
+use crate::model::phy::PhyRate;
+use crate::model::spectrum::ChannelSpan;
+use crate::model::LinkStats;
+use crate::model::ApLocation;
+use crate::util::mac::format_mac;
 use crate::util::ring_buffer::RingBuffer;
+use crate::util::time_series::{RollupTiers, TimedSample};
 
-/// Signal history: 240 samples = 60 seconds at 4 samples/sec.
-const SIGNAL_HISTORY_CAP: usize = 240;
+/// One time unit (TU), the unit of the beacon interval.
+const TU: Duration = Duration::from_micros(1024);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A TSF this far behind the last one means the AP restarted its timer.
+const TSF_RESET_SLACK_US: u64 = 1_000_000;
+
+/// Baseline needed before a TSF skew estimate is meaningful.
+const MIN_SKEW_BASELINE: Duration = Duration::from_secs(10);
+
+/// SSIDs kept per AP from its beacons, and from its probe responses. A
+/// Karma/MANA responder answers every SSID probed for; past this many we
+/// stop recording new ones, long after it has been flagged.
+const MAX_AP_SSIDS: usize = 64;
+
+/// Minimum gap between repeat alerts for one Karma/MANA suspect.
+const KARMA_REALERT: Duration = Duration::from_secs(60);
+
+/// Minimum gap between reboot or BSSID conflict alerts for one AP.
+const TSF_REALERT: Duration = Duration::from_secs(60);
+
+/// After TSFs from two radios are seen, backward jumps this soon after are
+/// the other radio, not a reboot.
+const TSF_CONFLICT_MEMORY: Duration = Duration::from_secs(300);
+
+/// Smoothing factor for beacon jitter.
+const JITTER_SMOOTHING: f64 = 0.1;
+
+/// Signal history: one sample per beacon/probe response. 1200 samples covers
+/// 60 seconds of a 100 TU beacon interval with the hopper locked on the AP.
+const SIGNAL_HISTORY_CAP: usize = 1200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum EncryptionType {
     Open,
     WEP,
@@ -29,37 +66,367 @@ impl fmt::Display for EncryptionType {
     }
 }
 
+/// Frame a hidden network's SSID was learned from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecloakSource {
+    ProbeResponse,
+    AssociationRequest,
+    ReassociationRequest,
+}
+
+impl fmt::Display for DecloakSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecloakSource::ProbeResponse => write!(f, "probe response"),
+            DecloakSource::AssociationRequest => write!(f, "association request"),
+            DecloakSource::ReassociationRequest => write!(f, "reassociation request"),
+        }
+    }
+}
+
+/// Timing fields of one received beacon.
+#[derive(Debug, Clone, Copy)]
+pub struct BeaconTiming {
+    pub interval_tu: u16,
+    /// The AP's TSF timer when it sent the beacon, in µs.
+    pub tsf: u64,
+    /// Our radio's TSF timer when the beacon arrived, if the driver gives it.
+    pub local_tsft: Option<u64>,
+}
+
+/// Load the AP advertises in its BSS Load element.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BssLoad {
+    pub station_count: u16,
+    /// Fraction of time the AP sensed the medium busy, scaled to 0-255.
+    pub channel_utilization: u8,
+}
+
+impl BssLoad {
+    pub fn utilization_percent(&self) -> f64 {
+        self.channel_utilization as f64 * 100.0 / 255.0
+    }
+}
+
+/// What a beacon's TSF says about the AP's timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsfEvent {
+    /// The timer went backwards, as after a reboot; holds the TSF before.
+    Reset(u64),
+    /// The timer disagrees with itself, so several radios use the BSSID.
+    Conflict,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct AccessPoint {
     pub ssid: String,
+    /// Beacons carry an empty or zeroed SSID.
+    pub hidden: bool,
+    /// Length of the real SSID, when beacons zero it out rather than omit it.
+    pub hidden_ssid_len: Option<u8>,
+    /// Where `ssid` came from, when not from a beacon.
+    pub decloaked_by: Option<DecloakSource>,
+    /// Non-empty SSIDs seen in the AP's beacons.
+    pub beaconed_ssids: BTreeSet<String>,
+    /// SSIDs the AP sent probe responses for.
+    pub responded_ssids: BTreeSet<String>,
+    /// Members of `responded_ssids` not in `beaconed_ssids`, kept up to date
+    /// as either changes.
+    pub impersonated_count: usize,
+    /// Impersonated SSID count and time of the last Karma alert.
+    #[serde(skip)]
+    karma_alerted: Option<(usize, Instant)>,
     pub bssid: [u8; 6],
     pub channel: u8,
+    /// `channel` is a 6 GHz channel number; those reuse 1-233.
+    pub six_ghz: bool,
     pub encryption: EncryptionType,
     pub signal_dbm: i8,
-    pub signal_history: RingBuffer<i8>,
+    /// Noise from the latest frame that reported it, if the driver gives it.
+    pub noise_dbm: Option<i8>,
+    /// Signal per receive chain from the latest beacon, as (antenna, dBm).
+    pub chain_signal: Vec<(u8, i8)>,
+    /// Rate the latest beacon or probe response was sent at.
+    pub rate: Option<PhyRate>,
+    pub signal_history: RingBuffer<TimedSample<i8>>,
+    /// Long-term signal history. Synced to attached clients separately
+    /// (see `api::snapshot`) because it is large and changes slowly.
+    #[serde(skip)]
+    pub signal_rollups: RollupTiers,
+    #[serde(with = "crate::util::serde_instant")]
     pub last_seen: Instant,
+    /// Wall-clock time of the first sighting (for exports).
+    pub first_seen: SystemTime,
     pub beacon_count: u64,
+    /// Location estimate from geotagged sightings (empty without GPS).
+    pub location: ApLocation,
+    /// Latest BSS Load element, if the AP sends one.
+    pub bss_load: Option<BssLoad>,
+    /// Retries and sequence gaps in frames the AP sent.
+    pub link: LinkStats,
+    /// Bonded channel from the HT/VHT/HE operation elements, if wider than 20 MHz.
+    pub operating_span: Option<ChannelSpan>,
+    /// From a Multiple BSSID element: BSSIDs of the set share all but this
+    /// many low bits with ours.
+    pub multi_bssid_indicator: Option<u8>,
+    /// BSSIDs the AP lists as co-located or in its multiple BSSID set in
+    /// its Reduced Neighbor Report.
+    pub colocated: Vec<[u8; 6]>,
+    /// Advertised beacon interval in TU.
+    pub beacon_interval_tu: Option<u16>,
+    #[serde(skip)]
+    last_beacon_at: Option<Instant>,
+    /// Beacons due while we were listening that never arrived.
+    pub missed_beacons: u64,
+    /// AP's TSF timer in the latest beacon (µs since it started, usually at boot).
+    pub tsf_us: Option<u64>,
+    /// TSF resets seen, i.e. likely reboots.
+    pub reboots: u64,
+    /// Forward TSF jumps seen, each meaning another radio beacons with this BSSID.
+    pub tsf_conflicts: u64,
+    #[serde(skip)]
+    last_tsf_conflict: Option<Instant>,
+    #[serde(skip)]
+    tsf_alerted_at: Option<Instant>,
+    /// Mean deviation of beacon arrivals from the advertised schedule, in µs.
+    pub beacon_jitter_us: Option<f64>,
+    /// How fast the AP's TSF runs against our radio's, in parts per million.
+    pub tsf_skew_ppm: Option<f64>,
+    /// First beacon since the last reset, as (AP TSF, our TSF, arrival time).
+    #[serde(skip)]
+    tsf_reference: Option<(u64, Option<u64>, Instant)>,
 }
 
 impl AccessPoint {
-    pub fn new(bssid: [u8; 6]) -> Self {
+    pub fn new(bssid: [u8; 6], seen_at: Instant, first_seen: SystemTime) -> Self {
         Self {
             ssid: String::new(),
+            hidden: false,
+            hidden_ssid_len: None,
+            decloaked_by: None,
+            beaconed_ssids: BTreeSet::new(),
+            responded_ssids: BTreeSet::new(),
+            impersonated_count: 0,
+            karma_alerted: None,
             bssid,
             channel: 0,
+            six_ghz: false,
             encryption: EncryptionType::Unknown,
             signal_dbm: -100,
+            noise_dbm: None,
+            chain_signal: Vec::new(),
+            rate: None,
             signal_history: RingBuffer::new(SIGNAL_HISTORY_CAP),
-            last_seen: Instant::now(),
+            signal_rollups: RollupTiers::default(),
+            last_seen: seen_at,
+            first_seen,
             beacon_count: 0,
+            location: ApLocation::default(),
+            bss_load: None,
+            link: LinkStats::default(),
+            operating_span: None,
+            multi_bssid_indicator: None,
+            colocated: Vec::new(),
+            beacon_interval_tu: None,
+            last_beacon_at: None,
+            missed_beacons: 0,
+            tsf_us: None,
+            reboots: 0,
+            tsf_conflicts: 0,
+            last_tsf_conflict: None,
+            tsf_alerted_at: None,
+            beacon_jitter_us: None,
+            tsf_skew_ppm: None,
+            tsf_reference: None,
+        }
+    }
+
+    /// Note a beacon heard at `now`. `listening_since` is when we started
+    /// listening on the AP's channel, or None if we aren't on it.
+    ///
+    /// Returns what the beacon's TSF revealed, if anything.
+    pub fn record_beacon(
+        &mut self,
+        timing: BeaconTiming,
+        now: Instant,
+        listening_since: Option<Instant>,
+    ) -> Option<TsfEvent> {
+        let BeaconTiming { interval_tu, tsf, local_tsft } = timing;
+        let since_last = self.last_beacon_at.map(|prev| now.saturating_duration_since(prev));
+        if let (Some(prev), Some(since)) = (self.last_beacon_at, listening_since) {
+            if interval_tu > 0 && prev >= since {
+                let period = (TU * interval_tu as u32).as_secs_f64();
+                let elapsed = now.saturating_duration_since(prev).as_secs_f64();
+                let intervals = (elapsed / period).round();
+                self.missed_beacons += (intervals as u64).saturating_sub(1);
+                if intervals >= 1.0 {
+                    let deviation = (elapsed - intervals * period).abs() * 1e6;
+                    let jitter = self.beacon_jitter_us.get_or_insert(deviation);
+                    *jitter += (deviation - *jitter) * JITTER_SMOOTHING;
+                }
+            }
+        }
+        self.beacon_interval_tu = Some(interval_tu);
+        self.last_beacon_at = Some(now);
+
+        // One timer can restart but never gets ahead of the time that passed
+        let conflicted = self
+            .last_tsf_conflict
+            .is_some_and(|at| now.saturating_duration_since(at) < TSF_CONFLICT_MEMORY);
+        let event = match (self.tsf_us, since_last) {
+            (Some(prev), _) if tsf.saturating_add(TSF_RESET_SLACK_US) < prev => {
+                Some(if conflicted { TsfEvent::Conflict } else { TsfEvent::Reset(prev) })
+            }
+            (Some(prev), Some(elapsed))
+                if tsf > prev.saturating_add(elapsed.as_micros() as u64).saturating_add(TSF_RESET_SLACK_US) =>
+            {
+                Some(TsfEvent::Conflict)
+            }
+            _ => None,
+        };
+        self.tsf_us = Some(tsf);
+        match event {
+            Some(TsfEvent::Reset(_)) => self.reboots += 1,
+            Some(TsfEvent::Conflict) => {
+                self.tsf_conflicts += 1;
+                self.last_tsf_conflict = Some(now);
+            }
+            None => {}
+        }
+        if event.is_some() {
+            self.tsf_reference = None;
+            self.tsf_skew_ppm = None;
+        }
+
+        match self.tsf_reference {
+            None => self.tsf_reference = Some((tsf, local_tsft, now)),
+            Some((ref_tsf, ref_local, ref_at)) => {
+                // Our radio's TSF is a steadier reference than capture timestamps
+                let local_us = match (ref_local, local_tsft) {
+                    (Some(from), Some(to)) if to >= from => (to - from) as f64,
+                    _ => now.saturating_duration_since(ref_at).as_micros() as f64,
+                };
+                if local_us >= MIN_SKEW_BASELINE.as_micros() as f64 {
+                    let remote_us = tsf.saturating_sub(ref_tsf) as f64;
+                    self.tsf_skew_ppm = Some((remote_us - local_us) / local_us * 1e6);
+                }
+            }
+        }
+        event
+    }
+
+    /// Note an SSID from the AP's beacons.
+    pub fn record_beaconed_ssid(&mut self, ssid: &str) {
+        if self.beaconed_ssids.contains(ssid) || self.beaconed_ssids.len() >= MAX_AP_SSIDS {
+            return;
+        }
+        self.beaconed_ssids.insert(ssid.to_string());
+        if self.responded_ssids.contains(ssid) {
+            self.impersonated_count -= 1;
+        }
+    }
+
+    /// Note an SSID the AP answered a probe for.
+    pub fn record_responded_ssid(&mut self, ssid: &str) {
+        if self.responded_ssids.contains(ssid) || self.responded_ssids.len() >= MAX_AP_SSIDS {
+            return;
+        }
+        self.responded_ssids.insert(ssid.to_string());
+        if !self.beaconed_ssids.contains(ssid) {
+            self.impersonated_count += 1;
         }
     }
 
+    /// SSIDs the AP answered probes for but never beaconed.
+    pub fn impersonated_ssids(&self) -> Vec<&str> {
+        self.responded_ssids
+            .difference(&self.beaconed_ssids)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Answers probes for SSIDs it doesn't beacon, as Karma/MANA responders
+    /// do. An AP beaconing no SSID (hidden, or probe responses only) may
+    /// still answer with its one real SSID.
+    pub fn is_karma(&self) -> bool {
+        let allowed = usize::from(self.beaconed_ssids.is_empty());
+        self.impersonated_count > allowed
+    }
+
+    /// Whether a Karma suspect deserves an alert: on first detection, then
+    /// when it has impersonated more SSIDs, at most once per `KARMA_REALERT`.
+    pub fn karma_alert_due(&mut self, now: Instant) -> bool {
+        let count = self.impersonated_count;
+        let due = match self.karma_alerted {
+            None => true,
+            Some((reported, at)) => count > reported && now.saturating_duration_since(at) >= KARMA_REALERT,
+        };
+        if due {
+            self.karma_alerted = Some((count, now));
+        }
+        due
+    }
+
+    /// Latest signal over the last noise reading.
+    pub fn snr_db(&self) -> Option<i8> {
+        Some(self.signal_dbm.saturating_sub(self.noise_dbm?))
+    }
+
+    /// Whether a reboot or BSSID conflict deserves an alert: at most once per
+    /// `TSF_REALERT`, so alternating radios don't flood the alert list.
+    pub fn tsf_alert_due(&mut self, now: Instant) -> bool {
+        let due = self
+            .tsf_alerted_at
+            .is_none_or(|at| now.saturating_duration_since(at) >= TSF_REALERT);
+        if due {
+            self.tsf_alerted_at = Some(now);
+        }
+        due
+    }
+
+    /// Whether beacons from more than one radio carry this BSSID.
+    pub fn has_tsf_conflict(&self) -> bool {
+        self.tsf_conflicts > 0
+    }
+
+    /// Time since the AP's TSF timer started, as of `now`.
+    pub fn uptime(&self, now: Instant) -> Option<Duration> {
+        let tsf = Duration::from_micros(self.tsf_us?);
+        Some(tsf + now.saturating_duration_since(self.last_seen))
+    }
+
+    /// Share of expected beacons that never arrived.
+    pub fn missed_beacon_percent(&self) -> Option<f64> {
+        let expected = self.beacon_count + self.missed_beacons;
+        (self.missed_beacons > 0).then(|| self.missed_beacons as f64 * 100.0 / expected as f64)
+    }
+
     pub fn bssid_str(&self) -> String {
-        format!(
-            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
-            self.bssid[0], self.bssid[1], self.bssid[2],
-            self.bssid[3], self.bssid[4], self.bssid[5],
-        )
+        format_mac(&self.bssid)
+    }
+
+    /// Frequencies the AP transmits on.
+    pub fn span(&self) -> Option<ChannelSpan> {
+        let primary = if self.six_ghz {
+            ChannelSpan::primary_6g(self.channel)
+        } else {
+            ChannelSpan::primary(self.channel)
+        };
+        self.operating_span.or(primary)
+    }
+
+    /// Band the AP is on, in GHz.
+    pub fn band_label(&self) -> &'static str {
+        match self.channel {
+            _ if self.six_ghz => "6",
+            0..=14 => "2.4",
+            _ => "5",
+        }
+    }
+
+    /// How the SSID of a hidden network was recovered, if it has been.
+    pub fn decloak_source(&self) -> Option<DecloakSource> {
+        self.decloaked_by.filter(|_| self.hidden && !self.ssid.is_empty())
     }
 
     pub fn display_ssid(&self) -> &str {
@@ -76,7 +443,91 @@ impl AccessPoint {
         ((clamped + 100.0) / 80.0 * 100.0) as u8
     }
 
-    pub fn seconds_since_seen(&self) -> u64 {
-        self.last_seen.elapsed().as_secs()
+    pub fn seconds_since_seen(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.last_seen).as_secs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacon(tsf_secs: u64) -> BeaconTiming {
+        BeaconTiming {
+            interval_tu: 100,
+            tsf: tsf_secs * 1_000_000,
+            local_tsft: None,
+        }
+    }
+
+    #[test]
+    fn tsf_reset_is_a_reboot() {
+        let t0 = Instant::now();
+        let mut ap = AccessPoint::new([2, 0, 0, 0, 0, 1], t0, SystemTime::now());
+        assert_eq!(ap.record_beacon(beacon(5000), t0, None), None);
+        assert_eq!(ap.record_beacon(beacon(5001), t0 + Duration::from_secs(1), None), None);
+        assert_eq!(
+            ap.record_beacon(beacon(3), t0 + Duration::from_secs(2), None),
+            Some(TsfEvent::Reset(5001 * 1_000_000))
+        );
+        assert_eq!(ap.record_beacon(beacon(4), t0 + Duration::from_secs(3), None), None);
+        assert_eq!(ap.reboots, 1);
+        assert!(!ap.has_tsf_conflict());
+    }
+
+    #[test]
+    fn alternating_tsfs_are_a_conflict() {
+        let t0 = Instant::now();
+        let mut ap = AccessPoint::new([2, 0, 0, 0, 0, 1], t0, SystemTime::now());
+        let events: Vec<_> = (0..6u64)
+            .map(|i| {
+                let tsf = if i % 2 == 0 { 5000 + i } else { 40 + i };
+                ap.record_beacon(beacon(tsf), t0 + Duration::from_secs(i), None)
+            })
+            .collect();
+        // The first backward jump looks like a reboot until the jump forward
+        assert_eq!(events[1], Some(TsfEvent::Reset(5000 * 1_000_000)));
+        assert!(events[2..].iter().all(|e| *e == Some(TsfEvent::Conflict)));
+        assert_eq!(ap.reboots, 1);
+        assert_eq!(ap.tsf_conflicts, 4);
+    }
+
+    #[test]
+    fn tsf_alerts_are_rate_limited() {
+        let t0 = Instant::now();
+        let mut ap = AccessPoint::new([2, 0, 0, 0, 0, 1], t0, SystemTime::now());
+        assert!(ap.tsf_alert_due(t0));
+        assert!(!ap.tsf_alert_due(t0 + Duration::from_secs(1)));
+        assert!(ap.tsf_alert_due(t0 + TSF_REALERT));
+    }
+
+    #[test]
+    fn impersonated_count_follows_beacons() {
+        let t0 = Instant::now();
+        let mut ap = AccessPoint::new([2, 0, 0, 0, 0, 1], t0, SystemTime::now());
+        ap.record_responded_ssid("corp");
+        assert_eq!(ap.impersonated_count, 1);
+        // Hidden APs may answer with their one real SSID
+        assert!(!ap.is_karma());
+
+        ap.record_beaconed_ssid("corp");
+        ap.record_responded_ssid("corp");
+        ap.record_responded_ssid("guest");
+        assert_eq!(ap.impersonated_count, 1);
+        assert!(ap.is_karma());
+        assert_eq!(ap.impersonated_ssids(), vec!["guest"]);
+    }
+
+    #[test]
+    fn karma_ssids_are_capped() {
+        let t0 = Instant::now();
+        let mut ap = AccessPoint::new([2, 0, 0, 0, 0, 1], t0, SystemTime::now());
+        for i in 0..1000 {
+            ap.record_responded_ssid(&format!("probe-{i}"));
+        }
+        assert_eq!(ap.responded_ssids.len(), MAX_AP_SSIDS);
+        assert_eq!(ap.impersonated_count, MAX_AP_SSIDS);
+        assert!(ap.karma_alert_due(t0));
+        assert!(!ap.karma_alert_due(t0 + KARMA_REALERT));
     }
 }
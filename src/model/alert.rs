@@ -0,0 +1,68 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Info,
+    Warning,
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::High => write!(f, "high"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "high" => Ok(Severity::High),
+            _ => Err(format!("invalid severity '{s}' (expected info, warning or high)")),
+        }
+    }
+}
+
+/// A noteworthy event raised by the capture or analysis code.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub severity: Severity,
+    pub message: String,
+    /// BSSID the alert relates to, if any.
+    pub bssid: Option<[u8; 6]>,
+    #[serde(with = "crate::util::serde_instant")]
+    pub raised_at: Instant,
+    /// Show over the TUI until acknowledged.
+    pub popup: bool,
+    /// Ring the terminal bell in the TUI.
+    pub bell: bool,
+}
+
+impl Alert {
+    pub fn new(
+        severity: Severity,
+        message: String,
+        bssid: Option<[u8; 6]>,
+        raised_at: Instant,
+    ) -> Self {
+        Self {
+            severity,
+            message,
+            bssid,
+            raised_at,
+            popup: false,
+            bell: false,
+        }
+    }
+}
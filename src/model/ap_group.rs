@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::model::{AccessPoint, EncryptionType};
+
+/// Virtual BSSs on one radio share its TSF, give or take processing delay.
+const SAME_RADIO_TSF_TOLERANCE: Duration = Duration::from_millis(100);
+
+/// Radios in one AP start their TSF timers at boot, within a few seconds.
+const SAME_DEVICE_TSF_TOLERANCE: Duration = Duration::from_secs(10);
+
+/// Low bits of the last MAC byte vendors vary between BSSIDs of one AP.
+const ADJACENT_MAC_MASK: u8 = 0x0f;
+
+/// Bit vendors often set in the first byte of virtual BSSIDs.
+const LOCALLY_ADMINISTERED: u8 = 0x02;
+
+/// How the AP table rolls up BSSIDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableGrouping {
+    /// One row per BSSID.
+    None,
+    /// One row per SSID and security profile.
+    Ess,
+    /// One row per physical AP.
+    Device,
+}
+
+impl TableGrouping {
+    pub fn next(self) -> Self {
+        match self {
+            TableGrouping::None => TableGrouping::Ess,
+            TableGrouping::Ess => TableGrouping::Device,
+            TableGrouping::Device => TableGrouping::None,
+        }
+    }
+}
+
+impl fmt::Display for TableGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableGrouping::None => write!(f, "BSSID"),
+            TableGrouping::Ess => write!(f, "ESS"),
+            TableGrouping::Device => write!(f, "AP"),
+        }
+    }
+}
+
+/// What makes BSSIDs part of the same extended service set: the SSID and
+/// the security profile.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EssKey {
+    pub ssid: String,
+    pub encryption: EncryptionType,
+    /// Set for hidden networks we haven't decloaked, which can't be matched
+    /// to anything and so stand alone.
+    pub bssid: Option<[u8; 6]>,
+}
+
+impl EssKey {
+    pub fn of(ap: &AccessPoint) -> Self {
+        Self {
+            ssid: ap.ssid.clone(),
+            encryption: ap.encryption,
+            bssid: ap.ssid.is_empty().then_some(ap.bssid),
+        }
+    }
+}
+
+/// Identifies a group of BSSIDs, e.g. to remember which are expanded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GroupKey {
+    Ess(EssKey),
+    /// A physical AP, named by its lowest BSSID.
+    Device([u8; 6]),
+}
+
+/// BSSIDs shown as one row, in table order.
+pub struct ApGroup<'a> {
+    pub key: GroupKey,
+    pub members: Vec<&'a AccessPoint>,
+}
+
+impl ApGroup<'_> {
+    pub fn label(&self) -> String {
+        match &self.key {
+            GroupKey::Ess(_) => self.members[0].display_ssid().to_string(),
+            GroupKey::Device(_) => {
+                let mut ssids: Vec<&str> = self.members.iter().map(|ap| ap.display_ssid()).collect();
+                ssids.sort_unstable();
+                ssids.dedup();
+                ssids.join(", ")
+            }
+        }
+    }
+
+    pub fn channels(&self) -> Vec<u8> {
+        let mut channels: Vec<u8> = self.members.iter().map(|ap| ap.channel).collect();
+        channels.sort_unstable();
+        channels.dedup();
+        channels
+    }
+
+    /// Radios in a physical AP, taking one per channel.
+    pub fn radio_count(&self) -> usize {
+        self.channels().len()
+    }
+
+    pub fn bands(&self) -> String {
+        let mut bands: Vec<&str> = self.members.iter().map(|ap| ap.band_label()).collect();
+        // "2.4" < "5" < "6" as strings too
+        bands.sort_unstable();
+        bands.dedup();
+        bands.join("+")
+    }
+
+    /// The members' security, or None if it differs between them.
+    pub fn encryption(&self) -> Option<EncryptionType> {
+        let first = self.members[0].encryption;
+        self.members.iter().all(|ap| ap.encryption == first).then_some(first)
+    }
+
+    pub fn strongest_dbm(&self) -> i8 {
+        self.members.iter().map(|ap| ap.signal_dbm).max().unwrap_or(-100)
+    }
+
+    pub fn weakest_dbm(&self) -> i8 {
+        self.members.iter().map(|ap| ap.signal_dbm).min().unwrap_or(-100)
+    }
+}
+
+/// One line of a grouped AP table.
+pub enum GroupRow<'a> {
+    Group(ApGroup<'a>),
+    /// A member BSSID of the group above, shown when it is expanded.
+    Member(&'a AccessPoint),
+}
+
+/// Group `aps` by ESS, ordered by each group's first AP.
+pub fn ess_groups<'a>(aps: &[&'a AccessPoint]) -> Vec<ApGroup<'a>> {
+    let mut groups: Vec<ApGroup> = Vec::new();
+    let mut index: HashMap<EssKey, usize> = HashMap::new();
+    for &ap in aps {
+        let key = EssKey::of(ap);
+        match index.get(&key) {
+            Some(&i) => groups[i].members.push(ap),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push(ApGroup {
+                    key: GroupKey::Ess(key),
+                    members: vec![ap],
+                });
+            }
+        }
+    }
+    groups
+}
+
+/// Group `aps` into physical devices, ordered by each group's first AP.
+pub fn device_groups<'a>(aps: &[&'a AccessPoint], now: Instant) -> Vec<ApGroup<'a>> {
+    // Union-find over indices into `aps`
+    let mut parent: Vec<usize> = (0..aps.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for i in 0..aps.len() {
+        for j in i + 1..aps.len() {
+            if same_device(aps[i], aps[j], now) {
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                parent[b.max(a)] = a.min(b);
+            }
+        }
+    }
+
+    let mut groups: Vec<ApGroup> = Vec::new();
+    let mut index: HashMap<usize, usize> = HashMap::new();
+    for (i, &ap) in aps.iter().enumerate() {
+        let r = root(&mut parent, i);
+        match index.get(&r) {
+            Some(&g) => groups[g].members.push(ap),
+            None => {
+                index.insert(r, groups.len());
+                groups.push(ApGroup {
+                    key: GroupKey::Device(ap.bssid),
+                    members: vec![ap],
+                });
+            }
+        }
+    }
+    for group in &mut groups {
+        let lowest = group.members.iter().map(|ap| ap.bssid).min().unwrap_or_default();
+        group.key = GroupKey::Device(lowest);
+    }
+    groups
+}
+
+/// Whether two BSSIDs are evidently served by the same physical AP.
+fn same_device(a: &AccessPoint, b: &AccessPoint, now: Instant) -> bool {
+    // Advertised by the AP itself
+    if in_multiple_bssid_set(a, &b.bssid) || in_multiple_bssid_set(b, &a.bssid) {
+        return true;
+    }
+    if a.colocated.contains(&b.bssid) || b.colocated.contains(&a.bssid) {
+        return true;
+    }
+
+    // Adjacent MACs only count when the TSF timers agree as well
+    if !adjacent_macs(&a.bssid, &b.bssid) {
+        return false;
+    }
+    let (Some(up_a), Some(up_b)) = (a.uptime(now), b.uptime(now)) else {
+        return false;
+    };
+    let tolerance = if a.channel == b.channel {
+        SAME_RADIO_TSF_TOLERANCE
+    } else {
+        SAME_DEVICE_TSF_TOLERANCE
+    };
+    up_a.abs_diff(up_b) <= tolerance
+}
+
+/// With a Multiple BSSID element, the BSSIDs of the set differ from the
+/// transmitting one only in the low `max_bssid_indicator` bits.
+fn in_multiple_bssid_set(transmitter: &AccessPoint, bssid: &[u8; 6]) -> bool {
+    let Some(bits) = transmitter.multi_bssid_indicator.filter(|&n| n > 0 && n < 48) else {
+        return false;
+    };
+    let mask = !0u64 << bits;
+    (mac_to_u64(&transmitter.bssid) & mask) == (mac_to_u64(bssid) & mask)
+}
+
+fn adjacent_macs(a: &[u8; 6], b: &[u8; 6]) -> bool {
+    a[0] & !LOCALLY_ADMINISTERED == b[0] & !LOCALLY_ADMINISTERED
+        && a[1..5] == b[1..5]
+        && a[5] & !ADJACENT_MAC_MASK == b[5] & !ADJACENT_MAC_MASK
+}
+
+fn mac_to_u64(mac: &[u8; 6]) -> u64 {
+    mac.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
+}
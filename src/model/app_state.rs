@@ -1,19 +1,82 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime};
 
-use crate::model::AccessPoint;
+use serde::{Deserialize, Serialize};
+
+use crate::model::link_stats::SequenceInfo;
+use crate::model::{
+    ap_group, AccessPoint, Alert, ApGroup, ChannelUsage, FrameKind, FrameStats, GpsFix, GroupKey, GroupRow,
+    HeatmapTarget, LinkStats, ProbedSsid, Severity, SsidProbes, Station, Survey, TableGrouping,
+};
+use crate::util::clock::Clock;
+use crate::util::mac::format_mac;
 use crate::util::ring_buffer::RingBuffer;
+use crate::util::time_series::{RollupTiers, TimedSample};
+
+/// Most SSIDs remembered per client and over the session.
+const MAX_PROBED_SSIDS_PER_STATION: usize = 64;
+const MAX_PROBED_SSIDS: usize = 2048;
 
 /// Packet rate history: 120 samples = 2 minutes at 1 sample/sec.
 const PACKET_RATE_HISTORY_CAP: usize = 120;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Most recent alerts kept for display and the API.
+const ALERT_HISTORY_CAP: usize = 256;
+
+/// A GPS fix older than this is not used to tag sightings.
+const GPS_FIX_MAX_AGE: Duration = Duration::from_secs(5);
+
+/// Weight of each new reading in the per-channel noise floor average.
+const NOISE_SMOOTHING: f32 = 0.05;
+
+/// Interval between packet rate samples.
+const RATE_TICK: Duration = Duration::from_secs(1);
+
+/// Seconds with less dwell than this on a channel are too short a sample
+/// to scale its frame count up to a rate.
+const MIN_RATE_DWELL_US: u64 = 50_000;
+
+/// Deauthentication plus disassociation frames per second that count as a flood.
+const DEAUTH_FLOOD_PER_SEC: u64 = 30;
+
+/// Minimum gap between deauthentication flood alerts.
+const DEAUTH_FLOOD_REALERT: Duration = Duration::from_secs(30);
+
+/// EAPOL frames between the same pair within this long are one handshake.
+const HANDSHAKE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Work for the capture thread's trigger ring and per-AP extractions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CaptureRequest {
+    /// Save the frames around an event, giving the reason.
+    Trigger(String),
+    /// Save what the trigger ring holds now.
+    ExportRing,
+    /// Start saving frames to or from this BSSID and its clients.
+    StartExtract([u8; 6]),
+    /// Close the BSSID's extraction file.
+    StopExtract([u8; 6]),
+}
+
+/// Counters reported by libpcap for the live capture handle.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct PcapStats {
+    pub received: u32,
+    pub dropped: u32,
+    pub if_dropped: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BandFilter {
     Both,
     Only2_4,
     Only5,
+    /// 6 GHz preferred scanning channels; not every radio can tune there.
+    Only6,
 }
 
 impl BandFilter {
@@ -21,7 +84,8 @@ impl BandFilter {
         match self {
             BandFilter::Both => BandFilter::Only2_4,
             BandFilter::Only2_4 => BandFilter::Only5,
-            BandFilter::Only5 => BandFilter::Both,
+            BandFilter::Only5 => BandFilter::Only6,
+            BandFilter::Only6 => BandFilter::Both,
         }
     }
 }
@@ -32,10 +96,36 @@ impl fmt::Display for BandFilter {
             BandFilter::Both => write!(f, "2.4+5 GHz"),
             BandFilter::Only2_4 => write!(f, "2.4 GHz"),
             BandFilter::Only5 => write!(f, "5 GHz"),
+            BandFilter::Only6 => write!(f, "6 GHz"),
+        }
+    }
+}
+
+impl FromStr for BandFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "both" | "all" => Ok(BandFilter::Both),
+            "2.4" | "2" => Ok(BandFilter::Only2_4),
+            "5" => Ok(BandFilter::Only5),
+            "6" => Ok(BandFilter::Only6),
+            _ => Err(format!("unknown band '{s}' (expected 2.4, 5, 6 or both)")),
         }
     }
 }
 
+/// What the channel hopper should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChannelLock {
+    /// Cycle through the channels of the selected band.
+    Hopping,
+    /// Stay on a fixed channel.
+    Channel(u8),
+    /// Follow whatever channel this BSSID is on.
+    Bssid([u8; 6]),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TimeWindow {
     Sec5,
@@ -43,6 +133,11 @@ pub enum TimeWindow {
     Sec15,
     Sec30,
     Sec60,
+    Min5,
+    Min15,
+    Hour1,
+    Hour6,
+    Hour24,
 }
 
 impl TimeWindow {
@@ -52,19 +147,17 @@ impl TimeWindow {
             TimeWindow::Sec10 => TimeWindow::Sec15,
             TimeWindow::Sec15 => TimeWindow::Sec30,
             TimeWindow::Sec30 => TimeWindow::Sec60,
-            TimeWindow::Sec60 => TimeWindow::Sec5,
+            TimeWindow::Sec60 => TimeWindow::Min5,
+            TimeWindow::Min5 => TimeWindow::Min15,
+            TimeWindow::Min15 => TimeWindow::Hour1,
+            TimeWindow::Hour1 => TimeWindow::Hour6,
+            TimeWindow::Hour6 => TimeWindow::Hour24,
+            TimeWindow::Hour24 => TimeWindow::Sec5,
         }
     }
 
-    /// Number of samples this window covers (at 4 samples/sec).
-    pub fn sample_count(self) -> usize {
-        match self {
-            TimeWindow::Sec5 => 20,
-            TimeWindow::Sec10 => 40,
-            TimeWindow::Sec15 => 60,
-            TimeWindow::Sec30 => 120,
-            TimeWindow::Sec60 => 240,
-        }
+    pub fn duration(self) -> Duration {
+        Duration::from_secs(self.seconds())
     }
 
     pub fn seconds(self) -> u64 {
@@ -74,13 +167,54 @@ impl TimeWindow {
             TimeWindow::Sec15 => 15,
             TimeWindow::Sec30 => 30,
             TimeWindow::Sec60 => 60,
+            TimeWindow::Min5 => 300,
+            TimeWindow::Min15 => 900,
+            TimeWindow::Hour1 => 3600,
+            TimeWindow::Hour6 => 6 * 3600,
+            TimeWindow::Hour24 => 24 * 3600,
         }
     }
 }
 
 impl fmt::Display for TimeWindow {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}s", self.seconds())
+        write!(f, "{}", format_span(self.seconds()))
+    }
+}
+
+/// Short human label for a span of seconds: `45s`, `15m`, `6h`.
+pub fn format_span(secs: u64) -> String {
+    if secs < 120 {
+        format!("{secs}s")
+    } else if secs < 7200 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h", secs / 3600)
+    }
+}
+
+/// Which per-bucket statistic the signal graph plots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphStat {
+    Mean,
+    Max,
+}
+
+impl GraphStat {
+    pub fn next(self) -> Self {
+        match self {
+            GraphStat::Mean => GraphStat::Max,
+            GraphStat::Max => GraphStat::Mean,
+        }
+    }
+}
+
+impl fmt::Display for GraphStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphStat::Mean => write!(f, "mean"),
+            GraphStat::Max => write!(f, "max"),
+        }
     }
 }
 
@@ -88,51 +222,196 @@ impl fmt::Display for TimeWindow {
 pub struct AppState {
     /// Map of BSSID → AccessPoint.
     pub access_points: HashMap<[u8; 6], AccessPoint>,
+    /// Map of client MAC → Station.
+    pub stations: HashMap<[u8; 6], Station>,
+    /// Recently raised alerts (oldest first).
+    pub alerts: RingBuffer<Alert>,
     /// Total packets captured.
     pub total_packets: u64,
     /// Packets captured in the current second (for rate calculation).
     pub packets_this_second: u64,
     /// Packet rate history (packets/sec).
-    pub packet_rate_history: RingBuffer<u64>,
+    pub packet_rate_history: RingBuffer<TimedSample<u64>>,
+    /// Long-term packet rate history.
+    pub packet_rate_rollups: RollupTiers,
     /// Packets per channel for utilization chart.
     pub channel_packets: HashMap<u8, u64>,
+    /// Packets per channel in the current second.
+    pub channel_packets_this_second: HashMap<u8, u64>,
+    /// Per-second packet counts for each channel.
+    pub channel_history: HashMap<u8, RingBuffer<TimedSample<u64>>>,
+    /// Long-term per-channel activity.
+    pub channel_rollups: HashMap<u8, RollupTiers>,
+    /// Signal rollups of expired APs, restored if they are heard again.
+    pub retired_signal_rollups: HashMap<[u8; 6], RollupTiers>,
+    /// Airtime and listening time per channel.
+    pub channel_usage: HashMap<u8, ChannelUsage>,
+    /// The same for 6 GHz channels, whose numbers overlap 2.4 GHz.
+    pub channel_usage_6g: HashMap<u8, ChannelUsage>,
+    /// Frames per channel that failed their FCS check.
+    pub channel_corrupt: HashMap<u8, u64>,
+    /// Retry counts per channel, over every transmitter heard on it.
+    pub channel_link: HashMap<u8, LinkStats>,
+    /// Smoothed noise floor per channel (dBm), where the driver reports noise.
+    pub channel_noise: HashMap<u8, f32>,
+    /// Frame counts by type and subtype.
+    pub frame_stats: FrameStats,
+    /// Directed probe requests per SSID, over the whole session.
+    pub probed_ssids: HashMap<String, SsidProbes>,
+    /// Alerts at or above this severity trigger a ring capture; None when
+    /// no trigger ring is running.
+    pub capture_trigger: Option<Severity>,
+    /// Requests for the capture thread, drained on each loop.
+    pub capture_requests: Vec<CaptureRequest>,
+    /// Where trigger captures, ring exports and extractions are written.
+    pub capture_dir: PathBuf,
+    /// BSSIDs whose frames are being saved, with their files.
+    pub extracting: HashMap<[u8; 6], PathBuf>,
+    /// When the last deauthentication flood alert was raised.
+    pub deauth_flood_alerted_at: Option<Instant>,
+    /// Last EAPOL frame per (station, BSSID), to report each handshake once.
+    pub handshakes: HashMap<([u8; 6], [u8; 6]), Instant>,
+    /// SSIDs of the networks under assessment; clients probing for them
+    /// away from their APs are flagged.
+    pub corporate_ssids: HashSet<String>,
     /// Current channel being monitored.
     pub current_channel: u8,
+    /// `current_channel` is a 6 GHz channel number.
+    pub current_six_ghz: bool,
+    /// When we tuned to `current_channel`, for dwell accounting.
+    pub tuned_since: Instant,
+    /// When we last moved to a different channel; before this we may have
+    /// missed frames on the current one.
+    pub listening_since: Instant,
+    /// No hopper is running (replay): take the tuned channel from each
+    /// frame's radiotap header instead.
+    pub channel_from_capture: bool,
     /// Interface name.
     pub interface: String,
     /// Signal that the app should stop.
     pub running: AtomicBool,
+    /// Source of "now" for ages, rates and expiry.
+    pub clock: Clock,
+    /// When the session began; for a replay, the first packet's time.
+    pub started_at: Instant,
     /// Last time the per-second counters were reset.
     pub last_rate_tick: Instant,
     /// Scroll offset for AP table.
     pub table_scroll: usize,
     /// AP expiry timeout (seconds).
     pub ap_expiry_secs: u64,
+    /// Drop counters from libpcap, refreshed by the capture thread.
+    pub pcap_stats: PcapStats,
     /// Error message from capture thread (visible after TUI teardown).
     pub error: Option<String>,
     /// Which band(s) the channel hopper should scan.
     pub band_filter: BandFilter,
     /// Time window for the signal graph.
     pub time_window: TimeWindow,
+    /// Statistic plotted per time bucket in the signal graph.
+    pub graph_stat: GraphStat,
+    /// Time for the hopper to return to a channel (one dwell when locked).
+    pub hop_cycle: Duration,
+    /// Whether the hopper cycles channels or stays put.
+    pub channel_lock: ChannelLock,
+    /// BSSIDs pinned to the top of the table and signal graph.
+    pub pinned: HashSet<[u8; 6]>,
+    /// Whether a gpsd connection was requested.
+    pub gps_enabled: bool,
+    /// Latest fix from gpsd, if any.
+    pub gps_fix: Option<GpsFix>,
+    /// Indoor site survey, when started with a floor plan.
+    pub survey: Option<Survey>,
+    /// Show the survey heatmap instead of the signal graph.
+    pub show_survey: bool,
+    /// Survey grid cell under the cursor.
+    pub survey_cursor: (usize, usize),
+    /// Draw the heatmap for the selected AP's whole SSID rather than its BSSID.
+    pub heatmap_by_ssid: bool,
+    /// Show the frame type breakdown instead of the channel chart.
+    pub show_frame_stats: bool,
+    /// Show the selected AP's details instead of the signal graph.
+    pub show_detail: bool,
+    /// Show channel recommendations instead of the channel chart.
+    pub show_recommend: bool,
+    /// Show APs across their channel widths instead of the signal graph.
+    pub show_spectrum: bool,
+    /// Show probed SSIDs and client preferred-network lists instead of the signal graph.
+    pub show_probes: bool,
+    /// How the AP table rolls up BSSIDs.
+    pub grouping: TableGrouping,
+    /// Group rows expanded to list their BSSIDs.
+    pub expanded_groups: HashSet<GroupKey>,
+    /// Popup alerts raised before this have been dismissed.
+    pub popup_acknowledged_at: Instant,
+    /// Bell alerts raised before this have rung.
+    pub bell_rung_at: Instant,
 }
 
 impl AppState {
-    pub fn new(interface: String) -> Self {
+    pub fn new(interface: String, clock: Clock) -> Self {
         Self {
             access_points: HashMap::new(),
+            stations: HashMap::new(),
+            alerts: RingBuffer::new(ALERT_HISTORY_CAP),
             total_packets: 0,
             packets_this_second: 0,
             packet_rate_history: RingBuffer::new(PACKET_RATE_HISTORY_CAP),
+            packet_rate_rollups: RollupTiers::default(),
             channel_packets: HashMap::new(),
+            channel_packets_this_second: HashMap::new(),
+            channel_history: HashMap::new(),
+            channel_rollups: HashMap::new(),
+            retired_signal_rollups: HashMap::new(),
+            channel_usage: HashMap::new(),
+            channel_usage_6g: HashMap::new(),
+            channel_noise: HashMap::new(),
+            channel_corrupt: HashMap::new(),
+            channel_link: HashMap::new(),
+            frame_stats: FrameStats::new(PACKET_RATE_HISTORY_CAP),
+            probed_ssids: HashMap::new(),
+            capture_trigger: None,
+            capture_requests: Vec::new(),
+            capture_dir: PathBuf::from("."),
+            extracting: HashMap::new(),
+            deauth_flood_alerted_at: None,
+            handshakes: HashMap::new(),
+            corporate_ssids: HashSet::new(),
             current_channel: 1,
+            current_six_ghz: false,
+            tuned_since: clock.now(),
+            listening_since: clock.now(),
+            channel_from_capture: false,
             interface,
             running: AtomicBool::new(true),
-            last_rate_tick: Instant::now(),
+            clock,
+            started_at: clock.now(),
+            last_rate_tick: clock.now(),
             table_scroll: 0,
             ap_expiry_secs: 120,
+            pcap_stats: PcapStats::default(),
             error: None,
             band_filter: BandFilter::Both,
             time_window: TimeWindow::Sec60,
+            graph_stat: GraphStat::Mean,
+            hop_cycle: Duration::ZERO,
+            channel_lock: ChannelLock::Hopping,
+            pinned: HashSet::new(),
+            gps_enabled: false,
+            gps_fix: None,
+            survey: None,
+            show_survey: false,
+            survey_cursor: (0, 0),
+            heatmap_by_ssid: false,
+            show_frame_stats: false,
+            show_detail: false,
+            show_recommend: false,
+            show_spectrum: false,
+            show_probes: false,
+            grouping: TableGrouping::None,
+            expanded_groups: HashSet::new(),
+            popup_acknowledged_at: clock.now(),
+            bell_rung_at: clock.now(),
         }
     }
 
@@ -144,23 +423,439 @@ impl AppState {
         self.running.store(false, Ordering::Relaxed);
     }
 
-    /// Tick the per-second packet rate counter. Call this once per second.
-    pub fn tick_rate(&mut self) {
-        self.packet_rate_history.push(self.packets_this_second);
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Run the once-per-second housekeeping if a second has passed since the last tick.
+    ///
+    /// A replayed capture can jump ahead by more than a second between packets,
+    /// so idle seconds in between are recorded as such.
+    pub fn tick_if_due(&mut self) {
+        let now = self.clock.now();
+        let behind = now.saturating_duration_since(self.last_rate_tick);
+        if behind < RATE_TICK {
+            return;
+        }
+        // Nothing older than the raw history is worth filling in tick by tick
+        let max_behind = RATE_TICK * PACKET_RATE_HISTORY_CAP as u32;
+        if behind > max_behind {
+            self.last_rate_tick = now - max_behind;
+        }
+        while now.saturating_duration_since(self.last_rate_tick) >= RATE_TICK {
+            self.tick_rate(self.last_rate_tick + RATE_TICK);
+        }
+        self.expire_aps();
+    }
+
+    /// Close the per-second packet rate counters for the second ending at `now`.
+    pub fn tick_rate(&mut self, now: Instant) {
+        self.packet_rate_history.push(TimedSample {
+            at: now,
+            value: self.packets_this_second,
+        });
+        self.packet_rate_rollups.add(now, self.packets_this_second as f32);
         self.packets_this_second = 0;
-        self.last_rate_tick = Instant::now();
+
+        // Record every channel heard so far, including idle ones, so means stay honest
+        for &ch in self.channel_packets.keys() {
+            let count = self.channel_packets_this_second.get(&ch).copied().unwrap_or(0);
+            self.channel_history
+                .entry(ch)
+                .or_insert_with(|| RingBuffer::new(PACKET_RATE_HISTORY_CAP))
+                .push(TimedSample { at: now, value: count });
+            self.channel_rollups.entry(ch).or_default().add(now, count as f32);
+        }
+        self.channel_packets_this_second.clear();
+        self.frame_stats.tick(now);
+        self.check_deauth_flood(now);
+
+        self.close_dwell(now);
+        for usage in self.channel_usage.values_mut().chain(self.channel_usage_6g.values_mut()) {
+            usage.tick(now);
+        }
+
+        self.last_rate_tick = now;
+    }
+
+    /// Record a retune, crediting the time spent on the previous channel.
+    pub fn set_current_channel(&mut self, channel: u8, six_ghz: bool) {
+        let now = self.clock.now();
+        self.close_dwell(now);
+        if (channel, six_ghz) != (self.current_channel, self.current_six_ghz) {
+            self.listening_since = now;
+        }
+        self.current_channel = channel;
+        self.current_six_ghz = six_ghz;
+    }
+
+    /// Count a frame's retry flag against its channel and check its sequence
+    /// number against the AP or station that sent it.
+    pub fn record_sequence(&mut self, channel: Option<u8>, info: &SequenceInfo, now: Instant) {
+        if let Some(ch) = channel {
+            self.channel_link.entry(ch).or_default().count(info.retry);
+        }
+        if let Some(ap) = self.access_points.get_mut(&info.transmitter) {
+            ap.link.record(info, now);
+        } else if let Some(sta) = self.stations.get_mut(&info.transmitter) {
+            sta.link.record(info, now);
+        }
+    }
+
+    /// Add a directed probe to the client's preferred-network list and the
+    /// session totals.
+    ///
+    /// A corporate SSID probed while none of its APs are heard means the
+    /// client is advertising it outside the building; that raises an alert
+    /// once per client and SSID.
+    ///
+    /// Probe requests are trivially forged, so both lists are capped and
+    /// drop the least recently probed SSID, keeping exposed and corporate
+    /// ones for as long as possible. Each SSID's client sets are capped too.
+    pub fn record_probe(&mut self, mac: [u8; 6], ssid: String, now: Instant) {
+        let corporate = self.corporate_ssids.contains(&ssid);
+        let exposed = corporate && !self.access_points.values().any(|ap| ap.ssid == ssid);
+        let Some(sta) = self.stations.get_mut(&mac) else {
+            return;
+        };
+        match sta.probed_ssids.iter_mut().find(|probe| probe.ssid == ssid) {
+            Some(probe) => {
+                probe.probes += 1;
+                probe.last_seen = now;
+                probe.exposed |= exposed;
+            }
+            None => {
+                if sta.probed_ssids.len() >= MAX_PROBED_SSIDS_PER_STATION {
+                    let oldest = sta
+                        .probed_ssids
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, probe)| (probe.exposed, probe.last_seen))
+                        .map(|(i, _)| i);
+                    if let Some(i) = oldest {
+                        sta.probed_ssids.swap_remove(i);
+                    }
+                }
+                sta.probed_ssids.push(ProbedSsid {
+                    ssid: ssid.clone(),
+                    probes: 1,
+                    last_seen: now,
+                    exposed,
+                });
+            }
+        }
+
+        if !self.probed_ssids.contains_key(&ssid) && self.probed_ssids.len() >= MAX_PROBED_SSIDS {
+            let oldest = self
+                .probed_ssids
+                .values()
+                .min_by_key(|tally| (tally.corporate, tally.last_seen))
+                .map(|tally| tally.ssid.clone());
+            if let Some(oldest) = oldest {
+                self.probed_ssids.remove(&oldest);
+            }
+        }
+        let tally = self
+            .probed_ssids
+            .entry(ssid.clone())
+            .or_insert_with(|| SsidProbes::new(ssid.clone(), corporate, now));
+        tally.probes += 1;
+        tally.last_seen = now;
+        tally.add_client(mac);
+        if exposed && tally.add_exposed_client(mac) {
+            let message = format!(
+                "{} probed for corporate SSID \"{ssid}\" with no corporate AP in range",
+                format_mac(&mac)
+            );
+            self.raise_alert(Severity::Warning, message, None);
+        }
     }
 
-    /// Remove APs not seen for longer than `ap_expiry_secs`.
+    /// Probed SSIDs, most widely probed first.
+    pub fn most_probed(&self) -> Vec<&SsidProbes> {
+        let mut probed: Vec<&SsidProbes> = self.probed_ssids.values().collect();
+        probed.sort_by(|a, b| {
+            b.clients
+                .len()
+                .cmp(&a.clients.len())
+                .then(b.probes.cmp(&a.probes))
+                .then(a.ssid.cmp(&b.ssid))
+        });
+        probed
+    }
+
+    /// Count airtime heard on `channel`.
+    pub fn record_airtime(&mut self, channel: u8, six_ghz: bool, airtime: Duration) {
+        self.usage_mut(channel, six_ghz).add_airtime(airtime);
+    }
+
+    /// Airtime and dwell on `channel`, 6 GHz or not.
+    pub fn usage(&self, channel: u8, six_ghz: bool) -> Option<&ChannelUsage> {
+        if six_ghz {
+            self.channel_usage_6g.get(&channel)
+        } else {
+            self.channel_usage.get(&channel)
+        }
+    }
+
+    fn usage_mut(&mut self, channel: u8, six_ghz: bool) -> &mut ChannelUsage {
+        let usage = if six_ghz {
+            &mut self.channel_usage_6g
+        } else {
+            &mut self.channel_usage
+        };
+        usage
+            .entry(channel)
+            .or_insert_with(|| ChannelUsage::new(PACKET_RATE_HISTORY_CAP))
+    }
+
+    /// Fold a noise reading into the channel's noise floor.
+    pub fn record_noise(&mut self, channel: u8, noise_dbm: i8) {
+        let floor = self.channel_noise.entry(channel).or_insert(noise_dbm as f32);
+        *floor += (noise_dbm as f32 - *floor) * NOISE_SMOOTHING;
+    }
+
+    /// Credit the current channel with the time listened since the last call.
+    fn close_dwell(&mut self, now: Instant) {
+        let dwell = now.saturating_duration_since(self.tuned_since);
+        self.tuned_since = self.tuned_since.max(now);
+        if self.current_channel > 0 {
+            self.usage_mut(self.current_channel, self.current_six_ghz).add_dwell(dwell);
+        }
+    }
+
+    /// Packet rate for the last completed second.
+    pub fn current_packet_rate(&self) -> u64 {
+        self.packet_rate_history.latest().map(|s| s.value).unwrap_or(0)
+    }
+
+    /// Frames per second on `channel` while we listen to it: the newest
+    /// second we spent long enough there, scaled up by the share of it we
+    /// dwelt. While hopping, most seconds have no dwell on a given channel.
+    /// None once we haven't been back for a whole hop cycle.
+    pub fn channel_rate(&self, channel: u8) -> Option<f64> {
+        let dwell = self
+            .channel_usage
+            .get(&channel)?
+            .dwell_history
+            .iter()
+            .rev()
+            .find(|sample| sample.value >= MIN_RATE_DWELL_US)?;
+        if self.now().saturating_duration_since(dwell.at) > self.hop_cycle + RATE_TICK {
+            return None;
+        }
+        let frames = self
+            .channel_history
+            .get(&channel)
+            .and_then(|history| history.iter().rev().find(|sample| sample.at == dwell.at))
+            .map_or(0, |sample| sample.value);
+        Some(frames as f64 * RATE_TICK.as_micros() as f64 / dwell.value as f64)
+    }
+
+    /// Remove APs and stations not seen for longer than `ap_expiry_secs`.
+    ///
+    /// An expired AP's long-term signal history is kept for as long as its
+    /// coarsest tier reaches, in case it comes back.
     pub fn expire_aps(&mut self) {
-        self.access_points
-            .retain(|_, ap| ap.seconds_since_seen() < self.ap_expiry_secs);
+        let now = self.clock.now();
+        let expiry = self.ap_expiry_secs;
+        let expired: Vec<[u8; 6]> = self
+            .access_points
+            .values()
+            .filter(|ap| ap.seconds_since_seen(now) >= expiry)
+            .map(|ap| ap.bssid)
+            .collect();
+        for bssid in expired {
+            if let Some(ap) = self.access_points.remove(&bssid) {
+                self.retired_signal_rollups.insert(bssid, ap.signal_rollups);
+            }
+        }
+        self.retired_signal_rollups.retain(|_, tiers| {
+            tiers
+                .last_at()
+                .is_some_and(|at| now.saturating_duration_since(at) < tiers.coarse.span())
+        });
+        self.stations
+            .retain(|_, sta| sta.seconds_since_seen(now) < self.ap_expiry_secs);
+    }
+
+    /// A newly heard AP, with the signal history it had if it expired earlier.
+    pub fn new_access_point(&mut self, bssid: [u8; 6], now: Instant, first_seen: SystemTime) -> AccessPoint {
+        let mut ap = AccessPoint::new(bssid, now, first_seen);
+        if let Some(tiers) = self.retired_signal_rollups.remove(&bssid) {
+            ap.signal_rollups = tiers;
+        }
+        ap
+    }
+
+    /// The GPS fix to tag new sightings with, if it is recent enough.
+    pub fn current_fix(&self) -> Option<&GpsFix> {
+        self.gps_fix
+            .as_ref()
+            .filter(|fix| self.clock.now().saturating_duration_since(fix.received_at) < GPS_FIX_MAX_AGE)
+    }
+
+    /// What the survey heatmap shows, based on the selected AP.
+    pub fn heatmap_target(&self) -> Option<HeatmapTarget> {
+        let ap = self.selected_ap()?;
+        if self.heatmap_by_ssid && !ap.ssid.is_empty() {
+            Some(HeatmapTarget::Ssid(ap.ssid.clone()))
+        } else {
+            Some(HeatmapTarget::Bssid(ap.bssid))
+        }
+    }
+
+    /// Number of stations currently associated with `bssid`.
+    pub fn client_count(&self, bssid: &[u8; 6]) -> usize {
+        self.stations
+            .values()
+            .filter(|sta| sta.bssid.as_ref() == Some(bssid))
+            .count()
+    }
+
+    /// Record an alert in the alert history.
+    pub fn raise_alert(&mut self, severity: Severity, message: String, bssid: Option<[u8; 6]>) {
+        let now = self.clock.now();
+        self.push_alert(Alert::new(severity, message, bssid, now));
+    }
+
+    /// Record an alert, triggering a ring capture if it is severe enough.
+    pub fn push_alert(&mut self, alert: Alert) {
+        if self.capture_trigger.is_some_and(|min| alert.severity >= min) {
+            self.capture_requests.push(CaptureRequest::Trigger(alert.message.clone()));
+        }
+        self.alerts.push(alert);
+    }
+
+    /// Ask the trigger ring to save the frames around an event. False when
+    /// no ring is running.
+    pub fn request_capture(&mut self, reason: String) -> bool {
+        if self.capture_trigger.is_none() {
+            return false;
+        }
+        self.capture_requests.push(CaptureRequest::Trigger(reason));
+        true
     }
 
-    /// Get APs sorted by signal strength (strongest first).
+    /// Note an EAPOL key frame. Returns true for the first frame of a
+    /// handshake between the pair.
+    pub fn record_handshake(&mut self, station: [u8; 6], bssid: [u8; 6], now: Instant) -> bool {
+        self.handshakes
+            .retain(|_, &mut at| now.saturating_duration_since(at) < HANDSHAKE_WINDOW);
+        self.handshakes.insert((station, bssid), now).is_none()
+    }
+
+    /// Raise an alert when the last second held a burst of deauthentication
+    /// or disassociation frames.
+    fn check_deauth_flood(&mut self, now: Instant) {
+        let last = &self.frame_stats.last_second;
+        let count = last[FrameKind::Deauthentication.index()] + last[FrameKind::Disassociation.index()];
+        let quiet = self
+            .deauth_flood_alerted_at
+            .is_none_or(|at| now.saturating_duration_since(at) >= DEAUTH_FLOOD_REALERT);
+        if count >= DEAUTH_FLOOD_PER_SEC && quiet {
+            self.deauth_flood_alerted_at = Some(now);
+            let message = format!(
+                "Deauthentication flood: {count} deauth/disassoc frames in one second on channel {}",
+                self.current_channel
+            );
+            self.raise_alert(Severity::High, message, None);
+        }
+    }
+
+    /// The newest popup alert raised since the last one was acknowledged.
+    pub fn pending_popup(&self) -> Option<&Alert> {
+        self.alerts
+            .iter()
+            .filter(|alert| alert.popup && alert.raised_at > self.popup_acknowledged_at)
+            .last()
+    }
+
+    /// Get APs sorted by signal strength (strongest first), pinned APs on top.
     pub fn sorted_aps(&self) -> Vec<&AccessPoint> {
         let mut aps: Vec<&AccessPoint> = self.access_points.values().collect();
-        aps.sort_by(|a, b| b.signal_dbm.cmp(&a.signal_dbm));
+        aps.sort_by(|a, b| {
+            self.is_pinned(&b.bssid)
+                .cmp(&self.is_pinned(&a.bssid))
+                .then(b.signal_dbm.cmp(&a.signal_dbm))
+        });
         aps
     }
+
+    /// The AP under the table cursor.
+    pub fn selected_ap(&self) -> Option<&AccessPoint> {
+        if self.grouping == TableGrouping::None {
+            return self.sorted_aps().get(self.table_scroll).copied();
+        }
+        // A group row stands for its strongest BSSID
+        match self.group_rows().into_iter().nth(self.table_scroll)? {
+            GroupRow::Group(group) => group.members.into_iter().max_by_key(|ap| ap.signal_dbm),
+            GroupRow::Member(ap) => Some(ap),
+        }
+    }
+
+    /// APs rolled up per the current grouping, in `sorted_aps` order.
+    pub fn ap_groups(&self) -> Vec<ApGroup<'_>> {
+        let aps = self.sorted_aps();
+        match self.grouping {
+            TableGrouping::None => Vec::new(),
+            TableGrouping::Ess => ap_group::ess_groups(&aps),
+            TableGrouping::Device => ap_group::device_groups(&aps, self.now()),
+        }
+    }
+
+    /// Rows of the grouped AP table, with expanded groups followed by their members.
+    pub fn group_rows(&self) -> Vec<GroupRow<'_>> {
+        let mut rows = Vec::new();
+        for group in self.ap_groups() {
+            let members = if self.expanded_groups.contains(&group.key) {
+                group.members.clone()
+            } else {
+                Vec::new()
+            };
+            rows.push(GroupRow::Group(group));
+            rows.extend(members.into_iter().map(GroupRow::Member));
+        }
+        rows
+    }
+
+    /// The group under the table cursor, or that of the BSSID under it.
+    pub fn selected_group(&self) -> Option<GroupKey> {
+        let mut current = None;
+        for (i, row) in self.group_rows().into_iter().enumerate() {
+            if let GroupRow::Group(group) = row {
+                current = Some(group.key);
+            }
+            if i == self.table_scroll {
+                return current;
+            }
+        }
+        None
+    }
+
+    /// Number of rows in the AP table as currently shown.
+    pub fn table_len(&self) -> usize {
+        match self.grouping {
+            TableGrouping::None => self.access_points.len(),
+            _ => self.group_rows().len(),
+        }
+    }
+
+    pub fn is_pinned(&self, bssid: &[u8; 6]) -> bool {
+        self.pinned.contains(bssid)
+    }
+
+    /// Channel the hopper should stay on, and whether it is a 6 GHz one, or
+    /// None to keep hopping.
+    pub fn locked_channel(&self) -> Option<(u8, bool)> {
+        match self.channel_lock {
+            ChannelLock::Hopping => None,
+            ChannelLock::Channel(ch) => Some((ch, false)),
+            ChannelLock::Bssid(bssid) => self
+                .access_points
+                .get(&bssid)
+                .map(|ap| (ap.channel, ap.six_ghz))
+                .filter(|&(ch, _)| ch > 0),
+        }
+    }
 }
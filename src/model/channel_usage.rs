@@ -0,0 +1,74 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::ring_buffer::RingBuffer;
+use crate::util::time_series::{self, RollupTiers, TimedSample};
+
+/// Airtime heard on one channel against the time we spent listening to it.
+///
+/// Both are kept as per-second microsecond totals so utilization over any
+/// window is the ratio of the two sums.
+#[derive(Serialize, Deserialize)]
+pub struct ChannelUsage {
+    #[serde(skip)]
+    airtime_this_second: Duration,
+    #[serde(skip)]
+    dwell_this_second: Duration,
+    pub airtime_history: RingBuffer<TimedSample<u64>>,
+    pub dwell_history: RingBuffer<TimedSample<u64>>,
+    #[serde(skip)]
+    pub airtime_rollups: RollupTiers,
+    #[serde(skip)]
+    pub dwell_rollups: RollupTiers,
+}
+
+impl ChannelUsage {
+    pub fn new(history_cap: usize) -> Self {
+        Self {
+            airtime_this_second: Duration::ZERO,
+            dwell_this_second: Duration::ZERO,
+            airtime_history: RingBuffer::new(history_cap),
+            dwell_history: RingBuffer::new(history_cap),
+            airtime_rollups: RollupTiers::default(),
+            dwell_rollups: RollupTiers::default(),
+        }
+    }
+
+    pub fn add_airtime(&mut self, airtime: Duration) {
+        self.airtime_this_second += airtime;
+    }
+
+    pub fn add_dwell(&mut self, dwell: Duration) {
+        self.dwell_this_second += dwell;
+    }
+
+    /// Close the per-second totals for the second ending at `now`.
+    pub fn tick(&mut self, now: Instant) {
+        let airtime = self.airtime_this_second.as_micros() as u64;
+        let dwell = self.dwell_this_second.as_micros() as u64;
+        self.airtime_history.push(TimedSample { at: now, value: airtime });
+        self.dwell_history.push(TimedSample { at: now, value: dwell });
+        self.airtime_rollups.add(now, airtime as f32);
+        self.dwell_rollups.add(now, dwell as f32);
+        self.airtime_this_second = Duration::ZERO;
+        self.dwell_this_second = Duration::ZERO;
+    }
+
+    /// Percentage of listening time the channel was busy over the `window`
+    /// ending at `now`, or None if we never listened to it in that time.
+    pub fn utilization_percent(&self, now: Instant, window: Duration) -> Option<f64> {
+        let sum = |raw: &RingBuffer<TimedSample<u64>>, rollups: &RollupTiers| {
+            time_series::resample_history(raw, rollups, now, window, 1)[0]
+                .map(|b| b.sum)
+                .unwrap_or(0.0)
+        };
+        let dwell = sum(&self.dwell_history, &self.dwell_rollups);
+        if dwell <= 0.0 {
+            return None;
+        }
+        let airtime = sum(&self.airtime_history, &self.airtime_rollups);
+        // Frames still on air as we tuned in can push this past 100%
+        Some((airtime / dwell * 100.0).min(100.0))
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::ring_buffer::RingBuffer;
+use crate::util::time_series::{RollupTiers, TimedSample};
+
+pub const FRAME_KIND_COUNT: usize = FrameKind::ALL.len();
+pub const FRAME_CATEGORY_COUNT: usize = FrameCategory::ALL.len();
+
+/// 802.11 frame type, from bits 2-3 of the frame control field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameCategory {
+    Management,
+    Control,
+    Data,
+    Extension,
+}
+
+impl FrameCategory {
+    pub const ALL: [FrameCategory; 4] = [
+        FrameCategory::Management,
+        FrameCategory::Control,
+        FrameCategory::Data,
+        FrameCategory::Extension,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for FrameCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameCategory::Management => write!(f, "mgmt"),
+            FrameCategory::Control => write!(f, "ctrl"),
+            FrameCategory::Data => write!(f, "data"),
+            FrameCategory::Extension => write!(f, "ext"),
+        }
+    }
+}
+
+/// Frame subtypes we count separately; the rest fall into an "other" bucket per type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    Beacon,
+    ProbeRequest,
+    ProbeResponse,
+    Authentication,
+    Deauthentication,
+    AssociationRequest,
+    AssociationResponse,
+    ReassociationRequest,
+    ReassociationResponse,
+    Disassociation,
+    Action,
+    OtherManagement,
+    Rts,
+    Cts,
+    Ack,
+    BlockAck,
+    BlockAckRequest,
+    PsPoll,
+    OtherControl,
+    Data,
+    QosData,
+    Null,
+    QosNull,
+    OtherData,
+    Extension,
+}
+
+impl FrameKind {
+    pub const ALL: [FrameKind; 25] = [
+        FrameKind::Beacon,
+        FrameKind::ProbeRequest,
+        FrameKind::ProbeResponse,
+        FrameKind::Authentication,
+        FrameKind::Deauthentication,
+        FrameKind::AssociationRequest,
+        FrameKind::AssociationResponse,
+        FrameKind::ReassociationRequest,
+        FrameKind::ReassociationResponse,
+        FrameKind::Disassociation,
+        FrameKind::Action,
+        FrameKind::OtherManagement,
+        FrameKind::Rts,
+        FrameKind::Cts,
+        FrameKind::Ack,
+        FrameKind::BlockAck,
+        FrameKind::BlockAckRequest,
+        FrameKind::PsPoll,
+        FrameKind::OtherControl,
+        FrameKind::Data,
+        FrameKind::QosData,
+        FrameKind::Null,
+        FrameKind::QosNull,
+        FrameKind::OtherData,
+        FrameKind::Extension,
+    ];
+
+    /// Classify a frame from the first byte of its frame control field.
+    ///
+    /// This works on the raw header, so it also counts frames libwifi can't parse.
+    pub fn from_frame_control(fc: u8) -> Self {
+        let subtype = fc >> 4;
+        match (fc >> 2) & 0b11 {
+            0 => match subtype {
+                0 => FrameKind::AssociationRequest,
+                1 => FrameKind::AssociationResponse,
+                2 => FrameKind::ReassociationRequest,
+                3 => FrameKind::ReassociationResponse,
+                4 => FrameKind::ProbeRequest,
+                5 => FrameKind::ProbeResponse,
+                8 => FrameKind::Beacon,
+                10 => FrameKind::Disassociation,
+                11 => FrameKind::Authentication,
+                12 => FrameKind::Deauthentication,
+                13 | 14 => FrameKind::Action,
+                _ => FrameKind::OtherManagement,
+            },
+            1 => match subtype {
+                8 => FrameKind::BlockAckRequest,
+                9 => FrameKind::BlockAck,
+                10 => FrameKind::PsPoll,
+                11 => FrameKind::Rts,
+                12 => FrameKind::Cts,
+                13 => FrameKind::Ack,
+                _ => FrameKind::OtherControl,
+            },
+            2 => match subtype {
+                0 => FrameKind::Data,
+                4 => FrameKind::Null,
+                8 => FrameKind::QosData,
+                12 => FrameKind::QosNull,
+                _ => FrameKind::OtherData,
+            },
+            _ => FrameKind::Extension,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn category(self) -> FrameCategory {
+        match self {
+            FrameKind::Beacon
+            | FrameKind::ProbeRequest
+            | FrameKind::ProbeResponse
+            | FrameKind::Authentication
+            | FrameKind::Deauthentication
+            | FrameKind::AssociationRequest
+            | FrameKind::AssociationResponse
+            | FrameKind::ReassociationRequest
+            | FrameKind::ReassociationResponse
+            | FrameKind::Disassociation
+            | FrameKind::Action
+            | FrameKind::OtherManagement => FrameCategory::Management,
+            FrameKind::Rts
+            | FrameKind::Cts
+            | FrameKind::Ack
+            | FrameKind::BlockAck
+            | FrameKind::BlockAckRequest
+            | FrameKind::PsPoll
+            | FrameKind::OtherControl => FrameCategory::Control,
+            FrameKind::Data
+            | FrameKind::QosData
+            | FrameKind::Null
+            | FrameKind::QosNull
+            | FrameKind::OtherData => FrameCategory::Data,
+            FrameKind::Extension => FrameCategory::Extension,
+        }
+    }
+}
+
+impl fmt::Display for FrameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            FrameKind::Beacon => "beacon",
+            FrameKind::ProbeRequest => "probe req",
+            FrameKind::ProbeResponse => "probe resp",
+            FrameKind::Authentication => "auth",
+            FrameKind::Deauthentication => "deauth",
+            FrameKind::AssociationRequest => "assoc req",
+            FrameKind::AssociationResponse => "assoc resp",
+            FrameKind::ReassociationRequest => "reassoc req",
+            FrameKind::ReassociationResponse => "reassoc resp",
+            FrameKind::Disassociation => "disassoc",
+            FrameKind::Action => "action",
+            FrameKind::OtherManagement => "other",
+            FrameKind::Rts => "RTS",
+            FrameKind::Cts => "CTS",
+            FrameKind::Ack => "ACK",
+            FrameKind::BlockAck => "block ack",
+            FrameKind::BlockAckRequest => "block ack req",
+            FrameKind::PsPoll => "PS-Poll",
+            FrameKind::OtherControl => "other",
+            FrameKind::Data => "data",
+            FrameKind::QosData => "QoS data",
+            FrameKind::Null => "null",
+            FrameKind::QosNull => "QoS null",
+            FrameKind::OtherData => "other",
+            FrameKind::Extension => "extension",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Frame counts broken down by type and subtype.
+#[derive(Serialize, Deserialize)]
+pub struct FrameStats {
+    /// Frames of each kind since startup, indexed by `FrameKind::index`.
+    pub totals: [u64; FRAME_KIND_COUNT],
+    /// Frames of each kind in the last completed second.
+    pub last_second: [u64; FRAME_KIND_COUNT],
+    #[serde(skip)]
+    this_second: [u64; FRAME_KIND_COUNT],
+    /// Per-second frame counts for each category, indexed by `FrameCategory::index`.
+    pub category_history: [RingBuffer<TimedSample<u64>>; FRAME_CATEGORY_COUNT],
+    /// Frames that failed their FCS check, which aren't classified.
+    pub corrupt: u64,
+    /// Long-term history for each category.
+    #[serde(skip)]
+    pub category_rollups: [RollupTiers; FRAME_CATEGORY_COUNT],
+}
+
+impl FrameStats {
+    pub fn new(history_cap: usize) -> Self {
+        Self {
+            totals: [0; FRAME_KIND_COUNT],
+            last_second: [0; FRAME_KIND_COUNT],
+            this_second: [0; FRAME_KIND_COUNT],
+            corrupt: 0,
+            category_history: std::array::from_fn(|_| RingBuffer::new(history_cap)),
+            category_rollups: Default::default(),
+        }
+    }
+
+    pub fn record(&mut self, kind: FrameKind) {
+        self.totals[kind.index()] += 1;
+        self.this_second[kind.index()] += 1;
+    }
+
+    /// Close the per-second counters for the second ending at `now`.
+    pub fn tick(&mut self, now: Instant) {
+        for cat in FrameCategory::ALL {
+            let count = self.category_count(&self.this_second, cat);
+            self.category_history[cat.index()].push(TimedSample { at: now, value: count });
+            self.category_rollups[cat.index()].add(now, count as f32);
+        }
+        self.last_second = std::mem::take(&mut self.this_second);
+    }
+
+    /// Frames of category `cat` in the last completed second.
+    pub fn category_rate(&self, cat: FrameCategory) -> u64 {
+        self.category_count(&self.last_second, cat)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.totals.iter().sum()
+    }
+
+    fn category_count(&self, counts: &[u64; FRAME_KIND_COUNT], cat: FrameCategory) -> u64 {
+        FrameKind::ALL
+            .iter()
+            .filter(|kind| kind.category() == cat)
+            .map(|kind| counts[kind.index()])
+            .sum()
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// Latest position reported by gpsd.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GpsFix {
+    pub lat: f64,
+    pub lon: f64,
+    /// Altitude above mean sea level (3D fixes only).
+    pub alt: Option<f64>,
+    /// Estimated horizontal error in metres, if gpsd reports it.
+    pub accuracy_m: Option<f64>,
+    #[serde(with = "crate::util::serde_instant")]
+    pub received_at: Instant,
+}
+
+/// One geotagged sighting of an AP.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GeoSample {
+    pub lat: f64,
+    pub lon: f64,
+    pub alt: Option<f64>,
+    pub accuracy_m: Option<f64>,
+    pub signal_dbm: i8,
+}
+
+/// Running location estimate for an AP built from geotagged sightings.
+///
+/// Keeps the strongest sighting plus the sums for a signal-weighted centroid,
+/// so memory stays constant however long the survey runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApLocation {
+    /// Sighting with the highest signal so far.
+    pub strongest: Option<GeoSample>,
+    pub sample_count: u64,
+    weight_sum: f64,
+    lat_sum: f64,
+    lon_sum: f64,
+}
+
+impl ApLocation {
+    pub fn observe(&mut self, fix: &GpsFix, signal_dbm: i8) {
+        let sample = GeoSample {
+            lat: fix.lat,
+            lon: fix.lon,
+            alt: fix.alt,
+            accuracy_m: fix.accuracy_m,
+            signal_dbm,
+        };
+        if self.strongest.is_none_or(|best| signal_dbm > best.signal_dbm) {
+            self.strongest = Some(sample);
+        }
+
+        // Weight by received power in mW so close sightings dominate
+        let weight = 10f64.powf(signal_dbm as f64 / 10.0);
+        self.weight_sum += weight;
+        self.lat_sum += fix.lat * weight;
+        self.lon_sum += fix.lon * weight;
+        self.sample_count += 1;
+    }
+
+    /// Signal-weighted centroid of all sightings.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        if self.weight_sum > 0.0 {
+            Some((self.lat_sum / self.weight_sum, self.lon_sum / self.weight_sum))
+        } else {
+            None
+        }
+    }
+}
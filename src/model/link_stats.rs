@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Sequence numbers wrap at 12 bits.
+const SEQ_MODULO: u16 = 4096;
+
+/// Gaps larger than this are more likely a restart or a long absence than loss.
+const MAX_COUNTED_GAP: u16 = 64;
+
+/// Only count a gap if the previous frame in the stream was this recent, so
+/// time spent on other channels doesn't read as loss.
+const MAX_GAP_AGE: Duration = Duration::from_millis(500);
+
+/// Sequence control and retry flag of one management or data frame.
+#[derive(Debug, Clone, Copy)]
+pub struct SequenceInfo {
+    pub transmitter: [u8; 6],
+    /// Counter the sequence number belongs to: the TID for QoS data, or
+    /// `STREAM_MANAGEMENT` / `STREAM_DATA` for the shared counters.
+    pub stream: u8,
+    pub seq: u16,
+    pub fragment: u8,
+    pub retry: bool,
+}
+
+impl SequenceInfo {
+    pub const STREAM_MANAGEMENT: u8 = 16;
+    pub const STREAM_DATA: u8 = 17;
+}
+
+/// Retransmission and sequence-gap counters for one transmitter (or channel).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkStats {
+    pub frames: u64,
+    /// Frames with the retry flag set.
+    pub retries: u64,
+    /// Retries of a frame we already saw, which the receiver would discard.
+    pub duplicates: u64,
+    /// Sequence numbers skipped while we were listening.
+    pub lost: u64,
+    /// Last (sequence, fragment) and when it was heard, per stream.
+    #[serde(skip)]
+    streams: HashMap<u8, (u16, u8, Instant)>,
+}
+
+impl LinkStats {
+    /// Count a frame for retry rate only (no sequence tracking).
+    pub fn count(&mut self, retry: bool) {
+        self.frames += 1;
+        if retry {
+            self.retries += 1;
+        }
+    }
+
+    /// Count a frame and check its sequence number against the stream.
+    pub fn record(&mut self, info: &SequenceInfo, now: Instant) {
+        self.count(info.retry);
+        let previous = self.streams.insert(info.stream, (info.seq, info.fragment, now));
+        let Some((last_seq, last_fragment, last_at)) = previous else {
+            return;
+        };
+        if info.seq == last_seq {
+            if info.retry && info.fragment == last_fragment {
+                self.duplicates += 1;
+            }
+            return;
+        }
+        let gap = (info.seq + SEQ_MODULO - last_seq) % SEQ_MODULO - 1;
+        if gap > 0 && gap <= MAX_COUNTED_GAP && now.saturating_duration_since(last_at) <= MAX_GAP_AGE {
+            self.lost += gap as u64;
+        }
+    }
+
+    pub fn retry_percent(&self) -> Option<f64> {
+        (self.frames > 0).then(|| self.retries as f64 * 100.0 / self.frames as f64)
+    }
+
+    /// Share of sequence numbers we expected but never heard.
+    pub fn loss_percent(&self) -> Option<f64> {
+        let unique = self.frames - self.duplicates;
+        let expected = unique + self.lost;
+        (expected > 0).then(|| self.lost as f64 * 100.0 / expected as f64)
+    }
+}
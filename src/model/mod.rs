@@ -1,5 +1,26 @@
 pub mod access_point;
+pub mod alert;
+pub mod ap_group;
 pub mod app_state;
+pub mod channel_usage;
+pub mod frame_stats;
+pub mod geo;
+pub mod link_stats;
+pub mod phy;
+pub mod probes;
+pub mod recommend;
+pub mod spectrum;
+pub mod station;
+pub mod survey;
 
-pub use access_point::{AccessPoint, EncryptionType};
-pub use app_state::{AppState, BandFilter, TimeWindow};
+pub use access_point::{AccessPoint, BeaconTiming, BssLoad, DecloakSource, EncryptionType, TsfEvent};
+pub use alert::{Alert, Severity};
+pub use ap_group::{ApGroup, GroupKey, GroupRow, TableGrouping};
+pub use app_state::{AppState, BandFilter, CaptureRequest, ChannelLock, GraphStat};
+pub use channel_usage::ChannelUsage;
+pub use frame_stats::{FrameCategory, FrameKind, FrameStats};
+pub use geo::{ApLocation, GpsFix};
+pub use link_stats::LinkStats;
+pub use probes::{ProbedSsid, SsidProbes};
+pub use station::Station;
+pub use survey::{FloorPlan, HeatmapTarget, Survey};
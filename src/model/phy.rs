@@ -0,0 +1,142 @@
+//! Per-frame PHY metadata, independent of the capture header format it came from.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How the frame was modulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PhyMode {
+    /// 802.11a/b/g rates, described by data rate alone.
+    Legacy,
+    /// 802.11n
+    Ht,
+    /// 802.11ac
+    Vht,
+    /// 802.11ax
+    He,
+}
+
+/// Data rate and the MCS parameters it was derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PhyRate {
+    pub mode: PhyMode,
+    pub mbps: f32,
+    /// MCS index (per stream for VHT/HE); None for legacy rates.
+    pub mcs: Option<u8>,
+    pub nss: u8,
+    pub bandwidth_mhz: u16,
+    /// Guard interval in nanoseconds.
+    pub gi_ns: u16,
+}
+
+/// Bits per subcarrier and coding rate for MCS 0-11 (HT uses 0-7 per stream).
+const MCS_MODULATION: [(f32, f32); 12] = [
+    (1.0, 1.0 / 2.0),
+    (2.0, 1.0 / 2.0),
+    (2.0, 3.0 / 4.0),
+    (4.0, 1.0 / 2.0),
+    (4.0, 3.0 / 4.0),
+    (6.0, 2.0 / 3.0),
+    (6.0, 3.0 / 4.0),
+    (6.0, 5.0 / 6.0),
+    (8.0, 3.0 / 4.0),
+    (8.0, 5.0 / 6.0),
+    (10.0, 3.0 / 4.0),
+    (10.0, 5.0 / 6.0),
+];
+
+impl PhyRate {
+    pub fn legacy(mbps: f32) -> Self {
+        Self {
+            mode: PhyMode::Legacy,
+            mbps,
+            mcs: None,
+            nss: 1,
+            bandwidth_mhz: 20,
+            gi_ns: 800,
+        }
+    }
+
+    /// Rate for an HT/VHT/HE MCS. Returns None for an MCS the mode doesn't define.
+    pub fn from_mcs(mode: PhyMode, mcs: u8, nss: u8, bandwidth_mhz: u16, gi_ns: u16) -> Option<Self> {
+        let &(bits, coding) = MCS_MODULATION.get(mcs as usize)?;
+        let mbps = data_subcarriers(mode, bandwidth_mhz)? * bits * coding * nss.max(1) as f32
+            / symbol_us(mode, gi_ns);
+        Some(Self {
+            mode,
+            mbps,
+            mcs: Some(mcs),
+            nss: nss.max(1),
+            bandwidth_mhz,
+            gi_ns,
+        })
+    }
+
+    /// Duration of one OFDM symbol including guard interval, in µs.
+    pub fn symbol_us(&self) -> f32 {
+        symbol_us(self.mode, self.gi_ns)
+    }
+
+    /// 802.11b DSSS/CCK rates, which use a different preamble from OFDM.
+    pub fn is_dsss(&self) -> bool {
+        self.mode == PhyMode::Legacy && [1.0, 2.0, 5.5, 11.0].contains(&self.mbps)
+    }
+}
+
+impl fmt::Display for PhyRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            PhyMode::Legacy => return write!(f, "{}M", self.mbps),
+            PhyMode::Ht => "HT",
+            PhyMode::Vht => "VHT",
+            PhyMode::He => "HE",
+        };
+        write!(
+            f,
+            "{mode} MCS{} {}ss {}MHz {:.0}M",
+            self.mcs.unwrap_or(0),
+            self.nss,
+            self.bandwidth_mhz,
+            self.mbps
+        )
+    }
+}
+
+fn data_subcarriers(mode: PhyMode, bandwidth_mhz: u16) -> Option<f32> {
+    let n = match (mode, bandwidth_mhz) {
+        (PhyMode::He, 20) => 234,
+        (PhyMode::He, 40) => 468,
+        (PhyMode::He, 80) => 980,
+        (PhyMode::He, 160) => 1960,
+        (_, 20) => 52,
+        (_, 40) => 108,
+        (_, 80) => 234,
+        (_, 160) => 468,
+        _ => return None,
+    };
+    Some(n as f32)
+}
+
+fn symbol_us(mode: PhyMode, gi_ns: u16) -> f32 {
+    let base = if mode == PhyMode::He { 12.8 } else { 3.2 };
+    base + gi_ns as f32 / 1000.0
+}
+
+/// Metadata about how a frame was received.
+#[derive(Debug, Clone, Default)]
+pub struct PhyInfo {
+    /// Receiver's TSF timer when the frame arrived, in µs.
+    pub tsft: Option<u64>,
+    pub freq_mhz: Option<u16>,
+    pub signal_dbm: Option<i8>,
+    pub noise_dbm: Option<i8>,
+    /// Signal per receive chain as (antenna index, dBm).
+    pub chain_signal: Vec<(u8, i8)>,
+    pub rate: Option<PhyRate>,
+    pub short_preamble: bool,
+    /// The frame ends with its 4-byte FCS.
+    pub fcs_included: bool,
+    /// The driver already found the FCS to be wrong.
+    pub bad_fcs: bool,
+}
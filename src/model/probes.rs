@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+/// Clients remembered per SSID. Probe requests are trivially forged, so
+/// past this an SSID only notes that there were more.
+pub const MAX_SSID_CLIENTS: usize = 256;
+
+/// One entry in a client's preferred-network list, learned from its
+/// directed probe requests.
+#[derive(Serialize, Deserialize)]
+pub struct ProbedSsid {
+    pub ssid: String,
+    pub probes: u64,
+    #[serde(with = "crate::util::serde_instant")]
+    pub last_seen: Instant,
+    /// A corporate SSID probed while none of its APs were in range.
+    pub exposed: bool,
+}
+
+/// Every client heard probing for one SSID over the session.
+///
+/// Kept apart from `Station` so the totals outlive station expiry.
+#[derive(Serialize, Deserialize)]
+pub struct SsidProbes {
+    pub ssid: String,
+    pub probes: u64,
+    #[serde(with = "crate::util::serde_instant")]
+    pub last_seen: Instant,
+    pub clients: HashSet<[u8; 6]>,
+    /// Clients were turned away once `clients` was full.
+    pub more_clients: bool,
+    /// Matches one of the configured corporate SSIDs.
+    pub corporate: bool,
+    /// Clients that probed for it away from corporate coverage.
+    pub exposed_clients: HashSet<[u8; 6]>,
+    /// Exposed clients were turned away once `exposed_clients` was full.
+    pub more_exposed: bool,
+}
+
+impl SsidProbes {
+    pub fn new(ssid: String, corporate: bool, now: Instant) -> Self {
+        Self {
+            ssid,
+            probes: 0,
+            last_seen: now,
+            clients: HashSet::new(),
+            more_clients: false,
+            corporate,
+            exposed_clients: HashSet::new(),
+            more_exposed: false,
+        }
+    }
+
+    pub fn add_client(&mut self, mac: [u8; 6]) {
+        insert_capped(&mut self.clients, &mut self.more_clients, mac);
+    }
+
+    /// Note a client probing for the SSID away from corporate coverage,
+    /// returning whether it wasn't already known to.
+    ///
+    /// Once the set is full nothing is new, so a flood of forged probes
+    /// raises at most `MAX_SSID_CLIENTS` alerts per SSID.
+    pub fn add_exposed_client(&mut self, mac: [u8; 6]) -> bool {
+        insert_capped(&mut self.exposed_clients, &mut self.more_exposed, mac)
+    }
+}
+
+fn insert_capped(set: &mut HashSet<[u8; 6]>, more: &mut bool, mac: [u8; 6]) -> bool {
+    if set.len() >= MAX_SSID_CLIENTS && !set.contains(&mac) {
+        *more = true;
+        return false;
+    }
+    set.insert(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_sets_are_capped() {
+        let mut probes = SsidProbes::new("Corp".to_string(), true, Instant::now());
+        for i in 0..MAX_SSID_CLIENTS as u16 + 10 {
+            let [hi, lo] = i.to_be_bytes();
+            let mac = [0x02, 0, 0, 0, hi, lo];
+            probes.add_client(mac);
+            assert_eq!(probes.add_exposed_client(mac), (i as usize) < MAX_SSID_CLIENTS);
+        }
+        assert_eq!(probes.clients.len(), MAX_SSID_CLIENTS);
+        assert_eq!(probes.exposed_clients.len(), MAX_SSID_CLIENTS);
+        assert!(probes.more_clients && probes.more_exposed);
+        // Known clients are still recognized
+        assert!(!probes.add_exposed_client([0x02, 0, 0, 0, 0, 0]));
+    }
+}
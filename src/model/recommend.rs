@@ -0,0 +1,285 @@
+//! Ranks channels and widths for a new AP from what we've heard.
+//!
+//! Blocks with a channel we haven't listened to in the window are left out:
+//! an empty channel we never tuned to would otherwise look ideal. In 6 GHz
+//! we only hop to the preferred scanning channels, one per 80 MHz block, so
+//! 6 GHz blocks are judged by the airtime on those.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::model::spectrum::{self, ChannelSpan, CHANNELS_5G, CHANNELS_6G_PSC};
+use crate::model::AppState;
+
+/// Recommendations listed per band.
+const PER_BAND: usize = 3;
+
+/// Cost weights: a fully overlapping strong AP, 100% measured utilization
+/// and 100% retries each cost about the same.
+const OVERLAP_WEIGHT: f64 = 10.0;
+const UTILIZATION_WEIGHT: f64 = 0.1;
+const RETRY_WEIGHT: f64 = 0.1;
+
+/// Radar detection may force a DFS channel to move, so prefer others a little.
+const DFS_PENALTY: f64 = 1.0;
+
+/// 2.4 GHz candidates: the only three channels that don't overlap each other.
+const CANDIDATES_2G: [u8; 3] = [1, 6, 11];
+
+/// 5 GHz bonded blocks, as (width, member 20 MHz channels).
+const BLOCKS_5G: [(u16, &[u8]); 15] = [
+    (40, &[36, 40]),
+    (40, &[44, 48]),
+    (40, &[52, 56]),
+    (40, &[60, 64]),
+    (40, &[100, 104]),
+    (40, &[108, 112]),
+    (40, &[116, 120]),
+    (40, &[124, 128]),
+    (40, &[132, 136]),
+    (40, &[149, 153]),
+    (40, &[157, 161]),
+    (80, &[36, 40, 44, 48]),
+    (80, &[52, 56, 60, 64]),
+    (80, &[100, 104, 108, 112]),
+    (80, &[149, 153, 157, 161]),
+];
+const BLOCKS_5G_160: [&[u8]; 2] = [
+    &[36, 40, 44, 48, 52, 56, 60, 64],
+    &[100, 104, 108, 112, 116, 120, 124, 128],
+];
+
+/// Number of 6 GHz 80 and 160 MHz blocks. They start at channel 1, and
+/// 225-233 is too short for 80 MHz.
+const BLOCKS_6G_80: u8 = 14;
+const BLOCKS_6G_160: u8 = 7;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Band {
+    Ghz2_4,
+    Ghz5,
+    Ghz6,
+}
+
+impl Band {
+    fn name(self) -> &'static str {
+        match self {
+            Band::Ghz2_4 => "2.4 GHz",
+            Band::Ghz5 => "5 GHz",
+            Band::Ghz6 => "6 GHz",
+        }
+    }
+}
+
+/// One ranked channel choice, with the reasons behind its cost.
+pub struct Recommendation {
+    pub band: &'static str,
+    /// Primary (lowest) channel of the block.
+    pub channel: u8,
+    pub width_mhz: u16,
+    /// Lower is better.
+    pub cost: f64,
+    pub reasons: Vec<String>,
+}
+
+impl fmt::Display for Recommendation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ch {} @ {} MHz (cost {:.1}): {}",
+            self.channel,
+            self.width_mhz,
+            self.cost,
+            self.reasons.join("; ")
+        )
+    }
+}
+
+/// The best few channels per band, best first, judged on utilization and
+/// retries measured over `window`.
+pub fn recommend(state: &AppState, window: Duration) -> Vec<Recommendation> {
+    let mut candidates_2g: Vec<Recommendation> = CANDIDATES_2G
+        .iter()
+        .filter_map(|&ch| evaluate(state, window, Band::Ghz2_4, &[ch], 20))
+        .collect();
+
+    let mut candidates_5g: Vec<Recommendation> = CHANNELS_5G
+        .iter()
+        .filter_map(|&ch| evaluate(state, window, Band::Ghz5, &[ch], 20))
+        .chain(
+            BLOCKS_5G
+                .iter()
+                .filter_map(|&(width, channels)| evaluate(state, window, Band::Ghz5, channels, width)),
+        )
+        .chain(
+            BLOCKS_5G_160
+                .iter()
+                .filter_map(|&channels| evaluate(state, window, Band::Ghz5, channels, 160)),
+        )
+        .collect();
+
+    let mut candidates_6g: Vec<Recommendation> = CHANNELS_6G_PSC
+        .iter()
+        .filter_map(|&ch| evaluate(state, window, Band::Ghz6, &[ch], 20))
+        .chain(
+            blocks_6g(BLOCKS_6G_80, 80)
+                .filter_map(|channels| evaluate(state, window, Band::Ghz6, &channels, 80)),
+        )
+        .chain(
+            blocks_6g(BLOCKS_6G_160, 160)
+                .filter_map(|channels| evaluate(state, window, Band::Ghz6, &channels, 160)),
+        )
+        .collect();
+
+    let mut ranked = Vec::new();
+    for candidates in [&mut candidates_2g, &mut candidates_5g, &mut candidates_6g] {
+        // Equal cost goes to the wider channel
+        candidates.sort_by(|a, b| a.cost.total_cmp(&b.cost).then(b.width_mhz.cmp(&a.width_mhz)));
+        ranked.extend(candidates.drain(..).take(PER_BAND));
+    }
+    ranked
+}
+
+/// Cost of putting a new AP on the block made of `channels`, or None if we
+/// haven't listened to all of them.
+fn evaluate(
+    state: &AppState,
+    window: Duration,
+    band: Band,
+    channels: &[u8],
+    width_mhz: u16,
+) -> Option<Recommendation> {
+    // 6 GHz channel numbers restart at 1, so the band decides what they mean
+    let six_ghz = band == Band::Ghz6;
+    let first = *channels.first()?;
+    let last = *channels.last()?;
+    let center = first + (last - first) / 2;
+    let span = if six_ghz {
+        ChannelSpan::bonded_6g(center, width_mhz)
+    } else {
+        ChannelSpan::bonded(center, width_mhz)
+            .filter(|_| channels.len() > 1)
+            .or_else(|| ChannelSpan::primary(first))
+    }?;
+    let now = state.now();
+    // Measured airtime on each member channel we hop to; None where we had no dwell
+    let utilization: Option<Vec<f64>> = channels
+        .iter()
+        .filter(|ch| !six_ghz || CHANNELS_6G_PSC.contains(ch))
+        .map(|&ch| state.usage(ch, six_ghz)?.utilization_percent(now, window))
+        .collect();
+    let utilization = mean(utilization?.into_iter())?;
+    let mut reasons = Vec::new();
+
+    // Overlap with every AP heard, weighted by how strongly we hear it
+    let overlapping: Vec<_> = state
+        .access_points
+        .values()
+        .filter_map(|ap| Some((ap, ap.span()?.overlap(&span))))
+        .filter(|&(_, overlap)| overlap > 0.0)
+        .collect();
+    let overlap: f64 = if six_ghz {
+        channels
+            .iter()
+            .filter_map(|&ch| ChannelSpan::primary_6g(ch))
+            .map(|victim| spectrum::interference_score(state.access_points.values(), &victim))
+            .sum()
+    } else {
+        spectrum::interference_scores(state.access_points.values(), channels)
+            .iter()
+            .map(|&(_, score)| score)
+            .sum()
+    };
+    match overlapping.iter().map(|(ap, _)| ap.signal_dbm).max() {
+        Some(strongest) => reasons.push(format!(
+            "{} AP{} overlap (strongest {strongest} dBm, score {overlap:.1})",
+            overlapping.len(),
+            if overlapping.len() == 1 { "" } else { "s" }
+        )),
+        None => reasons.push("no APs overlap".to_string()),
+    }
+
+    reasons.push(format!("{utilization:.0}% busy"));
+    // Retries on the member channels, where we have them; not kept for 6 GHz
+    let retries = mean(
+        channels
+            .iter()
+            .filter(|_| !six_ghz)
+            .filter_map(|ch| state.channel_link.get(ch)?.retry_percent()),
+    );
+    if let Some(percent) = retries {
+        reasons.push(format!("{percent:.0}% retries"));
+    }
+
+    let dfs = !six_ghz && channels.iter().any(|&ch| is_dfs(ch));
+    if dfs {
+        reasons.push("DFS".to_string());
+    }
+
+    let cost = overlap * OVERLAP_WEIGHT
+        + utilization * UTILIZATION_WEIGHT
+        + retries.unwrap_or(0.0) * RETRY_WEIGHT
+        + if dfs { DFS_PENALTY } else { 0.0 };
+
+    Some(Recommendation {
+        band: band.name(),
+        channel: first,
+        width_mhz,
+        cost,
+        reasons,
+    })
+}
+
+/// Member 20 MHz channels of each `width_mhz` block in 6 GHz, lowest first.
+/// 20 MHz channels there are numbered 1, 5, 9, ...
+fn blocks_6g(count: u8, width_mhz: u16) -> impl Iterator<Item = Vec<u8>> {
+    let members = (width_mhz / 20) as u8;
+    (0..count).map(move |i| (0..members).map(|n| 1 + 4 * (members * i + n)).collect())
+}
+
+/// Channels 52-144 need radar detection in most regulatory domains.
+fn is_dfs(channel: u8) -> bool {
+    (52..=144).contains(&channel)
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::util::clock::Clock;
+
+    #[test]
+    fn six_ghz_blocks_hold_one_psc_per_80_mhz() {
+        let blocks: Vec<_> = blocks_6g(BLOCKS_6G_80, 80).collect();
+        assert_eq!(blocks[0], vec![1, 5, 9, 13]);
+        assert_eq!(blocks.last().unwrap(), &vec![209, 213, 217, 221]);
+        for block in &blocks {
+            assert_eq!(block.iter().filter(|ch| CHANNELS_6G_PSC.contains(ch)).count(), 1);
+        }
+        let wide: Vec<_> = blocks_6g(BLOCKS_6G_160, 160).collect();
+        assert_eq!(wide[1], vec![33, 37, 41, 45, 49, 53, 57, 61]);
+        assert_eq!(wide.last().unwrap().last(), Some(&221));
+    }
+
+    #[test]
+    fn six_ghz_dwell_is_not_credited_to_2g() {
+        let t0 = Instant::now();
+        let mut state = AppState::new("wlan0".to_string(), Clock::fixed(t0));
+        state.set_current_channel(5, true);
+        state.clock.set(t0 + Duration::from_secs(10));
+        state.tick_if_due();
+
+        let ranked = recommend(&state, Duration::from_secs(60));
+        assert!(ranked.iter().all(|rec| rec.band == "6 GHz"));
+        assert!(ranked.iter().any(|rec| rec.channel == 5 && rec.width_mhz == 20));
+        // 1-13 at 80 MHz is judged by PSC 5 alone
+        assert!(ranked.iter().any(|rec| rec.channel == 1 && rec.width_mhz == 80));
+        assert!(state.usage(5, false).is_none());
+    }
+}
@@ -0,0 +1,122 @@
+//! Where APs sit in the spectrum, and how much they overlap each channel.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::AccessPoint;
+
+/// 20 MHz channels we score, per band.
+pub const CHANNELS_2G: [u8; 13] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+pub const CHANNELS_5G: [u8; 25] = [
+    36, 40, 44, 48, 52, 56, 60, 64, 100, 104, 108, 112, 116, 120, 124, 128, 132, 136, 140, 144, 149, 153,
+    157, 161, 165,
+];
+/// 6 GHz preferred scanning channels: one in every 80 MHz block, where
+/// clients look for APs first. These are the ones we hop to.
+pub const CHANNELS_6G_PSC: [u8; 15] = [5, 21, 37, 53, 69, 85, 101, 117, 133, 149, 165, 181, 197, 213, 229];
+
+/// A 20 MHz 2.4 GHz transmission spreads over about 22 MHz, which is why
+/// only channels five apart (1/6/11) stay clear of each other.
+const WIDTH_2G_MHZ: u16 = 22;
+
+/// Signal that counts for nothing, and signal that counts fully, in the score.
+const SCORE_FLOOR_DBM: f64 = -90.0;
+const SCORE_CEILING_DBM: f64 = -30.0;
+
+/// Frequency range an AP transmits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelSpan {
+    pub center_mhz: u16,
+    pub width_mhz: u16,
+}
+
+impl ChannelSpan {
+    /// A plain 20 MHz channel.
+    pub fn primary(channel: u8) -> Option<Self> {
+        let center_mhz = channel_center_mhz(channel)?;
+        let width_mhz = if channel <= 14 { WIDTH_2G_MHZ } else { 20 };
+        Some(Self { center_mhz, width_mhz })
+    }
+
+    /// A bonded channel of `width_mhz` centred on channel number `center`.
+    pub fn bonded(center: u8, width_mhz: u16) -> Option<Self> {
+        Some(Self {
+            center_mhz: channel_center_mhz(center)?,
+            width_mhz,
+        })
+    }
+
+    /// A 20 MHz 6 GHz channel.
+    pub fn primary_6g(channel: u8) -> Option<Self> {
+        Self::bonded_6g(channel, 20)
+    }
+
+    /// A 6 GHz channel of `width_mhz` centred on channel number `center`.
+    pub fn bonded_6g(center: u8, width_mhz: u16) -> Option<Self> {
+        Some(Self {
+            center_mhz: channel_center_mhz_6g(center)?,
+            width_mhz,
+        })
+    }
+
+    pub fn low_mhz(&self) -> f64 {
+        self.center_mhz as f64 - self.width_mhz as f64 / 2.0
+    }
+
+    pub fn high_mhz(&self) -> f64 {
+        self.center_mhz as f64 + self.width_mhz as f64 / 2.0
+    }
+
+    /// Share of `other` that this span covers, from 0 to 1.
+    pub fn overlap(&self, other: &ChannelSpan) -> f64 {
+        let overlap = self.high_mhz().min(other.high_mhz()) - self.low_mhz().max(other.low_mhz());
+        (overlap / other.width_mhz as f64).clamp(0.0, 1.0)
+    }
+}
+
+/// Centre frequency of a 2.4 or 5 GHz channel number.
+pub fn channel_center_mhz(channel: u8) -> Option<u16> {
+    match channel {
+        1..=13 => Some(2407 + 5 * channel as u16),
+        14 => Some(2484),
+        32..=177 => Some(5000 + 5 * channel as u16),
+        _ => None,
+    }
+}
+
+/// Centre frequency of a 6 GHz channel number. These restart at 1, so the
+/// band has to be known separately.
+pub fn channel_center_mhz_6g(channel: u8) -> Option<u16> {
+    match channel {
+        2 => Some(5935),
+        1..=233 if channel % 2 == 1 => Some(5950 + 5 * channel as u16),
+        _ => None,
+    }
+}
+
+/// How much energy from the APs in `aps` lands on each 20 MHz channel.
+///
+/// Each AP adds the share of the channel its span covers, weighted by its
+/// signal from 0 at -90 dBm to 1 at -30 dBm. A score around 1 is one strong
+/// AP; 2.4 GHz neighbours a channel or two away count partly.
+pub fn interference_scores<'a>(
+    aps: impl IntoIterator<Item = &'a AccessPoint> + Clone,
+    channels: &[u8],
+) -> Vec<(u8, f64)> {
+    channels
+        .iter()
+        .filter_map(|&ch| Some((ch, interference_score(aps.clone(), &ChannelSpan::primary(ch)?))))
+        .collect()
+}
+
+/// How much energy from the APs in `aps` lands on `victim`, scored as for
+/// `interference_scores`.
+pub fn interference_score<'a>(aps: impl IntoIterator<Item = &'a AccessPoint>, victim: &ChannelSpan) -> f64 {
+    aps.into_iter()
+        .filter_map(|ap| Some((ap.span()?, ap.signal_dbm)))
+        .map(|(span, dbm)| span.overlap(victim) * signal_weight(dbm))
+        .sum()
+}
+
+fn signal_weight(dbm: i8) -> f64 {
+    ((dbm as f64 - SCORE_FLOOR_DBM) / (SCORE_CEILING_DBM - SCORE_FLOOR_DBM)).clamp(0.0, 1.0)
+}
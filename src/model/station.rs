@@ -0,0 +1,49 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{LinkStats, ProbedSsid};
+use crate::util::mac::format_mac;
+
+/// A client device seen transmitting data or management frames.
+#[derive(Serialize, Deserialize)]
+pub struct Station {
+    pub mac: [u8; 6],
+    /// BSSID the station is associated with, if known.
+    pub bssid: Option<[u8; 6]>,
+    pub signal_dbm: i8,
+    #[serde(with = "crate::util::serde_instant")]
+    pub last_seen: Instant,
+    pub packet_count: u64,
+    /// Retries and sequence gaps in frames the station sent.
+    pub link: LinkStats,
+    /// SSIDs from directed probe requests, in the order first heard.
+    pub probed_ssids: Vec<ProbedSsid>,
+}
+
+impl Station {
+    pub fn new(mac: [u8; 6], seen_at: Instant) -> Self {
+        Self {
+            mac,
+            bssid: None,
+            signal_dbm: -100,
+            last_seen: seen_at,
+            packet_count: 0,
+            link: LinkStats::default(),
+            probed_ssids: Vec::new(),
+        }
+    }
+
+    pub fn mac_str(&self) -> String {
+        format_mac(&self.mac)
+    }
+
+    /// Whether any probed SSID was flagged as a corporate exposure.
+    pub fn is_exposed(&self) -> bool {
+        self.probed_ssids.iter().any(|probe| probe.exposed)
+    }
+
+    pub fn seconds_since_seen(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.last_seen).as_secs()
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+/// Minimum spacing between recorded samples of one BSSID at one position.
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Characters treated as walls in an ASCII floor plan.
+const WALL_CHARS: &[char] = &['#', '|', '-', '+'];
+
+/// Grid floor plan the operator marks positions on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FloorPlan {
+    pub width: usize,
+    pub height: usize,
+    walls: Vec<bool>,
+}
+
+impl FloorPlan {
+    /// Load from `WxH` dimensions (e.g. `40x20`) or an ASCII map file.
+    pub fn load(spec: &str) -> Result<Self, String> {
+        if let Some(plan) = Self::from_dimensions(spec) {
+            return Ok(plan);
+        }
+        let text = std::fs::read_to_string(spec)
+            .map_err(|e| format!("Failed to read floor plan {spec}: {e}"))?;
+        Self::from_ascii(&text)
+    }
+
+    fn from_dimensions(spec: &str) -> Option<Self> {
+        let (w, h) = spec.split_once('x')?;
+        let (width, height) = (w.parse().ok()?, h.parse().ok()?);
+        if width == 0 || height == 0 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            walls: vec![false; width * height],
+        })
+    }
+
+    /// Parse an ASCII map: `#`, `|`, `-` and `+` are walls, anything else is floor.
+    pub fn from_ascii(text: &str) -> Result<Self, String> {
+        let rows: Vec<Vec<char>> = text.lines().map(|l| l.chars().collect()).collect();
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        let height = rows.len();
+        if width == 0 || height == 0 {
+            return Err("Floor plan is empty".to_string());
+        }
+        let walls = rows
+            .iter()
+            .flat_map(|row| (0..width).map(move |x| row.get(x).is_some_and(|c| WALL_CHARS.contains(c))))
+            .collect();
+        Ok(Self { width, height, walls })
+    }
+
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        self.walls.get(y * self.width + x).copied().unwrap_or(true)
+    }
+}
+
+/// One RSSI reading taken while the operator stood at a grid cell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SurveySample {
+    pub x: usize,
+    pub y: usize,
+    pub bssid: [u8; 6],
+    pub ssid: String,
+    pub channel: u8,
+    pub signal_dbm: i8,
+    pub taken_at: SystemTime,
+}
+
+/// Aggregated readings of one BSSID in one cell (drives the heatmap).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellReading {
+    pub bssid: [u8; 6],
+    pub ssid: String,
+    pub count: u32,
+    pub sum_dbm: i64,
+    pub max_dbm: i8,
+}
+
+impl CellReading {
+    pub fn mean_dbm(&self) -> i8 {
+        (self.sum_dbm / self.count.max(1) as i64) as i8
+    }
+}
+
+/// What the heatmap is drawn for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeatmapTarget {
+    Bssid([u8; 6]),
+    /// Best BSSID of the SSID in each cell.
+    Ssid(String),
+}
+
+/// Indoor site survey: a floor plan plus RSSI readings per marked position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Survey {
+    pub plan: FloorPlan,
+    /// Cell the operator says they are standing in; readings go here.
+    pub position: Option<(usize, usize)>,
+    /// Raw readings for export. Not sent to attached clients.
+    #[serde(skip)]
+    pub samples: Vec<SurveySample>,
+    /// Per-BSSID aggregates for each cell, keyed by `y * width + x`.
+    cells: HashMap<usize, Vec<CellReading>>,
+    #[serde(skip)]
+    last_sample: HashMap<[u8; 6], Instant>,
+}
+
+impl Survey {
+    pub fn new(plan: FloorPlan) -> Self {
+        Self {
+            plan,
+            position: None,
+            samples: Vec::new(),
+            cells: HashMap::new(),
+            last_sample: HashMap::new(),
+        }
+    }
+
+    /// Start recording at `(x, y)`. Fails for walls and out-of-bounds cells.
+    pub fn mark_position(&mut self, x: usize, y: usize) -> Result<(), String> {
+        if x >= self.plan.width || y >= self.plan.height {
+            return Err(format!("cell ({x},{y}) is outside the floor plan"));
+        }
+        if self.plan.is_wall(x, y) {
+            return Err(format!("cell ({x},{y}) is a wall"));
+        }
+        self.position = Some((x, y));
+        self.last_sample.clear();
+        Ok(())
+    }
+
+    /// Stop recording (e.g. while walking to the next position).
+    pub fn clear_position(&mut self) {
+        self.position = None;
+    }
+
+    /// Record a reading at the current position, rate-limited per BSSID.
+    pub fn record(
+        &mut self,
+        bssid: [u8; 6],
+        ssid: &str,
+        channel: u8,
+        signal_dbm: i8,
+        now: Instant,
+        taken_at: SystemTime,
+    ) {
+        let Some((x, y)) = self.position else {
+            return;
+        };
+        if self
+            .last_sample
+            .get(&bssid)
+            .is_some_and(|t| now.duration_since(*t) < SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        self.last_sample.insert(bssid, now);
+
+        self.samples.push(SurveySample {
+            x,
+            y,
+            bssid,
+            ssid: ssid.to_string(),
+            channel,
+            signal_dbm,
+            taken_at,
+        });
+
+        let cell = self.cells.entry(y * self.plan.width + x).or_default();
+        let idx = match cell.iter().position(|r| r.bssid == bssid) {
+            Some(idx) => idx,
+            None => {
+                cell.push(CellReading {
+                    bssid,
+                    ssid: String::new(),
+                    count: 0,
+                    sum_dbm: 0,
+                    max_dbm: i8::MIN,
+                });
+                cell.len() - 1
+            }
+        };
+        let reading = &mut cell[idx];
+        if !ssid.is_empty() {
+            reading.ssid = ssid.to_string();
+        }
+        reading.count += 1;
+        reading.sum_dbm += signal_dbm as i64;
+        reading.max_dbm = reading.max_dbm.max(signal_dbm);
+    }
+
+    /// Mean signal for `target` in a cell, or None if it was never heard there.
+    pub fn cell_signal(&self, x: usize, y: usize, target: &HeatmapTarget) -> Option<i8> {
+        let cell = self.cells.get(&(y * self.plan.width + x))?;
+        cell.iter()
+            .filter(|r| match target {
+                HeatmapTarget::Bssid(bssid) => r.bssid == *bssid,
+                HeatmapTarget::Ssid(ssid) => r.ssid == *ssid,
+            })
+            .map(CellReading::mean_dbm)
+            .max()
+    }
+
+    /// Number of cells with at least one reading.
+    pub fn surveyed_cells(&self) -> usize {
+        self.cells.len()
+    }
+}
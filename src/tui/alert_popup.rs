@@ -0,0 +1,34 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap};
+
+use crate::model::{AppState, Severity};
+use crate::tui::theme;
+
+/// The newest unacknowledged popup alert, centred over the other panels.
+pub fn render_alert_popup(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(alert) = state.pending_popup() else {
+        return;
+    };
+    let [popup] = Layout::vertical([Constraint::Length(7)]).flex(layout::Flex::Center).areas(area);
+    let [popup] = Layout::horizontal([Constraint::Percentage(60)]).flex(layout::Flex::Center).areas(popup);
+
+    let color = match alert.severity {
+        Severity::High => theme::SIGNAL_WEAK,
+        _ => theme::BORDER_COLOR,
+    };
+    let block = Block::default()
+        .title(format!(" Alert ({}) ", alert.severity))
+        .title_bottom(Line::from(" any key to dismiss ").right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(color));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(alert.message.as_str())
+            .style(theme::TABLE_ROW)
+            .wrap(Wrap { trim: true })
+            .block(block),
+        popup,
+    );
+}
@@ -0,0 +1,139 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use crate::model::{AccessPoint, AppState};
+use crate::tui::theme;
+use crate::util::time::{format_duration, format_utc};
+
+/// Everything we know about the AP under the table cursor.
+pub fn render_ap_detail(frame: &mut Frame, area: Rect, state: &AppState) {
+    let block = Block::default()
+        .title(" AP Detail ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::BORDER_COLOR));
+
+    let lines = match state.selected_ap() {
+        Some(ap) => detail_lines(ap, state),
+        None => vec![Line::from("No AP selected")],
+    };
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn detail_lines<'a>(ap: &'a AccessPoint, state: &AppState) -> Vec<Line<'a>> {
+    let dash = || "-".to_string();
+    let noise_floor = state
+        .channel_noise
+        .get(&ap.channel)
+        // Noise is only tracked per 2.4/5 GHz channel
+        .filter(|_| !ap.six_ghz)
+        .map(|floor| format!("{floor:.0} dBm"))
+        .unwrap_or_else(dash);
+    let chains = if ap.chain_signal.is_empty() {
+        dash()
+    } else {
+        ap.chain_signal
+            .iter()
+            .map(|(antenna, dbm)| format!("A{antenna} {dbm} dBm"))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    let bss_load = ap
+        .bss_load
+        .map(|load| {
+            format!(
+                "{} stations, {:.0}% busy",
+                load.station_count,
+                load.utilization_percent()
+            )
+        })
+        .unwrap_or_else(dash);
+    let retries = ap
+        .link
+        .retry_percent()
+        .map(|retry| {
+            format!(
+                "{retry:.1}% of {} frames, {} duplicates, {:.1}% lost",
+                ap.link.frames,
+                ap.link.duplicates,
+                ap.link.loss_percent().unwrap_or(0.0)
+            )
+        })
+        .unwrap_or_else(dash);
+    let beacons = match ap.beacon_interval_tu {
+        Some(interval) => format!(
+            "{} every {interval} TU, {} missed ({:.1}%)",
+            ap.beacon_count,
+            ap.missed_beacons,
+            ap.missed_beacon_percent().unwrap_or(0.0)
+        ),
+        None => ap.beacon_count.to_string(),
+    };
+    let uptime = match ap.uptime(state.now()) {
+        _ if ap.has_tsf_conflict() => format!("unknown, TSFs from more than one radio ({} jumps)", ap.tsf_conflicts),
+        Some(up) if ap.reboots > 0 => format!("{} ({} reboots seen)", format_duration(up), ap.reboots),
+        Some(up) => format_duration(up),
+        None => dash(),
+    };
+    let timing = match (ap.beacon_jitter_us, ap.tsf_skew_ppm) {
+        (None, None) => dash(),
+        (jitter, skew) => format!(
+            "jitter {}, TSF skew vs our radio {}",
+            jitter.map(|j| format!("{:.1} ms", j / 1000.0)).unwrap_or_else(dash),
+            skew.map(|s| format!("{s:+.1} ppm")).unwrap_or_else(dash),
+        ),
+    };
+    let hidden = match (ap.hidden, ap.decloak_source(), ap.hidden_ssid_len) {
+        (false, _, _) => "no".to_string(),
+        (true, Some(source), _) => format!("yes, decloaked from {source}"),
+        (true, None, Some(len)) => format!("yes, {len}-byte SSID not yet seen"),
+        (true, None, None) => "yes, not yet decloaked".to_string(),
+    };
+    let impersonated = if ap.is_karma() {
+        format!("Karma/MANA suspect: {}", ap.impersonated_ssids().join(", "))
+    } else {
+        format!("{} SSIDs in probe responses", ap.responded_ssids.len())
+    };
+    let location = ap
+        .location
+        .centroid()
+        .map(|(lat, lon)| format!("{lat:.6}, {lon:.6}"))
+        .unwrap_or_else(dash);
+
+    vec![
+        field("SSID", ap.display_ssid().to_string()),
+        field("Hidden", hidden),
+        field("Responds", impersonated),
+        field("BSSID", ap.bssid_str()),
+        field(
+            "Channel",
+            format!("{} ({} GHz, noise floor {noise_floor})", ap.channel, ap.band_label()),
+        ),
+        field("Encryption", ap.encryption.to_string()),
+        field("Signal", format!("{} dBm", ap.signal_dbm)),
+        field(
+            "Noise",
+            ap.noise_dbm.map(|n| format!("{n} dBm")).unwrap_or_else(dash),
+        ),
+        field("SNR", ap.snr_db().map(|s| format!("{s} dB")).unwrap_or_else(dash)),
+        field("Chains", chains),
+        field("Rate", ap.rate.map(|r| r.to_string()).unwrap_or_else(dash)),
+        field("BSS load", bss_load),
+        field("Clients", state.client_count(&ap.bssid).to_string()),
+        field("Retries", retries),
+        field("Beacons", beacons),
+        field("Timing", timing),
+        field("Uptime", uptime),
+        field("First seen", format_utc(ap.first_seen)),
+        field("Last seen", format!("{}s ago", ap.seconds_since_seen(state.now()))),
+        field("Location", location),
+    ]
+}
+
+fn field<'a>(label: &'a str, value: String) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(format!("{label:<11}"), theme::TABLE_HEADER),
+        Span::styled(value, theme::TABLE_ROW),
+    ])
+}
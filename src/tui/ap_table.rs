@@ -6,6 +6,7 @@ use crate::tui::theme;
 
 pub fn render_ap_table(frame: &mut Frame, area: Rect, state: &AppState) {
     let aps = state.sorted_aps();
+    let now = state.now();
 
     let header = Row::new(vec![
         Cell::from("SSID"),
@@ -13,6 +14,8 @@ pub fn render_ap_table(frame: &mut Frame, area: Rect, state: &AppState) {
         Cell::from("CH"),
         Cell::from("Enc"),
         Cell::from("Signal"),
+        Cell::from("SNR"),
+        Cell::from("Retry"),
         Cell::from("Bar"),
         Cell::from("Age"),
     ])
@@ -22,21 +25,29 @@ pub fn render_ap_table(frame: &mut Frame, area: Rect, state: &AppState) {
     let rows: Vec<Row> = aps
         .iter()
         .skip(state.table_scroll)
-        .map(|ap| {
-            let style = if ap.seconds_since_seen() > 30 {
+        .enumerate()
+        .map(|(i, ap)| {
+            let style = if i == 0 {
+                // The top visible row is the cursor for l:lock / p:pin / e:extract
+                theme::TABLE_HIGHLIGHT
+            } else if ap.seconds_since_seen(now) > 30 {
                 theme::TABLE_ROW_DIM
             } else {
                 theme::TABLE_ROW
             };
+            let pin = if state.is_pinned(&ap.bssid) { "* " } else { "" };
+            let rec = if state.extracting.contains_key(&ap.bssid) { " [rec]" } else { "" };
 
             Row::new(vec![
-                Cell::from(ap.display_ssid().to_string()),
+                Cell::from(format!("{pin}{}{}{rec}", ap.display_ssid(), ssid_marker(ap))),
                 Cell::from(ap.bssid_str()),
                 Cell::from(format!("{:>3}", ap.channel)),
                 Cell::from(ap.encryption.to_string()),
                 Cell::from(format!("{}dBm", ap.signal_dbm)),
+                Cell::from(ap.snr_db().map(|snr| format!("{snr}dB")).unwrap_or_else(|| "-".into())),
+                Cell::from(format_percent(ap.link.retry_percent())),
                 Cell::from(signal_bar(ap)),
-                Cell::from(format_age(ap.seconds_since_seen())),
+                Cell::from(format_age(ap.seconds_since_seen(now))),
             ])
             .style(style)
         })
@@ -48,6 +59,8 @@ pub fn render_ap_table(frame: &mut Frame, area: Rect, state: &AppState) {
         Constraint::Length(4),
         Constraint::Length(6),
         Constraint::Length(8),
+        Constraint::Length(5),
+        Constraint::Length(6),
         Constraint::Length(10),
         Constraint::Length(6),
     ];
@@ -80,6 +93,22 @@ fn signal_bar(ap: &AccessPoint) -> String {
     color_char
 }
 
+/// Marks a suspected Karma/MANA responder, or a hidden network whose SSID
+/// we recovered from other frames.
+fn ssid_marker(ap: &AccessPoint) -> &'static str {
+    if ap.is_karma() {
+        " (karma)"
+    } else if ap.decloak_source().is_some() {
+        " (h)"
+    } else {
+        ""
+    }
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    percent.map(|p| format!("{p:.0}%")).unwrap_or_else(|| "-".into())
+}
+
 fn format_age(secs: u64) -> String {
     if secs < 60 {
         format!("{}s", secs)
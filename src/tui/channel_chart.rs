@@ -4,43 +4,121 @@ use ratatui::widgets::{Bar, BarChart, BarGroup, Block, BorderType, Borders};
 use crate::model::AppState;
 use crate::tui::theme;
 
+const REPORTED_COLOR: Color = Color::Rgb(120, 120, 140);
+
 pub fn render_channel_chart(frame: &mut Frame, area: Rect, state: &AppState) {
-    let mut channels: Vec<(u8, u64)> = state
-        .channel_packets
+    // Measured airtime share within the selected time window, next to what
+    // APs on the channel report in their BSS Load element
+    let now = state.now();
+    let window = state.time_window.duration();
+    let mut channels: Vec<(u8, f64, Option<f64>)> = state
+        .channel_usage
         .iter()
-        .map(|(&ch, &count)| (ch, count))
+        .filter_map(|(&ch, usage)| {
+            let measured = usage.utilization_percent(now, window)?;
+            Some((ch, measured, reported_utilization(state, ch)))
+        })
         .collect();
-    channels.sort_by_key(|(ch, _)| *ch);
+    channels.sort_by_key(|(ch, _, _)| *ch);
 
-    let bars: Vec<Bar> = channels
+    let groups: Vec<BarGroup> = channels
         .iter()
-        .map(|(ch, count)| {
-            let color = if *ch <= 14 {
+        .map(|&(ch, measured, reported)| {
+            let color = if ch <= 14 {
                 Color::Rgb(80, 160, 255) // 2.4GHz = blue
             } else {
                 Color::Rgb(0, 220, 80) // 5GHz = green
             };
-            Bar::default()
-                .label(format!("{ch}").into())
-                .value(*count)
-                .style(Style::default().fg(color))
+            let mut bars = vec![percent_bar(measured, color)];
+            if let Some(reported) = reported {
+                bars.push(percent_bar(reported, REPORTED_COLOR));
+            }
+            BarGroup::default()
+                .label(Line::from(ch.to_string()).centered())
+                .bars(&bars)
         })
         .collect();
 
-    let group = BarGroup::default().bars(&bars);
+    let title = Line::from(vec![
+        Span::raw(format!(" Channel Utilization [{}] ", state.time_window)),
+        Span::styled("■ ", Style::default().fg(REPORTED_COLOR)),
+        Span::raw("AP-reported "),
+    ]);
 
-    let chart = BarChart::default()
+    let mut block = Block::default().title(title);
+    if let Some(retries) = retry_summary(state) {
+        block = block.title_bottom(Line::from(retries).left_aligned());
+    }
+    if let Some(noisy) = corrupt_summary(state) {
+        block = block.title_bottom(Line::from(noisy).right_aligned());
+    }
+
+    let mut chart = BarChart::default()
         .block(
-            Block::default()
-                .title(" Channel Utilization ")
+            block
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(theme::BORDER_COLOR)),
         )
-        .data(group)
         .bar_width(3)
-        .bar_gap(1)
-        .max(channels.iter().map(|(_, c)| *c).max().unwrap_or(1));
+        .bar_gap(0)
+        .group_gap(1)
+        .max(100);
+    for group in groups {
+        chart = chart.data(group);
+    }
 
     frame.render_widget(chart, area);
 }
+
+fn percent_bar<'a>(percent: f64, color: Color) -> Bar<'a> {
+    Bar::default()
+        .value(percent.round() as u64)
+        .text_value(format!("{percent:.0}%"))
+        .style(Style::default().fg(color))
+}
+
+/// Retry rate on each channel heard, in channel order.
+fn retry_summary(state: &AppState) -> Option<String> {
+    let mut channels: Vec<(u8, f64)> = state
+        .channel_link
+        .iter()
+        .filter_map(|(&ch, link)| Some((ch, link.retry_percent()?)))
+        .collect();
+    channels.sort_by_key(|(ch, _)| *ch);
+    let parts: Vec<String> = channels
+        .iter()
+        .map(|(ch, retry)| format!("{ch}:{retry:.0}%"))
+        .collect();
+    (!parts.is_empty()).then(|| format!(" retries {} ", parts.join(" ")))
+}
+
+/// Channels with the highest share of frames failing their FCS check.
+fn corrupt_summary(state: &AppState) -> Option<String> {
+    let mut shares: Vec<(u8, f64)> = state
+        .channel_corrupt
+        .iter()
+        .filter_map(|(&ch, &bad)| {
+            let total = *state.channel_packets.get(&ch)?;
+            (total > 0 && bad > 0).then(|| (ch, bad as f64 * 100.0 / total as f64))
+        })
+        .collect();
+    shares.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let worst: Vec<String> = shares
+        .iter()
+        .take(3)
+        .map(|(ch, share)| format!("ch{ch} {share:.1}%"))
+        .collect();
+    (!worst.is_empty()).then(|| format!(" bad FCS: {} ", worst.join("  ")))
+}
+
+/// Mean channel utilization reported by APs on `channel`, if any report it.
+fn reported_utilization(state: &AppState, channel: u8) -> Option<f64> {
+    let reports: Vec<f64> = state
+        .access_points
+        .values()
+        .filter(|ap| ap.channel == channel)
+        .filter_map(|ap| ap.bss_load.map(|load| load.utilization_percent()))
+        .collect();
+    (!reports.is_empty()).then(|| reports.iter().sum::<f64>() / reports.len() as f64)
+}
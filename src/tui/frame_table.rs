@@ -0,0 +1,69 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Cell, Row, Table};
+
+use crate::model::{AppState, FrameKind};
+use crate::tui::theme;
+
+/// Frame counts per type and subtype, for kinds seen at least once.
+pub fn render_frame_table(frame: &mut Frame, area: Rect, state: &AppState) {
+    let stats = &state.frame_stats;
+    let total = (stats.total() + stats.corrupt).max(1);
+
+    let header = Row::new(vec![
+        Cell::from("Type"),
+        Cell::from("Subtype"),
+        Cell::from("pkt/s"),
+        Cell::from("Total"),
+        Cell::from("Share"),
+    ])
+    .style(theme::TABLE_HEADER)
+    .height(1);
+
+    let mut rows: Vec<Row> = FrameKind::ALL
+        .iter()
+        .filter(|kind| stats.totals[kind.index()] > 0)
+        .map(|kind| {
+            let count = stats.totals[kind.index()];
+            let category = kind.category();
+            Row::new(vec![
+                Cell::from(category.to_string())
+                    .style(Style::default().fg(theme::frame_category_color(category))),
+                Cell::from(kind.to_string()),
+                Cell::from(format!("{:>5}", stats.last_second[kind.index()])),
+                Cell::from(format!("{count:>8}")),
+                Cell::from(format!("{:>5.1}%", count as f64 * 100.0 / total as f64)),
+            ])
+            .style(theme::TABLE_ROW)
+        })
+        .collect();
+    if stats.corrupt > 0 {
+        rows.push(
+            Row::new(vec![
+                Cell::from("-"),
+                Cell::from("bad FCS"),
+                Cell::from(format!("{:>5}", "")),
+                Cell::from(format!("{:>8}", stats.corrupt)),
+                Cell::from(format!("{:>5.1}%", stats.corrupt as f64 * 100.0 / total as f64)),
+            ])
+            .style(Style::default().fg(theme::SIGNAL_WEAK)),
+        );
+    }
+
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Min(13),
+        Constraint::Length(6),
+        Constraint::Length(9),
+        Constraint::Length(7),
+    ];
+
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .title(" Frame Types ")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme::BORDER_COLOR)),
+    );
+
+    frame.render_widget(table, area);
+}
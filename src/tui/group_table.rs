@@ -0,0 +1,117 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Cell, Row, Table};
+
+use crate::model::{AccessPoint, ApGroup, AppState, GroupKey, GroupRow, TableGrouping};
+use crate::tui::theme;
+
+/// APs rolled up into one row per ESS (SSID + security) or per physical AP,
+/// with expanded groups followed by their BSSIDs.
+pub fn render_group_table(frame: &mut Frame, area: Rect, state: &AppState) {
+    let rows = state.group_rows();
+    let group_count = rows.iter().filter(|row| matches!(row, GroupRow::Group(_))).count();
+
+    let (name_header, title) = match state.grouping {
+        TableGrouping::Device => ("Physical AP", "physical APs"),
+        _ => ("ESS", "ESS"),
+    };
+    let header = Row::new(vec![
+        Cell::from(name_header),
+        Cell::from("BSSIDs"),
+        Cell::from("Channels"),
+        Cell::from("Band"),
+        Cell::from("Enc"),
+        Cell::from("RSSI max/min"),
+        Cell::from("Clients"),
+    ])
+    .style(theme::TABLE_HEADER)
+    .height(1);
+
+    let table_rows: Vec<Row> = rows
+        .iter()
+        .skip(state.table_scroll)
+        .enumerate()
+        .map(|(i, row)| {
+            let cells = match row {
+                GroupRow::Group(group) => group_cells(group, state),
+                GroupRow::Member(ap) => member_cells(ap, state),
+            };
+            let style = if i == 0 {
+                theme::TABLE_HIGHLIGHT
+            } else if matches!(row, GroupRow::Member(_)) {
+                theme::TABLE_ROW_DIM
+            } else {
+                theme::TABLE_ROW
+            };
+            Row::new(cells).style(style)
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Min(20),
+        Constraint::Length(17),
+        Constraint::Length(16),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(12),
+        Constraint::Length(7),
+    ];
+
+    let table = Table::new(table_rows, widths).header(header).block(
+        Block::default()
+            .title(format!(
+                " Networks ({group_count} {title}, {} BSSIDs) ",
+                state.access_points.len()
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme::BORDER_COLOR)),
+    );
+
+    frame.render_widget(table, area);
+}
+
+fn group_cells<'a>(group: &ApGroup, state: &AppState) -> Vec<Cell<'a>> {
+    let expanded = if state.expanded_groups.contains(&group.key) { "▾" } else { "▸" };
+    let channels = group
+        .channels()
+        .iter()
+        .map(|ch| ch.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    // A physical AP reads as "N virtual BSSs / M radios"
+    let bssids = match group.key {
+        GroupKey::Device(_) => format!(
+            "{} BSS / {} radio{}",
+            group.members.len(),
+            group.radio_count(),
+            if group.radio_count() == 1 { "" } else { "s" }
+        ),
+        GroupKey::Ess(_) => format!("{:>6}", group.members.len()),
+    };
+    let encryption = group
+        .encryption()
+        .map(|enc| enc.to_string())
+        .unwrap_or_else(|| "mixed".to_string());
+    let clients: usize = group.members.iter().map(|ap| state.client_count(&ap.bssid)).sum();
+    vec![
+        Cell::from(format!("{expanded} {}", group.label())),
+        Cell::from(bssids),
+        Cell::from(channels),
+        Cell::from(group.bands()),
+        Cell::from(encryption),
+        Cell::from(format!("{:>4} / {:<4}", group.strongest_dbm(), group.weakest_dbm())),
+        Cell::from(format!("{clients:>7}")),
+    ]
+}
+
+fn member_cells<'a>(ap: &AccessPoint, state: &AppState) -> Vec<Cell<'a>> {
+    vec![
+        Cell::from(format!("  └ {}", ap.display_ssid())),
+        Cell::from(ap.bssid_str()),
+        Cell::from(ap.channel.to_string()),
+        Cell::from(ap.band_label()),
+        Cell::from(ap.encryption.to_string()),
+        Cell::from(format!("{:>4}", ap.signal_dbm)),
+        Cell::from(format!("{:>7}", state.client_count(&ap.bssid))),
+    ]
+}
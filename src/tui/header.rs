@@ -1,18 +1,35 @@
 use ratatui::prelude::*;
 use ratatui::widgets::Paragraph;
 
-use crate::model::AppState;
+use crate::model::{AppState, ChannelLock};
 use crate::tui::theme;
 
 pub fn render_header(frame: &mut Frame, area: Rect, state: &AppState) {
+    let lock = match state.channel_lock {
+        ChannelLock::Hopping => "",
+        _ => " (locked)",
+    };
+    // 6 GHz channel numbers overlap 2.4 GHz ones
+    let six_ghz = if state.current_six_ghz { " 6G" } else { "" };
+    let gps = if !state.gps_enabled {
+        String::new()
+    } else if let Some(fix) = state.current_fix() {
+        format!("  |  GPS: {:.5},{:.5}", fix.lat, fix.lon)
+    } else {
+        "  |  GPS: no fix".to_string()
+    };
     let text = format!(
-        " wifimonitor-tui  |  {}  |  CH: {}  |  Band: {}  |  Window: {}  |  Pkts: {}  |  APs: {}  |  q:quit  ↑↓:scroll  b:band  t:time",
+        " wifimonitor-tui  |  {}  |  CH: {}{}{}  |  Band: {}  |  Window: {}  |  Pkts: {}  |  APs: {}{}  |  q:quit  ↑↓:scroll  b:band  t:time  m:mean/max  l:lock  p:pin  d:detail  c:spectrum  n:probes  f:frames  r:recommend  g:group ({})  ⏎:expand  w:trigger  W:save ring  e:extract AP  s:survey",
         state.interface,
         state.current_channel,
+        six_ghz,
+        lock,
         state.band_filter,
         state.time_window,
         state.total_packets,
         state.access_points.len(),
+        gps,
+        state.grouping,
     );
 
     let header = Paragraph::new(text)
@@ -1,7 +1,9 @@
 use ratatui::prelude::*;
 
-use crate::model::AppState;
-use crate::tui::{ap_table, channel_chart, header, packet_rate, signal_graph};
+use crate::model::{AppState, TableGrouping};
+use crate::tui::{
+    alert_popup, ap_detail, ap_table, channel_chart, frame_table, group_table, header, packet_rate, probe_view, recommend_panel, signal_graph, spectrum_view, survey_view,
+};
 
 pub fn draw(frame: &mut Frame, state: &AppState) {
     let outer = Layout::vertical([
@@ -21,18 +23,39 @@ pub fn draw(frame: &mut Frame, state: &AppState) {
     ])
     .split(outer[1]);
 
-    signal_graph::render_signal_graph(frame, top[0], state);
+    if state.show_survey {
+        survey_view::render_survey(frame, top[0], state);
+    } else if state.show_detail {
+        ap_detail::render_ap_detail(frame, top[0], state);
+    } else if state.show_spectrum {
+        spectrum_view::render_spectrum(frame, top[0], state);
+    } else if state.show_probes {
+        probe_view::render_probes(frame, top[0], state);
+    } else {
+        signal_graph::render_signal_graph(frame, top[0], state);
+    }
 
     // Right panel: packet rate (top) + channel chart (bottom)
     let right = Layout::vertical([
         Constraint::Percentage(40), // Packet rate sparkline
-        Constraint::Percentage(60), // Channel utilization
+        Constraint::Percentage(60), // Channel utilization, frame breakdown or recommendations
     ])
     .split(top[1]);
 
     packet_rate::render_packet_rate(frame, right[0], state);
-    channel_chart::render_channel_chart(frame, right[1], state);
-
-    // Bottom: AP table
-    ap_table::render_ap_table(frame, outer[2], state);
+    if state.show_frame_stats {
+        frame_table::render_frame_table(frame, right[1], state);
+    } else if state.show_recommend {
+        recommend_panel::render_recommendations(frame, right[1], state);
+    } else {
+        channel_chart::render_channel_chart(frame, right[1], state);
+    }
+
+    // Bottom: AP table, or the same APs grouped by ESS or physical AP
+    match state.grouping {
+        TableGrouping::None => ap_table::render_ap_table(frame, outer[2], state),
+        _ => group_table::render_group_table(frame, outer[2], state),
+    }
+
+    alert_popup::render_alert_popup(frame, frame.area(), state);
 }
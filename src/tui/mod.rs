@@ -1,9 +1,17 @@
+pub mod alert_popup;
+pub mod ap_detail;
 pub mod ap_table;
 pub mod channel_chart;
+pub mod frame_table;
+pub mod group_table;
 pub mod header;
 pub mod layout;
 pub mod packet_rate;
+pub mod probe_view;
+pub mod recommend_panel;
 pub mod signal_graph;
+pub mod spectrum_view;
+pub mod survey_view;
 pub mod theme;
 
 use std::io;
@@ -1,26 +1,113 @@
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, BorderType, Borders, Sparkline};
+use ratatui::widgets::{Block, BorderType, Borders};
 
-use crate::model::AppState;
+use crate::model::frame_stats::FRAME_CATEGORY_COUNT;
+use crate::model::{AppState, FrameCategory};
 use crate::tui::theme;
+use crate::util::time_series;
 
+/// Windows up to this long show the raw per-second history as-is.
+const RAW_WINDOW_SECS: u64 = 60;
+
+/// Per-second frame counts, stacked by frame type (management at the bottom).
 pub fn render_packet_rate(frame: &mut Frame, area: Rect, state: &AppState) {
-    let data: Vec<u64> = state.packet_rate_history.iter().copied().collect();
-
-    let max_rate = data.iter().copied().max().unwrap_or(1).max(1);
-    let current = data.last().copied().unwrap_or(0);
-
-    let sparkline = Sparkline::default()
-        .block(
-            Block::default()
-                .title(format!(" Packet Rate ({current} pkt/s) "))
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(theme::BORDER_COLOR)),
-        )
-        .data(&data)
-        .max(max_rate)
-        .style(Style::default().fg(Color::Rgb(80, 160, 255)));
-
-    frame.render_widget(sparkline, area);
+    let window_secs = state.time_window.seconds();
+    let current = state.current_packet_rate();
+    let stats = &state.frame_stats;
+
+    let title = if window_secs <= RAW_WINDOW_SECS {
+        format!(" Packet Rate ({current} pkt/s) ")
+    } else {
+        format!(" Packet Rate ({current} pkt/s) [{}] ", state.time_window)
+    };
+
+    let mut legend = vec![Span::raw(" ")];
+    for cat in FrameCategory::ALL {
+        legend.push(Span::styled("■ ", Style::default().fg(theme::frame_category_color(cat))));
+        legend.push(Span::raw(format!("{cat} {}  ", stats.category_rate(cat))));
+    }
+
+    let block = Block::default()
+        .title(title)
+        .title_bottom(Line::from(legend))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::BORDER_COLOR));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+
+    let columns = if window_secs <= RAW_WINDOW_SECS {
+        raw_columns(state)
+    } else {
+        resampled_columns(state, inner.width as usize)
+    };
+    let shown = &columns[columns.len().saturating_sub(inner.width as usize)..];
+    let max_rate = shown
+        .iter()
+        .map(|col| col.iter().sum::<u64>())
+        .max()
+        .unwrap_or(1)
+        .max(1);
+
+    // Newest column at the right edge
+    let left = inner.right() - shown.len() as u16;
+    let height = inner.height as u64;
+    let buf = frame.buffer_mut();
+    for (i, col) in shown.iter().enumerate() {
+        let x = left + i as u16;
+        let mut below = 0u64;
+        let mut cumulative = 0u64;
+        for cat in FrameCategory::ALL {
+            cumulative += col[cat.index()];
+            // Round the running total so segments always add up to the bar height
+            let top = (cumulative * height + max_rate / 2) / max_rate;
+            for row in below..top {
+                let y = inner.bottom() - 1 - row as u16;
+                if let Some(cell) = buf.cell_mut((x, y)) {
+                    cell.set_symbol("█")
+                        .set_fg(theme::frame_category_color(cat));
+                }
+            }
+            below = below.max(top);
+        }
+    }
+}
+
+/// The raw per-second history, one column per second.
+fn raw_columns(state: &AppState) -> Vec<[u64; FRAME_CATEGORY_COUNT]> {
+    let history = &state.frame_stats.category_history;
+    let len = history.iter().map(|h| h.len()).min().unwrap_or(0);
+    let mut columns = vec![[0; FRAME_CATEGORY_COUNT]; len];
+    for (cat, series) in history.iter().enumerate() {
+        // Each series was pushed on the same ticks; align them from the newest end
+        let skip = series.len() - len;
+        for (col, sample) in columns.iter_mut().zip(series.iter().skip(skip)) {
+            col[cat] = sample.value;
+        }
+    }
+    columns
+}
+
+/// `count` columns spanning the time window, each the mean rate over its slice.
+fn resampled_columns(state: &AppState, count: usize) -> Vec<[u64; FRAME_CATEGORY_COUNT]> {
+    let stats = &state.frame_stats;
+    let width = state.time_window.duration() / count as u32;
+    let mut columns = vec![[0; FRAME_CATEGORY_COUNT]; count];
+    for cat in FrameCategory::ALL {
+        let buckets = time_series::resample_history(
+            &stats.category_history[cat.index()],
+            &stats.category_rollups[cat.index()],
+            state.now(),
+            width,
+            count,
+        );
+        for (col, bucket) in columns.iter_mut().zip(buckets) {
+            col[cat.index()] = bucket.map(|b| b.mean.round() as u64).unwrap_or(0);
+        }
+    }
+    columns
 }
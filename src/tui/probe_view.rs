@@ -0,0 +1,125 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, Wrap};
+
+use crate::model::{AppState, SsidProbes, Station};
+use crate::tui::theme;
+
+/// SSIDs clients are probing for, most widely probed first, beside each
+/// client's preferred-network list. Corporate SSIDs probed away from
+/// corporate coverage are highlighted.
+pub fn render_probes(frame: &mut Frame, area: Rect, state: &AppState) {
+    let halves = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).split(area);
+    render_most_probed(frame, halves[0], state);
+    render_pnls(frame, halves[1], state);
+}
+
+fn render_most_probed(frame: &mut Frame, area: Rect, state: &AppState) {
+    let header = Row::new(vec![
+        Cell::from("SSID"),
+        Cell::from("Clients"),
+        Cell::from("Probes"),
+        Cell::from(""),
+    ])
+    .style(theme::TABLE_HEADER)
+    .height(1);
+
+    let probed = state.most_probed();
+    let rows: Vec<Row> = probed
+        .iter()
+        .map(|probes| {
+            Row::new(vec![
+                Cell::from(probes.ssid.clone()),
+                Cell::from(format!("{:>7}", count(probes.clients.len(), probes.more_clients))),
+                Cell::from(format!("{:>6}", probes.probes)),
+                Cell::from(corporate_marker(probes)),
+            ])
+            .style(ssid_style(probes))
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Min(12),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows, widths).header(header).block(
+        Block::default()
+            .title(format!(" Most-probed SSIDs ({}) ", probed.len()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme::BORDER_COLOR)),
+    );
+
+    frame.render_widget(table, area);
+}
+
+fn render_pnls(frame: &mut Frame, area: Rect, state: &AppState) {
+    let mut clients: Vec<&Station> = state
+        .stations
+        .values()
+        .filter(|sta| !sta.probed_ssids.is_empty())
+        .collect();
+    // Exposed clients first, then the longest lists
+    clients.sort_by(|a, b| {
+        b.is_exposed()
+            .cmp(&a.is_exposed())
+            .then(b.probed_ssids.len().cmp(&a.probed_ssids.len()))
+            .then(a.mac.cmp(&b.mac))
+    });
+    let exposed = clients.iter().filter(|sta| sta.is_exposed()).count();
+
+    let lines: Vec<Line> = clients
+        .iter()
+        .map(|sta| {
+            let mut spans = vec![Span::styled(format!("{} ", sta.mac_str()), theme::TABLE_ROW_DIM)];
+            for (i, probe) in sta.probed_ssids.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(", ", theme::TABLE_ROW_DIM));
+                }
+                let style = if probe.exposed {
+                    Style::default().fg(theme::SIGNAL_WEAK)
+                } else {
+                    theme::TABLE_ROW
+                };
+                spans.push(Span::styled(probe.ssid.clone(), style));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!(" Client PNLs ({} clients, {exposed} exposed) ", clients.len()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::BORDER_COLOR));
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(block), area);
+}
+
+fn corporate_marker(probes: &SsidProbes) -> String {
+    if !probes.exposed_clients.is_empty() {
+        format!("exposed {}", count(probes.exposed_clients.len(), probes.more_exposed))
+    } else if probes.corporate {
+        "corp".to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// A client count, marked when the SSID stopped remembering clients.
+fn count(clients: usize, more: bool) -> String {
+    if more {
+        format!("{clients}+")
+    } else {
+        clients.to_string()
+    }
+}
+
+fn ssid_style(probes: &SsidProbes) -> Style {
+    if !probes.exposed_clients.is_empty() {
+        Style::default().fg(theme::SIGNAL_WEAK)
+    } else {
+        theme::TABLE_ROW
+    }
+}
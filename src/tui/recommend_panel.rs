@@ -0,0 +1,43 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
+
+use crate::model::recommend;
+use crate::model::AppState;
+use crate::tui::theme;
+
+/// Best channels for a new AP, per band, with the reasons for each.
+pub fn render_recommendations(frame: &mut Frame, area: Rect, state: &AppState) {
+    let recommendations = recommend::recommend(state, state.time_window.duration());
+
+    let mut lines = Vec::new();
+    let mut band = "";
+    let mut rank = 0;
+    for rec in &recommendations {
+        if rec.band != band {
+            band = rec.band;
+            rank = 0;
+            lines.push(Line::styled(band, theme::TABLE_HEADER));
+        }
+        rank += 1;
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{rank}. ch {} @ {} MHz ", rec.channel, rec.width_mhz),
+                theme::TABLE_ROW,
+            ),
+            Span::styled(format!("({:.1}) ", rec.cost), theme::TABLE_ROW_DIM),
+            Span::styled(rec.reasons.join(", "), theme::TABLE_ROW_DIM),
+        ]));
+    }
+
+    if lines.is_empty() {
+        lines.push(Line::styled("No channels listened to in this window yet", theme::TABLE_ROW_DIM));
+    }
+
+    let block = Block::default()
+        .title(format!(" Channel Recommendations [{}] ", state.time_window))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::BORDER_COLOR));
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }).block(block), area);
+}
@@ -1,29 +1,49 @@
+use std::time::Duration;
+
 use ratatui::prelude::*;
 use ratatui::symbols;
 use ratatui::widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, GraphType};
 
-use crate::model::AppState;
+use crate::model::app_state::format_span;
+use crate::model::{AppState, GraphStat};
 use crate::tui::theme;
+use crate::util::time_series::{self, Bucket};
+
+/// Upper bound on buckets per window; roughly the chart's braille resolution.
+const MAX_BUCKETS: u32 = 120;
 
 pub fn render_signal_graph(frame: &mut Frame, area: Rect, state: &AppState) {
     let aps = state.sorted_aps();
     let top_aps: Vec<_> = aps.into_iter().take(5).collect();
 
-    let window_samples = state.time_window.sample_count();
+    let window = state.time_window.duration();
     let window_secs = state.time_window.seconds();
 
-    // Build datasets, only taking samples within the time window
-    let mut all_data: Vec<(String, Vec<(f64, f64)>, Color)> = Vec::new();
+    // A bucket narrower than the hop cycle would show every cycle we spent on
+    // other channels as a gap, so never go below it
+    let bucket_width = (window / MAX_BUCKETS).max(state.hop_cycle);
+    let bucket_count = ((window.as_secs_f64() / bucket_width.as_secs_f64()).ceil() as usize).max(1);
+    let now = state.now();
+
+    // Build one run of points per contiguous stretch of non-empty buckets
+    let mut all_data: Vec<(String, Vec<Vec<(f64, f64)>>, Color)> = Vec::new();
     let mut global_min: f64 = 0.0;
     let mut global_max: f64 = -120.0;
     let mut has_data = false;
 
     for (i, ap) in top_aps.iter().enumerate() {
         let color = theme::AP_COLORS[i % theme::AP_COLORS.len()];
-        let points = ap.signal_history.as_dataset_last_n(window_samples, |&v| v as f64);
+        let buckets = time_series::resample_history(
+            &ap.signal_history,
+            &ap.signal_rollups,
+            now,
+            bucket_width,
+            bucket_count,
+        );
+        let runs = bucket_runs(&buckets, bucket_width, state.graph_stat);
 
         // Track min/max across all visible data points
-        for &(_, y) in &points {
+        for &(_, y) in runs.iter().flatten() {
             if y < global_min {
                 global_min = y;
             }
@@ -40,7 +60,7 @@ pub fn render_signal_graph(frame: &mut Frame, area: Rect, state: &AppState) {
         } else {
             ap.ssid.clone()
         };
-        all_data.push((label, points, color));
+        all_data.push((label, runs, color));
     }
 
     // Dynamic Y bounds: pad by 5 dBm on each side, fall back to -100..-20 if no data
@@ -58,21 +78,30 @@ pub fn render_signal_graph(frame: &mut Frame, area: Rect, state: &AppState) {
         (-100.0, -20.0)
     };
 
-    let datasets: Vec<Dataset> = all_data
-        .iter()
-        .map(|(label, points, color)| {
-            Dataset::default()
-                .name(label.as_str())
+    let mut datasets: Vec<Dataset> = Vec::new();
+    for (label, runs, color) in &all_data {
+        for (i, points) in runs.iter().enumerate() {
+            // A lone bucket has nothing to connect to, so draw it as a dot
+            let graph_type = if points.len() == 1 {
+                GraphType::Scatter
+            } else {
+                GraphType::Line
+            };
+            let mut dataset = Dataset::default()
                 .marker(symbols::Marker::Braille)
-                .graph_type(GraphType::Line)
+                .graph_type(graph_type)
                 .style(Style::default().fg(*color))
-                .data(points)
-        })
-        .collect();
+                .data(points);
+            // Name only the first run so the legend has one entry per AP
+            if i == 0 {
+                dataset = dataset.name(label.as_str());
+            }
+            datasets.push(dataset);
+        }
+    }
 
-    let x_max = window_samples as f64;
-    let half_label = format!("{}s", window_secs / 2);
-    let full_label = format!("{}s", window_secs);
+    let half_label = format!("-{}", format_span(window_secs / 2));
+    let full_label = format!("-{}", format_span(window_secs));
 
     let x_labels: Vec<Line> = vec![full_label.into(), half_label.into(), "now".into()];
 
@@ -89,14 +118,19 @@ pub fn render_signal_graph(frame: &mut Frame, area: Rect, state: &AppState) {
     let chart = Chart::new(datasets)
         .block(
             Block::default()
-                .title(format!(" Signal Strength (dBm) [{window_secs}s] "))
+                .title(format!(
+                    " Signal Strength (dBm) [{}, {} per {:.1}s] ",
+                    state.time_window,
+                    state.graph_stat,
+                    bucket_width.as_secs_f64(),
+                ))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
                 .border_style(Style::default().fg(theme::BORDER_COLOR)),
         )
         .x_axis(
             Axis::default()
-                .bounds([0.0, x_max])
+                .bounds([-(window_secs as f64), 0.0])
                 .labels(x_labels),
         )
         .y_axis(
@@ -107,3 +141,32 @@ pub fn render_signal_graph(frame: &mut Frame, area: Rect, state: &AppState) {
 
     frame.render_widget(chart, area);
 }
+
+/// Split buckets into runs of consecutive non-empty buckets.
+///
+/// x is the bucket's centre in seconds relative to now (negative = past).
+fn bucket_runs(buckets: &[Option<Bucket>], width: Duration, stat: GraphStat) -> Vec<Vec<(f64, f64)>> {
+    let width = width.as_secs_f64();
+    let n = buckets.len();
+    let mut runs: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut current: Vec<(f64, f64)> = Vec::new();
+
+    for (i, bucket) in buckets.iter().enumerate() {
+        match bucket {
+            Some(b) => {
+                let x = -((n - 1 - i) as f64 + 0.5) * width;
+                let y = match stat {
+                    GraphStat::Mean => b.mean,
+                    GraphStat::Max => b.max,
+                };
+                current.push((x, y));
+            }
+            None if !current.is_empty() => runs.push(std::mem::take(&mut current)),
+            None => {}
+        }
+    }
+    if !current.is_empty() {
+        runs.push(current);
+    }
+    runs
+}
@@ -0,0 +1,123 @@
+use ratatui::prelude::*;
+use ratatui::symbols;
+use ratatui::widgets::{Axis, Block, BorderType, Borders, Chart, Dataset, GraphType};
+
+use crate::model::spectrum::{self, CHANNELS_2G, CHANNELS_5G};
+use crate::model::{AccessPoint, AppState};
+use crate::tui::theme;
+
+/// APs named in each band's legend; the rest are drawn unlabelled.
+const LEGEND_APS: usize = 5;
+
+/// Baseline the AP curves rise from.
+const FLOOR_DBM: f64 = -100.0;
+
+/// One AP's outline across its channel.
+struct Curve {
+    /// Legend text; only the first `LEGEND_APS` get one.
+    label: Option<String>,
+    points: Vec<(f64, f64)>,
+    color: Color,
+}
+
+/// Each AP as a span across its channel width, with height from its signal,
+/// so adjacent-channel overlap and bonded channels are visible.
+pub fn render_spectrum(frame: &mut Frame, area: Rect, state: &AppState) {
+    let bands = Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(area);
+    let aps = state.sorted_aps();
+
+    // 6 GHz channel numbers would land among the 2.4 GHz ones
+    let (low, high): (Vec<&AccessPoint>, Vec<&AccessPoint>) = aps
+        .iter()
+        .filter(|ap| ap.span().is_some() && !ap.six_ghz)
+        .partition(|ap| ap.channel <= 14);
+    render_band(frame, bands[0], "2.4 GHz", &low, &CHANNELS_2G, (2400.0, 2495.0));
+    render_band(frame, bands[1], "5 GHz", &high, &CHANNELS_5G, (5160.0, 5845.0));
+}
+
+fn render_band(
+    frame: &mut Frame,
+    area: Rect,
+    name: &str,
+    aps: &[&AccessPoint],
+    channels: &[u8],
+    (x_min, x_max): (f64, f64),
+) {
+    let curves: Vec<Curve> = aps
+        .iter()
+        .enumerate()
+        .filter_map(|(i, ap)| {
+            let span = ap.span()?;
+            let dbm = ap.signal_dbm as f64;
+            // A trapezoid: the skirts are a fixed 2 MHz so narrow channels stay visible
+            let points = vec![
+                (span.low_mhz(), FLOOR_DBM),
+                (span.low_mhz() + 2.0, dbm),
+                (span.high_mhz() - 2.0, dbm),
+                (span.high_mhz(), FLOOR_DBM),
+            ];
+            let label = (i < LEGEND_APS).then(|| format!("{} ({}MHz)", ap.display_ssid(), span.width_mhz));
+            Some(Curve {
+                label,
+                points,
+                color: theme::AP_COLORS[i % theme::AP_COLORS.len()],
+            })
+        })
+        .collect();
+
+    let datasets: Vec<Dataset> = curves
+        .iter()
+        .map(|curve| {
+            let dataset = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(curve.color))
+                .data(&curve.points);
+            // Any name, even an empty one, takes a legend line
+            match &curve.label {
+                Some(label) => dataset.name(label.clone()),
+                None => dataset,
+            }
+        })
+        .collect();
+
+    let scores: Vec<String> = spectrum::interference_scores(aps.iter().copied(), channels)
+        .into_iter()
+        .filter(|&(_, score)| score >= 0.05)
+        .map(|(ch, score)| format!("{ch}:{score:.1}"))
+        .collect();
+    let score_line = if scores.is_empty() {
+        " no interference ".to_string()
+    } else {
+        format!(" score {} ", scores.join(" "))
+    };
+
+    let x_labels: Vec<Span> = [x_min, (x_min + x_max) / 2.0, x_max]
+        .iter()
+        .map(|mhz| Span::raw(format!("{mhz:.0}")))
+        .collect();
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .title(format!(" Spectrum {name} "))
+                .title_bottom(Line::from(score_line).left_aligned())
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(theme::BORDER_COLOR)),
+        )
+        .x_axis(
+            Axis::default()
+                .bounds([x_min, x_max])
+                .labels(x_labels)
+                .style(Style::default().fg(theme::BORDER_COLOR)),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([FLOOR_DBM, -20.0])
+                .labels(vec![Span::raw("-100"), Span::raw("-60"), Span::raw("-20")])
+                .style(Style::default().fg(theme::BORDER_COLOR)),
+        );
+
+    frame.render_widget(chart, area);
+}
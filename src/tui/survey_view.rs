@@ -0,0 +1,80 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
+
+use crate::model::{AppState, HeatmapTarget};
+use crate::tui::theme;
+use crate::util::mac::format_mac;
+
+/// Each grid cell is drawn two columns wide so cells look roughly square.
+const CELL_WIDTH: u16 = 2;
+
+pub fn render_survey(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(survey) = &state.survey else {
+        return;
+    };
+    let plan = &survey.plan;
+    let target = state.heatmap_target();
+    let (cx, cy) = state.survey_cursor;
+
+    let target_label = match &target {
+        Some(HeatmapTarget::Bssid(bssid)) => format_mac(bssid),
+        Some(HeatmapTarget::Ssid(ssid)) => format!("SSID {ssid}"),
+        None => "no AP selected".to_string(),
+    };
+    let status = match survey.position {
+        Some((x, y)) => format!("recording at ({x},{y})"),
+        None => "not recording".to_string(),
+    };
+
+    let block = Block::default()
+        .title(format!(
+            " Site Survey: {target_label}  cursor ({cx},{cy})  {status}  [{} cells, {} samples] ",
+            survey.surveyed_cells(),
+            survey.samples.len(),
+        ))
+        .title_bottom(" ←↑↓→:move  enter:mark  x:stop  h:ssid/bssid  pgup/pgdn:select AP ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme::BORDER_COLOR));
+    let inner = block.inner(area);
+
+    // Scroll so the cursor stays visible on plans bigger than the panel
+    let visible_w = (inner.width / CELL_WIDTH) as usize;
+    let visible_h = inner.height as usize;
+    let x0 = scroll_offset(cx, visible_w, plan.width);
+    let y0 = scroll_offset(cy, visible_h, plan.height);
+
+    let lines: Vec<Line> = (y0..plan.height.min(y0 + visible_h))
+        .map(|y| {
+            let spans: Vec<Span> = (x0..plan.width.min(x0 + visible_w))
+                .map(|x| {
+                    let (text, mut style) = if plan.is_wall(x, y) {
+                        ("██", Style::default().fg(theme::SURVEY_WALL))
+                    } else if survey.position == Some((x, y)) {
+                        ("@ ", Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+                    } else {
+                        match target.as_ref().and_then(|t| survey.cell_signal(x, y, t)) {
+                            Some(dbm) => ("██", Style::default().fg(theme::heat_color(dbm))),
+                            None => ("· ", Style::default().fg(theme::SURVEY_EMPTY)),
+                        }
+                    };
+                    if (x, y) == (cx, cy) {
+                        style = style.add_modifier(Modifier::REVERSED);
+                    }
+                    Span::styled(text, style)
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn scroll_offset(cursor: usize, visible: usize, total: usize) -> usize {
+    if total <= visible {
+        0
+    } else {
+        cursor.saturating_sub(visible / 2).min(total - visible)
+    }
+}
@@ -1,5 +1,7 @@
 use ratatui::style::{Color, Modifier, Style};
 
+use crate::model::FrameCategory;
+
 pub const HEADER_BG: Color = Color::Rgb(40, 40, 60);
 pub const HEADER_FG: Color = Color::Rgb(180, 200, 255);
 
@@ -41,3 +43,28 @@ pub fn signal_color(dbm: i8) -> Color {
         SIGNAL_WEAK
     }
 }
+
+pub const SURVEY_WALL: Color = Color::Rgb(90, 90, 110);
+pub const SURVEY_EMPTY: Color = Color::Rgb(60, 60, 80);
+
+/// Heatmap colour: red at -90 dBm through yellow at -70 to green at -50 and above.
+pub fn heat_color(dbm: i8) -> Color {
+    let t = ((dbm as f32 + 90.0) / 40.0).clamp(0.0, 1.0);
+    if t < 0.5 {
+        let k = t * 2.0;
+        Color::Rgb(220, (60.0 + 140.0 * k) as u8, 60 - (60.0 * k) as u8)
+    } else {
+        let k = (t - 0.5) * 2.0;
+        Color::Rgb((220.0 - 220.0 * k) as u8, (200.0 + 20.0 * k) as u8, (80.0 * k) as u8)
+    }
+}
+
+/// Colour for a frame type in the packet rate view and breakdown table.
+pub fn frame_category_color(category: FrameCategory) -> Color {
+    match category {
+        FrameCategory::Management => Color::Rgb(255, 160, 40),
+        FrameCategory::Control => Color::Rgb(200, 140, 255),
+        FrameCategory::Data => Color::Rgb(80, 160, 255),
+        FrameCategory::Extension => Color::Rgb(180, 180, 60),
+    }
+}
@@ -0,0 +1,156 @@
+//! Time source for the model.
+//!
+//! Everything that ages, rates or expires takes its notion of "now" from a
+//! `Clock` on `AppState` rather than calling `Instant::now()`. Live captures
+//! use the monotonic clock; replays advance a fixed clock with each packet's
+//! capture timestamp, so ages and rates reflect when frames were captured, not
+//! when we got round to processing them.
+
+use std::time::{Duration, Instant, SystemTime};
+
+/// How far a live packet timestamp may drift from the monotonic clock before
+/// we re-anchor (e.g. after the system clock was stepped).
+const MAX_LIVE_SKEW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Wall,
+    Fixed(Instant),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    source: Source,
+    /// The same moment on the monotonic and system clocks, for converting
+    /// capture timestamps. Set from the first packet.
+    anchor: Option<(Instant, SystemTime)>,
+}
+
+impl Clock {
+    /// Live clock: `now()` follows the monotonic clock.
+    pub fn wall() -> Self {
+        Self {
+            source: Source::Wall,
+            anchor: None,
+        }
+    }
+
+    /// Clock that stands still at `start` until moved, by replayed packets or
+    /// by `set` (e.g. in tests).
+    pub fn fixed(start: Instant) -> Self {
+        Self {
+            source: Source::Fixed(start),
+            anchor: None,
+        }
+    }
+
+    pub fn now(&self) -> Instant {
+        match self.source {
+            Source::Wall => Instant::now(),
+            Source::Fixed(at) => at,
+        }
+    }
+
+    /// Move a fixed clock forward to `at`. It never runs backwards; a wall
+    /// clock ignores this.
+    pub fn set(&mut self, at: Instant) {
+        if let Source::Fixed(cur) = &mut self.source {
+            *cur = (*cur).max(at);
+        }
+    }
+
+    /// Convert a packet's capture timestamp to an `Instant` on this clock,
+    /// advancing a fixed clock to it.
+    pub fn packet_time(&mut self, ts: SystemTime) -> Instant {
+        let now = self.now();
+        let (anchor_at, anchor_ts) = *self.anchor.get_or_insert((now, ts));
+        let mut at = shift(anchor_at, anchor_ts, ts);
+
+        if let Source::Wall = self.source {
+            let skew = if at > now { at - now } else { now - at };
+            if skew > MAX_LIVE_SKEW {
+                self.anchor = Some((now, ts));
+                at = now;
+            }
+            // Frames are never from the future, whatever the kernel stamped
+            at = at.min(now);
+        }
+
+        self.set(at);
+        at
+    }
+
+    /// System time corresponding to `at`, for exports and display.
+    pub fn system_time(&self, at: Instant) -> SystemTime {
+        let (anchor_at, anchor_ts) = self
+            .anchor
+            .unwrap_or_else(|| (self.now(), SystemTime::now()));
+        match at.checked_duration_since(anchor_at) {
+            Some(after) => anchor_ts + after,
+            None => anchor_ts
+                .checked_sub(anchor_at - at)
+                .unwrap_or(anchor_ts),
+        }
+    }
+}
+
+/// The `Instant` that is as far from `from_at` as `to` is from `from`.
+fn shift(from_at: Instant, from: SystemTime, to: SystemTime) -> Instant {
+    match to.duration_since(from) {
+        Ok(after) => from_at + after,
+        Err(e) => from_at.checked_sub(e.duration()).unwrap_or(from_at),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock_moves_only_forward() {
+        let start = Instant::now();
+        let mut clock = Clock::fixed(start);
+        assert_eq!(clock.now(), start);
+
+        clock.set(start + Duration::from_secs(5));
+        assert_eq!(clock.now(), start + Duration::from_secs(5));
+        clock.set(start);
+        assert_eq!(clock.now(), start + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn fixed_clock_follows_packet_times() {
+        let start = Instant::now();
+        let mut clock = Clock::fixed(start);
+        let first = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        assert_eq!(clock.packet_time(first), start);
+        let later = clock.packet_time(first + Duration::from_millis(1500));
+        assert_eq!(later, start + Duration::from_millis(1500));
+        assert_eq!(clock.now(), later);
+
+        // An out-of-order packet maps before the clock without moving it back
+        let earlier = clock.packet_time(first + Duration::from_millis(500));
+        assert_eq!(earlier, start + Duration::from_millis(500));
+        assert_eq!(clock.now(), later);
+    }
+
+    #[test]
+    fn system_time_round_trips() {
+        let start = Instant::now();
+        let mut clock = Clock::fixed(start);
+        let first = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        clock.packet_time(first);
+
+        assert_eq!(clock.system_time(start + Duration::from_secs(3)), first + Duration::from_secs(3));
+        assert_eq!(clock.system_time(start), first);
+    }
+
+    #[test]
+    fn wall_clock_ignores_set() {
+        let mut clock = Clock::wall();
+        let before = Instant::now();
+        clock.set(before + Duration::from_secs(3600));
+        assert!(clock.now() < before + Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,25 @@
+/// Format a MAC address as colon-separated uppercase hex.
+pub fn format_mac(mac: &[u8; 6]) -> String {
+    format!(
+        "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+        mac[0], mac[1], mac[2], mac[3], mac[4], mac[5],
+    )
+}
+
+/// Parse a colon- or dash-separated MAC address (case-insensitive).
+pub fn parse_mac(s: &str) -> Option<[u8; 6]> {
+    let mut mac = [0u8; 6];
+    let mut parts = s.trim().split([':', '-']);
+    for byte in mac.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(mac)
+}
+
+/// True for group (broadcast/multicast) addresses.
+pub fn is_multicast(mac: &[u8; 6]) -> bool {
+    mac[0] & 0x01 != 0
+}
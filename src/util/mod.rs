@@ -1 +1,9 @@
+pub mod clock;
+pub mod crc32;
+pub mod mac;
+pub mod pcap_file;
+pub mod pcapng;
 pub mod ring_buffer;
+pub mod serde_instant;
+pub mod time;
+pub mod time_series;
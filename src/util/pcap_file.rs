@@ -0,0 +1,40 @@
+//! Minimal classic (libpcap) capture file writer.
+
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PCAP_MAGIC: u32 = 0xA1B2_C3D4;
+const SNAPLEN: u32 = 65_535;
+
+pub struct PcapWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Write the file header for frames of link type `dlt`, with
+    /// microsecond timestamps.
+    pub fn new(mut out: W, dlt: i32) -> io::Result<Self> {
+        out.write_all(&PCAP_MAGIC.to_le_bytes())?;
+        out.write_all(&2u16.to_le_bytes())?;
+        out.write_all(&4u16.to_le_bytes())?;
+        out.write_all(&0i32.to_le_bytes())?;
+        out.write_all(&0u32.to_le_bytes())?;
+        out.write_all(&SNAPLEN.to_le_bytes())?;
+        out.write_all(&(dlt as u32).to_le_bytes())?;
+        Ok(Self { out })
+    }
+
+    pub fn write_packet(&mut self, ts: SystemTime, data: &[u8]) -> io::Result<()> {
+        let since_epoch = ts.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let len = data.len().min(SNAPLEN as usize);
+        self.out.write_all(&(since_epoch.as_secs() as u32).to_le_bytes())?;
+        self.out.write_all(&since_epoch.subsec_micros().to_le_bytes())?;
+        self.out.write_all(&(len as u32).to_le_bytes())?;
+        self.out.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.out.write_all(&data[..len])
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,111 @@
+//! Minimal pcapng writer: one section, one interface, enhanced packet blocks.
+
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+pub struct PcapngWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Write the section and interface headers for frames of link type `dlt`.
+    /// Timestamps use the default microsecond resolution.
+    pub fn new(mut out: W, dlt: i32) -> io::Result<Self> {
+        // Section header: no options, section length unknown
+        write_block(&mut out, SECTION_HEADER_BLOCK, |body| {
+            body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+            body.extend_from_slice(&1u16.to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes());
+            body.extend_from_slice(&(-1i64).to_le_bytes());
+        })?;
+        // Interface description: no snap length limit, no options
+        write_block(&mut out, INTERFACE_DESCRIPTION_BLOCK, |body| {
+            body.extend_from_slice(&(dlt as u16).to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes());
+            body.extend_from_slice(&0u32.to_le_bytes());
+        })?;
+        Ok(Self { out })
+    }
+
+    pub fn write_packet(&mut self, ts: SystemTime, data: &[u8]) -> io::Result<()> {
+        let micros = ts.duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0);
+        write_block(&mut self.out, ENHANCED_PACKET_BLOCK, |body| {
+            body.extend_from_slice(&0u32.to_le_bytes());
+            body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+            body.extend_from_slice(&(micros as u32).to_le_bytes());
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(data);
+        })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Write a block: type, total length, body padded to 32 bits, total length again.
+fn write_block<W: Write>(out: &mut W, block_type: u32, fill: impl FnOnce(&mut Vec<u8>)) -> io::Result<()> {
+    let mut body = Vec::new();
+    fill(&mut body);
+    body.resize(body.len().next_multiple_of(4), 0);
+    let total_len = (body.len() + 12) as u32;
+    out.write_all(&block_type.to_le_bytes())?;
+    out.write_all(&total_len.to_le_bytes())?;
+    out.write_all(&body)?;
+    out.write_all(&total_len.to_le_bytes())
+}
+
+/// Split a pcapng file into (block type, body) pairs, checking that each
+/// block's leading and trailing lengths agree.
+#[cfg(test)]
+pub fn read_blocks(mut data: &[u8]) -> Vec<(u32, &[u8])> {
+    let word = |bytes: &[u8], at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+    let mut blocks = Vec::new();
+    while !data.is_empty() {
+        let total_len = word(data, 4) as usize;
+        assert!(total_len >= 12 && total_len.is_multiple_of(4), "bad block length {total_len}");
+        assert_eq!(word(data, total_len - 4) as usize, total_len);
+        blocks.push((word(data, 0), &data[8..total_len - 4]));
+        data = &data[total_len..];
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn blocks_are_padded_and_framed() {
+        let mut out = Vec::new();
+        let mut writer = PcapngWriter::new(&mut out, 127).unwrap();
+        let ts = UNIX_EPOCH + Duration::from_micros(0x1_0000_0002);
+        writer.write_packet(ts, &[0xAB; 5]).unwrap();
+        writer.write_packet(ts, &[]).unwrap();
+
+        let blocks = read_blocks(&out);
+        let types: Vec<u32> = blocks.iter().map(|(block_type, _)| *block_type).collect();
+        assert_eq!(
+            types,
+            [SECTION_HEADER_BLOCK, INTERFACE_DESCRIPTION_BLOCK, ENHANCED_PACKET_BLOCK, ENHANCED_PACKET_BLOCK]
+        );
+        assert_eq!(&blocks[0].1[..4], &BYTE_ORDER_MAGIC.to_le_bytes());
+        assert_eq!(&blocks[1].1[..2], &127u16.to_le_bytes());
+
+        let (_, packet) = blocks[2];
+        // Interface, timestamp high/low, captured and original lengths, 5 bytes padded to 8
+        assert_eq!(packet.len(), 20 + 8);
+        assert_eq!(&packet[4..12], &[1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(&packet[12..20], &[5, 0, 0, 0, 5, 0, 0, 0]);
+        assert_eq!(&packet[20..], &[0xAB, 0xAB, 0xAB, 0xAB, 0xAB, 0, 0, 0]);
+        assert_eq!(blocks[3].1.len(), 20);
+    }
+}
@@ -1,56 +1,43 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 /// Fixed-size circular buffer for time-series data.
+///
+/// Storage grows as items arrive, up to `capacity`, so long histories cost
+/// nothing until they fill. Serializes as its capacity and the stored items,
+/// oldest first, so a mostly empty buffer stays small on the wire.
 pub struct RingBuffer<T> {
-    data: Vec<Option<T>>,
+    data: VecDeque<T>,
     capacity: usize,
-    write_pos: usize,
-    len: usize,
 }
 
 impl<T: Clone> RingBuffer<T> {
     pub fn new(capacity: usize) -> Self {
         Self {
-            data: vec![None; capacity],
+            data: VecDeque::new(),
             capacity,
-            write_pos: 0,
-            len: 0,
         }
     }
 
     pub fn push(&mut self, value: T) {
-        self.data[self.write_pos] = Some(value);
-        self.write_pos = (self.write_pos + 1) % self.capacity;
-        if self.len < self.capacity {
-            self.len += 1;
+        if self.data.len() == self.capacity {
+            self.data.pop_front();
         }
+        self.data.push_back(value);
     }
 
     pub fn latest(&self) -> Option<&T> {
-        if self.len == 0 {
-            return None;
-        }
-        let idx = if self.write_pos == 0 {
-            self.capacity - 1
-        } else {
-            self.write_pos - 1
-        };
-        self.data[idx].as_ref()
+        self.data.back()
     }
 
     /// Returns items in chronological order (oldest first).
-    pub fn iter(&self) -> impl Iterator<Item = &T> {
-        let start = if self.len < self.capacity {
-            0
-        } else {
-            self.write_pos
-        };
-        (0..self.len).filter_map(move |i| {
-            let idx = (start + i) % self.capacity;
-            self.data[idx].as_ref()
-        })
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.data.iter()
     }
 
     pub fn len(&self) -> usize {
-        self.len
+        self.data.len()
     }
 
     pub fn capacity(&self) -> usize {
@@ -65,16 +52,66 @@ impl<T: Clone> RingBuffer<T> {
             .map(|(i, v)| (i as f64, map_fn(v)))
             .collect()
     }
+}
 
-    /// Like `as_dataset` but only returns the last `n` samples.
-    /// x values are 0..n (re-indexed so the window always starts at 0).
-    pub fn as_dataset_last_n<F: Fn(&T) -> f64>(&self, n: usize, map_fn: F) -> Vec<(f64, f64)> {
-        let items: Vec<&T> = self.iter().collect();
-        let skip = items.len().saturating_sub(n);
-        items[skip..]
-            .iter()
-            .enumerate()
-            .map(|(i, v)| (i as f64, map_fn(v)))
-            .collect()
+#[derive(Serialize)]
+struct RingBufferRef<'a, T> {
+    capacity: usize,
+    items: Vec<&'a T>,
+}
+
+#[derive(Deserialize)]
+struct RingBufferOwned<T> {
+    capacity: usize,
+    items: Vec<T>,
+}
+
+impl<T: Clone + Serialize> Serialize for RingBuffer<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RingBufferRef {
+            capacity: self.capacity,
+            items: self.iter().collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: Clone + Deserialize<'de>> Deserialize<'de> for RingBuffer<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let owned = RingBufferOwned::<T>::deserialize(deserializer)?;
+        if owned.capacity == 0 {
+            return Err(serde::de::Error::custom("ring buffer capacity must be nonzero"));
+        }
+        let mut buffer = RingBuffer::new(owned.capacity);
+        for item in owned.items {
+            buffer.push(item);
+        }
+        Ok(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_only_stored_items() {
+        let mut buffer = RingBuffer::new(1200);
+        buffer.push(1u32);
+        buffer.push(2);
+        assert_eq!(serde_json::to_string(&buffer).unwrap(), r#"{"capacity":1200,"items":[1,2]}"#);
+    }
+
+    #[test]
+    fn round_trips_after_wrapping() {
+        let mut buffer = RingBuffer::new(3);
+        for value in 0..5u32 {
+            buffer.push(value);
+        }
+        let json = serde_json::to_string(&buffer).unwrap();
+        let decoded: RingBuffer<u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.capacity(), 3);
+        assert_eq!(decoded.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(decoded.latest(), Some(&4));
     }
 }
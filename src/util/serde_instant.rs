@@ -0,0 +1,19 @@
+//! Serde adapter that sends an `Instant` as "milliseconds ago".
+//!
+//! `Instant` has no meaning outside the process that created it, so snapshots
+//! carry ages instead and the receiver rebuilds an `Instant` relative to its
+//! own clock.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(instant.elapsed().as_millis() as u64)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+    let age_ms = u64::deserialize(deserializer)?;
+    let now = Instant::now();
+    Ok(now.checked_sub(Duration::from_millis(age_ms)).unwrap_or(now))
+}
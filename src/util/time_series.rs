@@ -0,0 +1,330 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::ring_buffer::RingBuffer;
+
+/// Width and length of the fine rollup tier: 10 s slots for 6 hours.
+const FINE_WIDTH: Duration = Duration::from_secs(10);
+const FINE_CAP: usize = 6 * 360;
+
+/// Width and length of the coarse rollup tier: 1 min slots for 3 days.
+const COARSE_WIDTH: Duration = Duration::from_secs(60);
+const COARSE_CAP: usize = 3 * 1440;
+
+/// A value tagged with the time it was observed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimedSample<T> {
+    #[serde(with = "crate::util::serde_instant")]
+    pub at: Instant,
+    pub value: T,
+}
+
+/// Aggregate of the samples that fell into one fixed-width time slot.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rollup {
+    #[serde(with = "crate::util::serde_instant")]
+    pub start: Instant,
+    pub max: f32,
+    pub sum: f32,
+    pub count: u32,
+}
+
+impl Rollup {
+    fn single(at: Instant, value: f32) -> Self {
+        Self {
+            start: at,
+            max: value,
+            sum: value,
+            count: 1,
+        }
+    }
+}
+
+/// Consecutive rollups of one width, oldest first.
+pub struct RollupTier {
+    pub width: Duration,
+    /// Slot still being filled.
+    current: Option<Rollup>,
+    done: RingBuffer<Rollup>,
+    /// Rollups closed so far, including ones the ring has since dropped.
+    closed: u64,
+}
+
+/// Rollups a tier closed since an earlier sync, plus its open slot.
+#[derive(Serialize, Deserialize)]
+pub struct TierDelta {
+    /// `done` replaces the receiver's rollups instead of extending them.
+    full: bool,
+    done: Vec<Rollup>,
+    current: Option<Rollup>,
+}
+
+impl RollupTier {
+    pub fn new(width: Duration, capacity: usize) -> Self {
+        Self {
+            width,
+            current: None,
+            done: RingBuffer::new(capacity),
+            closed: 0,
+        }
+    }
+
+    pub fn add(&mut self, at: Instant, value: f32) {
+        if let Some(cur) = &mut self.current {
+            let elapsed = at.saturating_duration_since(cur.start);
+            if elapsed < self.width {
+                cur.max = cur.max.max(value);
+                cur.sum += value;
+                cur.count += 1;
+                return;
+            }
+            // Close the slot and open the one `at` falls into, keeping slots aligned
+            let skipped = (elapsed.as_nanos() / self.width.as_nanos()) as u32;
+            let start = cur.start + self.width * skipped;
+            self.done.push(*cur);
+            self.closed += 1;
+            self.current = Some(Rollup::single(start, value));
+        } else {
+            self.current = Some(Rollup::single(at, value));
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Rollup> {
+        self.done.iter().chain(self.current.iter())
+    }
+
+    /// How far back this tier reaches once full.
+    pub fn span(&self) -> Duration {
+        self.width * self.done.capacity() as u32
+    }
+
+    /// What changed since `sent` rollups had been closed, updating `sent`.
+    /// A tier that was recreated or overran its ring since is sent whole.
+    pub fn delta(&self, sent: &mut u64) -> TierDelta {
+        let new = self.closed.checked_sub(*sent).and_then(|n| usize::try_from(n).ok());
+        let (full, count) = match new {
+            Some(n) if n <= self.done.len() => (false, n),
+            _ => (true, self.done.len()),
+        };
+        *sent = self.closed;
+        TierDelta {
+            full,
+            done: self.done.iter().skip(self.done.len() - count).copied().collect(),
+            current: self.current,
+        }
+    }
+
+    pub fn apply(&mut self, delta: TierDelta) {
+        if delta.full {
+            self.done = RingBuffer::new(self.done.capacity());
+        }
+        for rollup in delta.done {
+            self.done.push(rollup);
+            self.closed += 1;
+        }
+        self.current = delta.current;
+    }
+}
+
+/// 10 s rollups for hours plus 1 min rollups for days.
+pub struct RollupTiers {
+    pub fine: RollupTier,
+    pub coarse: RollupTier,
+}
+
+impl Default for RollupTiers {
+    fn default() -> Self {
+        Self {
+            fine: RollupTier::new(FINE_WIDTH, FINE_CAP),
+            coarse: RollupTier::new(COARSE_WIDTH, COARSE_CAP),
+        }
+    }
+}
+
+impl RollupTiers {
+    pub fn add(&mut self, at: Instant, value: f32) {
+        self.fine.add(at, value);
+        self.coarse.add(at, value);
+    }
+
+    /// Start of the newest slot, if anything was recorded.
+    pub fn last_at(&self) -> Option<Instant> {
+        self.coarse.iter().last().map(|r| r.start)
+    }
+
+    pub fn delta(&self, sent: &mut TiersSent) -> TiersDelta {
+        TiersDelta {
+            fine: self.fine.delta(&mut sent.fine),
+            coarse: self.coarse.delta(&mut sent.coarse),
+        }
+    }
+
+    pub fn apply(&mut self, delta: TiersDelta) {
+        self.fine.apply(delta.fine);
+        self.coarse.apply(delta.coarse);
+    }
+
+    /// The finest tier that reaches back over `window`.
+    pub fn for_window(&self, window: Duration) -> &RollupTier {
+        if window <= self.fine.span() {
+            &self.fine
+        } else {
+            &self.coarse
+        }
+    }
+}
+
+/// How many closed rollups of each tier a receiver has been sent.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TiersSent {
+    fine: u64,
+    coarse: u64,
+}
+
+/// Changes to both tiers since an earlier sync.
+#[derive(Serialize, Deserialize)]
+pub struct TiersDelta {
+    fine: TierDelta,
+    coarse: TierDelta,
+}
+
+/// Sample values that can be folded into rollups.
+pub trait SampleValue: Copy {
+    fn to_f32(self) -> f32;
+}
+
+impl SampleValue for i8 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+impl SampleValue for u64 {
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+}
+
+/// Summary of the samples that fell into one time bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct Bucket {
+    pub max: f64,
+    pub mean: f64,
+    pub sum: f64,
+}
+
+/// Resample raw samples, or rollups when the raw ring doesn't reach back far
+/// enough, into `count` buckets of `width` ending at `now`.
+pub fn resample_history<T>(
+    raw: &RingBuffer<TimedSample<T>>,
+    rollups: &RollupTiers,
+    now: Instant,
+    width: Duration,
+    count: usize,
+) -> Vec<Option<Bucket>>
+where
+    T: SampleValue,
+{
+    let window = width * count as u32;
+    // A ring that never wrapped still holds everything recorded so far
+    let raw_covers = raw.len() < raw.capacity()
+        || raw
+            .iter()
+            .next()
+            .is_some_and(|oldest| now.saturating_duration_since(oldest.at) >= window);
+
+    if raw_covers {
+        let samples = raw
+            .iter()
+            .map(|s| Rollup::single(s.at, s.value.to_f32()));
+        resample(samples, now, width, count)
+    } else {
+        resample(rollups.for_window(window).iter().copied(), now, width, count)
+    }
+}
+
+/// Resample time-stamped rollups into `count` fixed-width buckets ending at `now`.
+///
+/// Returns buckets oldest first; a bucket with no samples is `None` so callers
+/// can draw it as a gap instead of interpolating across it.
+pub fn resample(
+    rollups: impl Iterator<Item = Rollup>,
+    now: Instant,
+    width: Duration,
+    count: usize,
+) -> Vec<Option<Bucket>> {
+    let mut sums = vec![(f64::MIN, 0.0, 0u32); count];
+    let span = width * count as u32;
+
+    for r in rollups {
+        let age = now.saturating_duration_since(r.start);
+        if age >= span {
+            continue;
+        }
+        // Index from the newest end: age 0 lands in the last bucket
+        let from_end = (age.as_nanos() / width.as_nanos().max(1)) as usize;
+        let (max, sum, n) = &mut sums[count - 1 - from_end];
+        *max = max.max(r.max as f64);
+        *sum += r.sum as f64;
+        *n += r.count;
+    }
+
+    sums.into_iter()
+        .map(|(max, sum, n)| {
+            (n > 0).then(|| Bucket {
+                max,
+                mean: sum / n as f64,
+                sum,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn starts(tier: &RollupTier) -> Vec<Instant> {
+        tier.iter().map(|r| r.start).collect()
+    }
+
+    #[test]
+    fn deltas_rebuild_the_tier() {
+        let t0 = Instant::now();
+        let mut source = RollupTier::new(Duration::from_secs(10), 4);
+        let mut copy = RollupTier::new(Duration::from_secs(10), 4);
+        let mut sent = 0;
+
+        for secs in [0, 5, 12, 25] {
+            source.add(t0 + Duration::from_secs(secs), 1.0);
+        }
+        let first = source.delta(&mut sent);
+        assert_eq!(first.done.len(), 2);
+        copy.apply(first);
+        assert_eq!(starts(&copy), starts(&source));
+
+        source.add(t0 + Duration::from_secs(31), 2.0);
+        let second = source.delta(&mut sent);
+        assert!(!second.full);
+        assert_eq!(second.done.len(), 1);
+        copy.apply(second);
+        assert_eq!(starts(&copy), starts(&source));
+        assert_eq!(copy.iter().last().map(|r| r.sum), Some(2.0));
+    }
+
+    #[test]
+    fn overrun_sends_whole_tier() {
+        let t0 = Instant::now();
+        let mut source = RollupTier::new(Duration::from_secs(10), 2);
+        let mut sent = 0;
+        source.delta(&mut sent);
+        for secs in (0..60).step_by(10) {
+            source.add(t0 + Duration::from_secs(secs), 1.0);
+        }
+        let delta = source.delta(&mut sent);
+        assert!(delta.full);
+        assert_eq!(delta.done.len(), 2);
+        assert_eq!(sent, 5);
+    }
+}